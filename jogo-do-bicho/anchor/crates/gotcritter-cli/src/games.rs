@@ -0,0 +1,187 @@
+// Finds and describes games, with the same filters used by `findGames` on anchor/src/gotcritter-exports.ts
+use anchor_lang::Discriminator;
+use gotcritter_client::{Bet, Game, PrizeTier};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{hash::Hash, native_token::lamports_to_sol, pubkey::Pubkey};
+
+const BET_GAME_OFFSET: usize = 8; // discriminator
+const BET_BETTOR_OFFSET: usize = 8 + 32; // discriminator + game
+const GAME_PARTICIPANTS_COUNT_OFFSET: usize = 8 + 396; // discriminator + the fields before participants_count
+
+#[derive(Clone, Copy, Default)]
+pub struct GameFilter {
+    pub public: bool,    // only games without participants
+    pub open: bool,      // only games that are still accepting bets
+    pub closeable: bool, // only games whose betting period can be ended right now
+}
+
+impl GameFilter {
+    // The filters applied by the RPC node, the remaining ones are checked by `matches`
+    pub fn rpc_filters(&self) -> Vec<RpcFilterType> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &Game::DISCRIMINATOR))];
+        if self.public {
+            // 2 bytes representing no participants
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(GAME_PARTICIPANTS_COUNT_OFFSET, &[0, 0])));
+        }
+        filters
+    }
+
+    pub fn matches(&self, game: &Game, slot: u64, timestamp: i64) -> bool {
+        (!self.public || !game.is_private())
+            && (!self.open || !game.is_betting_period_ended())
+            && (!self.closeable || is_closeable(game, slot, timestamp))
+    }
+}
+
+// The filters to find the bets of a bettor on a game
+pub fn bet_filters(game: &Pubkey, bettor: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &Bet::DISCRIMINATOR)),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(BET_GAME_OFFSET, game.as_ref())),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(BET_BETTOR_OFFSET, bettor.as_ref())),
+    ]
+}
+
+// A bet on a Special Block ends the betting period once the minimum ending slot (or timestamp) is reached
+// and every number has a bet
+pub fn is_closeable(game: &Game, slot: u64, timestamp: i64) -> bool {
+    !game.is_betting_period_ended() && game.is_min_ending_reached(slot, timestamp) && all_numbers_have_bets(game)
+}
+
+pub fn all_numbers_have_bets(game: &Game) -> bool {
+    game.bets_per_number.iter().all(|&value| value > 0)
+}
+
+// A Special Block is a block whose hash ends with 2 equal hex digits
+pub fn is_special_blockhash(blockhash: &Hash) -> bool {
+    let last_byte = blockhash.to_bytes()[31];
+    last_byte >> 4 == last_byte & 0x0f
+}
+
+// One line summary used by `list`
+pub fn summary(address: &Pubkey, game: &Game) -> String {
+    let numbers_with_bets = game.bets_per_number.iter().filter(|&&value| value > 0).count();
+    format!(
+        "{} {:>12} SOL  {:>2}/25 numbers  {:>4} bets  min ending slot {}  {}{}",
+        address,
+        lamports_to_sol(game.total_value),
+        numbers_with_bets,
+        game.number_of_bets,
+        game.min_ending_slot,
+        if game.is_private() { "private" } else { "public" },
+        if game.is_betting_period_ended() { "  ended" } else { "" },
+    )
+}
+
+// Full description used by `inspect`
+pub fn describe(address: &Pubkey, game: &Game, slot: u64, timestamp: i64) -> String {
+    let mut lines = vec![
+        format!("Game:                   {}", address),
+        format!("Creator:                {}", game.creator),
+    ];
+    if let Some((first, others)) = game.participants().split_first() {
+        lines.push(format!("Participants:           {}", first));
+        for participant in others {
+            lines.push(format!("                        {}", participant));
+        }
+    } else {
+        lines.push("Participants:           anyone (public game)".to_string());
+    }
+    lines.push(format!("Total value:            {} SOL", lamports_to_sol(game.total_value)));
+    lines.push(format!("Number of bets:         {}", game.number_of_bets));
+    if game.starts_at != 0 {
+        lines.push(format!("Starts at:              {} (now {})", game.starts_at, timestamp));
+    }
+    if game.min_ending_timestamp != 0 {
+        lines.push(format!("Min ending timestamp:   {} (now {})", game.min_ending_timestamp, timestamp));
+    } else {
+        lines.push(format!("Min ending slot:        {} (current slot {})", game.min_ending_slot, slot));
+    }
+    if game.neighbour_prize_bps != 0 || game.group_prize_bps != 0 {
+        lines.push(format!(
+            "Prize tiers:            {}% exact, {}% neighbours, {}% group",
+            game.tier_bps(PrizeTier::Exact) as f64 / 100.0,
+            game.neighbour_prize_bps as f64 / 100.0,
+            game.group_prize_bps as f64 / 100.0
+        ));
+    }
+    lines.push(format!("Betting period ended:   {}", game.is_betting_period_ended()));
+    lines.push(format!("Layout version:         {}", game.version));
+    if let Some(drawn_number) = game.confirmed_drawn_number() {
+        lines.push(format!("Drawn number:           {}", drawn_number));
+        lines.push(format!("Paid to winners:        {} SOL", lamports_to_sol(game.value_provided_to_winners)));
+    } else if is_closeable(game, slot, timestamp) {
+        lines.push("Closeable:              yes, the next bet on a Special Block ends the betting period".to_string());
+    }
+    lines.push("Value per number:".to_string());
+    for (index, (value, bets)) in game.bets_per_number.iter().zip(game.bettors_per_number).enumerate() {
+        let share = if game.total_value > 0 { *value as f64 * 100.0 / game.total_value as f64 } else { 0.0 };
+        lines.push(format!("  {:>2}: {:>12} SOL ({:.1}%) in {} bets", index + 1, lamports_to_sol(*value), share, bets));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn game(bets_per_number: [u64; 25], min_ending_slot: u64) -> Game {
+        let mut game = Game::zeroed();
+        game.creator = Pubkey::new_unique();
+        game.total_value = bets_per_number.iter().sum();
+        game.min_ending_slot = min_ending_slot;
+        game.bets_per_number = bets_per_number;
+        game.bettors_per_number = bets_per_number.map(|value| (value > 0) as u32);
+        game.number_of_bets = bets_per_number.iter().filter(|&&value| value > 0).count() as u64;
+        game.version = 1;
+        game
+    }
+
+    #[test]
+    fn special_blockhashes_end_with_2_equal_hex_digits() {
+        let mut bytes = [0x12; 32];
+        for (last_byte, special) in [(0x00, true), (0x77, true), (0xff, true), (0x12, false), (0x0f, false)] {
+            bytes[31] = last_byte;
+            assert_eq!(is_special_blockhash(&Hash::new_from_array(bytes)), special);
+        }
+    }
+
+    #[test]
+    fn closeable_games_need_every_number_and_the_min_ending_slot() {
+        let mut filled = game([1; 25], 100);
+        assert!(is_closeable(&filled, 100, 0));
+        assert!(!is_closeable(&filled, 99, 0));
+
+        let mut missing_number = [1; 25];
+        missing_number[24] = 0;
+        assert!(!is_closeable(&game(missing_number, 100), 100, 0));
+
+        filled.betting_period_ended = 1;
+        assert!(!is_closeable(&filled, 100, 0));
+    }
+
+    #[test]
+    fn closeable_games_with_a_deadline_ignore_the_slot() {
+        let mut filled = game([1; 25], 0);
+        filled.min_ending_timestamp = 1_700_000_000;
+        assert!(!is_closeable(&filled, 100, 1_699_999_999));
+        assert!(is_closeable(&filled, 0, 1_700_000_000));
+    }
+
+    #[test]
+    fn filters_private_and_ended_games() {
+        let mut private = game([1; 25], 0);
+        private.set_participants(&[Pubkey::new_unique()]).unwrap();
+        let mut ended = game([1; 25], 0);
+        ended.betting_period_ended = 1;
+
+        let public = GameFilter { public: true, ..Default::default() };
+        assert!(!public.matches(&private, 0, 0));
+        assert!(public.matches(&ended, 0, 0));
+
+        let open = GameFilter { open: true, ..Default::default() };
+        assert!(open.matches(&private, 0, 0));
+        assert!(!open.matches(&ended, 0, 0));
+    }
+}
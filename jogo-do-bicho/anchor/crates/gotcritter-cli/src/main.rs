@@ -0,0 +1,461 @@
+// Command line tool to operate gotcritter games, the Rust counterpart of scripts/fill-game.ts and
+// scripts/game-recycler.ts
+//
+// to run:
+// cargo run -p gotcritter-cli -- --help
+mod games;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use games::GameFilter;
+use gotcritter_client::events::{parse_logs, GotcritterEvent};
+use gotcritter_client::{accounts, instructions, pda, Bet, BettingWindow, Game, PrizeTiers};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
+use solana_sdk::{
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use std::{thread::sleep, time::Duration};
+
+const MIN_BET_VALUE: u64 = 10_000_000; // same as the program
+const SLOT_DURATION: Duration = Duration::from_millis(400); // the average time it takes to process a block
+const PARTICIPANTS_PER_TRANSACTION: usize = 20; // more participants than this don't fit in a transaction
+
+// FOR PRODUCTION
+// // around 12 HOURS / 400ms
+// const DEFAULT_GAME_DURATION: u64 = 12 * 60 * 60 * 1000 / 400;
+
+// OR FOR TESTING
+// around 5 minutes / 400ms
+const DEFAULT_GAME_DURATION: u64 = 5 * 60 * 1000 / 400;
+
+#[derive(Parser)]
+#[command(version, about = "Operate gotcritter games")]
+struct Cli {
+    /// RPC URL of the cluster
+    #[arg(short, long, global = true, default_value = "http://localhost:8899")]
+    url: String,
+
+    /// Keypair file of the wallet that pays and signs the transactions
+    #[arg(short, long, global = true, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new game
+    CreateGame {
+        /// Minimum number of slots of the betting period
+        #[arg(long, default_value_t = DEFAULT_GAME_DURATION)]
+        slots: u64,
+        /// Unix timestamp of the end of the betting window, instead of the slots
+        #[arg(long, conflicts_with = "slots")]
+        until: Option<i64>,
+        /// Unix timestamp before which bets are rejected, needs the end of the betting window
+        #[arg(long, requires = "until")]
+        starts_at: Option<i64>,
+        /// Makes the game private, only the participants can bet. Can be repeated, up to 64 times
+        #[arg(long = "participant")]
+        participants: Vec<Pubkey>,
+        /// Basis points of the pool for the bets next to the drawn number
+        #[arg(long, default_value_t = 0)]
+        neighbour_prize_bps: u16,
+        /// Basis points of the pool for the other bets on the group of 5 numbers of the drawn number
+        #[arg(long, default_value_t = 0)]
+        group_prize_bps: u16,
+    },
+    /// Bet on a number of a game
+    Bet {
+        game: Pubkey,
+        /// Number between 1 and 25
+        number: u8,
+        /// Value of the bet in SOL
+        sol: f64,
+        /// Wallet of who brought you, receives a slice of the bet
+        #[arg(long)]
+        referrer: Option<Pubkey>,
+    },
+    /// Bet on every number of a game that has no bet yet
+    Fill {
+        game: Pubkey,
+        /// Value of each bet in SOL
+        #[arg(long, default_value_t = 0.01)]
+        sol: f64,
+    },
+    /// Wait for a Special Block and bet on it to end the betting period, earning the reward
+    ClosePeriod {
+        game: Pubkey,
+        /// The number of the bet used to close, it is only registered if the period doesn't end
+        #[arg(long, default_value_t = 1)]
+        number: u8,
+    },
+    /// Claim the prizes of bets
+    Claim {
+        /// The bets to claim
+        bets: Vec<Pubkey>,
+        /// Claims every winning bet of the wallet on this game
+        #[arg(long, conflicts_with = "bets", required_unless_present = "bets")]
+        game: Option<Pubkey>,
+    },
+    /// List the games of the program
+    List(ListArgs),
+    /// Show a game with the value bet on each number
+    Inspect { game: Pubkey },
+    /// Close the closeable public games and create a new one when there is no public game open
+    Recycle {
+        /// Keep running, checking the games every interval
+        #[arg(long)]
+        daemon: bool,
+        /// Seconds between the checks of the daemon
+        #[arg(long, default_value_t = 15)]
+        interval: u64,
+        /// Minimum number of slots of the betting period of the created games
+        #[arg(long, default_value_t = DEFAULT_GAME_DURATION)]
+        slots: u64,
+    },
+    /// Move the games and bets created by older versions of the program to the current layout, paying the extra rent
+    Migrate,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    /// Only games still accepting bets
+    #[arg(long)]
+    open: bool,
+    /// Only games whose betting period can be ended now
+    #[arg(long)]
+    closeable: bool,
+    /// Only public games
+    #[arg(long)]
+    public: bool,
+}
+
+struct Cluster {
+    client: RpcClient,
+    payer: Keypair,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let keypair_path = match cli.keypair.strip_prefix("~/") {
+        Some(path) => format!("{}/{}", std::env::var("HOME")?, path),
+        None => cli.keypair.clone(),
+    };
+    let payer = read_keypair_file(&keypair_path).map_err(|error| anyhow!("could not read the keypair {}: {}", keypair_path, error))?;
+    let cluster = Cluster {
+        client: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer,
+    };
+    println!("Wallet address: {}", cluster.payer.pubkey());
+
+    match cli.command {
+        Command::CreateGame { slots, until, starts_at, participants, neighbour_prize_bps, group_prize_bps } => {
+            let participants = (!participants.is_empty()).then_some(participants);
+            let prize_tiers = PrizeTiers { neighbour_bps: neighbour_prize_bps, group_bps: group_prize_bps };
+            let betting_window = match until {
+                Some(timestamp) => BettingWindow::Until { timestamp },
+                None => BettingWindow::Slots { count: slots },
+            };
+            create_game(&cluster, betting_window, participants, starts_at, prize_tiers)?;
+        }
+        Command::Bet { game, number, sol, referrer } => {
+            let (bet, signature) = cluster.bet(&game, number, sol_to_lamports(sol), referrer.as_ref())?;
+            println!("Bet {} placed on number {}: {}", bet, number, signature);
+        }
+        Command::Fill { game, sol } => fill(&cluster, &game, sol_to_lamports(sol))?,
+        Command::ClosePeriod { game, number } => close_period(&cluster, &game, number)?,
+        Command::Claim { bets, game } => match game {
+            Some(game) => claim_game(&cluster, &game)?,
+            None => {
+                for bet in bets {
+                    let game = cluster.bet_account(&bet)?.game;
+                    claim(&cluster, &game, &bet)?;
+                }
+            }
+        },
+        Command::List(args) => {
+            let filter = GameFilter { public: args.public, open: args.open, closeable: args.closeable };
+            let games = cluster.find_games(filter)?;
+            for (address, game) in &games {
+                println!("{}", games::summary(address, game));
+            }
+            println!("{} games found", games.len());
+        }
+        Command::Inspect { game } => {
+            let clock = cluster.clock()?;
+            println!("{}", games::describe(&game, &cluster.game(&game)?, clock.slot, clock.unix_timestamp));
+        }
+        Command::Recycle { daemon, interval, slots } => loop {
+            match recycle(&cluster, slots) {
+                Ok(()) => {}
+                Err(error) if daemon => eprintln!("Error: {:#}", error), // the daemon keeps running
+                Err(error) => return Err(error),
+            }
+            if !daemon {
+                break;
+            }
+            sleep(Duration::from_secs(interval));
+        },
+        Command::Migrate => migrate(&cluster)?,
+    }
+
+    Ok(())
+}
+
+impl Cluster {
+    fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let blockhash = self.client.get_latest_blockhash()?;
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&self.payer.pubkey()), &all_signers, blockhash);
+        Ok(self.client.send_and_confirm_transaction(&transaction)?)
+    }
+
+    // The events emitted by the program on a confirmed transaction
+    fn events(&self, signature: &Signature) -> Result<Vec<GotcritterEvent>> {
+        let transaction = self.client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let logs: Option<Vec<String>> = transaction.transaction.meta.and_then(|meta| meta.log_messages.into());
+        Ok(parse_logs(&logs.unwrap_or_default()))
+    }
+
+    // The clock of the cluster, the same the program compares the betting window with
+    fn clock(&self) -> Result<Clock> {
+        let account = self.client.get_account(&sysvar::clock::ID)?;
+        solana_sdk::account::from_account(&account).ok_or_else(|| anyhow!("could not read the clock sysvar"))
+    }
+
+    fn game(&self, game: &Pubkey) -> Result<Game> {
+        let data = self.client.get_account_data(game).with_context(|| format!("game {} not found", game))?;
+        Ok(accounts::decode_game(&data)?)
+    }
+
+    fn bet_account(&self, bet: &Pubkey) -> Result<Bet> {
+        let data = self.client.get_account_data(bet).with_context(|| format!("bet {} not found", bet))?;
+        Ok(accounts::decode_bet(&data)?)
+    }
+
+    fn bet(&self, game: &Pubkey, number: u8, value: u64, referrer: Option<&Pubkey>) -> Result<(Pubkey, Signature)> {
+        // The bet address depends on the number of bets of the game when it is placed
+        let number_of_bets = self.game(game)?.number_of_bets;
+        let bettor = self.payer.pubkey();
+        let instruction = instructions::place_bet(game, &bettor, number_of_bets, number, value, referrer);
+        let signature = self.send(&[instruction], &[])?;
+        Ok((pda::bet(game, &bettor, number_of_bets).0, signature))
+    }
+
+    // The games matching the filter, the most valuable first
+    fn find_games(&self, filter: GameFilter) -> Result<Vec<(Pubkey, Game)>> {
+        let clock = self.clock()?;
+        let accounts = self.client.get_program_accounts_with_config(&gotcritter_client::ID, program_accounts_config(filter.rpc_filters()))?;
+
+        let mut games: Vec<(Pubkey, Game)> = accounts
+            .into_iter()
+            .filter_map(|(address, account)| Some((address, accounts::decode_game(&account.data).ok()?)))
+            .filter(|(_, game)| filter.matches(game, clock.slot, clock.unix_timestamp))
+            .collect();
+        games.sort_by_key(|(_, game)| std::cmp::Reverse(game.total_value));
+        Ok(games)
+    }
+}
+
+// Every game and bet with an older layout, found by their size
+fn migrate(cluster: &Cluster) -> Result<()> {
+    let accounts = cluster.client.get_program_accounts_with_config(&gotcritter_client::ID, program_accounts_config(vec![]))?;
+    let payer = cluster.payer.pubkey();
+    let mut migrated = 0;
+    for (address, account) in accounts.iter().filter(|(_, account)| accounts::needs_migration(&account.data)) {
+        let instruction = match accounts::account_kind(&account.data) {
+            Some(accounts::AccountKind::Game) => instructions::migrate_game(address, &payer),
+            _ => instructions::migrate_bet(address, &payer),
+        };
+        let signature = cluster.send(&[instruction], &[])?;
+        println!("Migrated {}: {}", address, signature);
+        migrated += 1;
+    }
+    println!("{} accounts migrated", migrated);
+    Ok(())
+}
+
+fn program_accounts_config(filters: Vec<solana_client::rpc_filter::RpcFilterType>) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn create_game(
+    cluster: &Cluster,
+    betting_window: BettingWindow,
+    participants: Option<Vec<Pubkey>>,
+    starts_at: Option<i64>,
+    prize_tiers: PrizeTiers,
+) -> Result<Pubkey> {
+    let game = Keypair::new();
+    let creator = cluster.payer.pubkey();
+
+    // The participants that don't fit in the creation are added in the following transactions
+    let participants = participants.unwrap_or_default();
+    let mut chunks = participants.chunks(PARTICIPANTS_PER_TRANSACTION);
+    let first_chunk = chunks.next().map(|chunk| chunk.to_vec());
+
+    let prize_tiers = (prize_tiers != PrizeTiers::default()).then_some(prize_tiers);
+    let instruction = instructions::create_game(&game.pubkey(), &creator, betting_window, first_chunk, starts_at, prize_tiers);
+    let signature = cluster.send(&[instruction], &[&game])?;
+    println!("Created new game {}: {}", game.pubkey(), signature);
+
+    for chunk in chunks {
+        let instruction = instructions::add_participants(&game.pubkey(), &creator, chunk.to_vec());
+        let signature = cluster.send(&[instruction], &[])?;
+        println!("Added {} participants: {}", chunk.len(), signature);
+    }
+    Ok(game.pubkey())
+}
+
+// Bets on all numbers that were not bet on yet, like scripts/fill-game.ts
+fn fill(cluster: &Cluster, game: &Pubkey, value: u64) -> Result<()> {
+    let game_account = cluster.game(game)?;
+    for (index, bets) in game_account.bets_per_number.iter().enumerate() {
+        if *bets == 0 {
+            let number = index as u8 + 1;
+            println!("Placing bet on number {}", number);
+            let (bet, signature) = cluster.bet(game, number, value, None)?;
+            println!("Bet {} placed on number {} with signature {}", bet, number, signature);
+        }
+    }
+    Ok(())
+}
+
+// Waits for a Special Block and bets right after it, like closeGame on anchor/src/gotcritter-exports.ts
+fn close_period(cluster: &Cluster, game: &Pubkey, number: u8) -> Result<()> {
+    loop {
+        let game_account = cluster.game(game)?;
+        if game_account.is_betting_period_ended() {
+            bail!("the betting period of the game {} has already ended", game);
+        }
+        if !games::all_numbers_have_bets(&game_account) {
+            bail!("every number needs a bet before the betting period can end, try the fill command");
+        }
+
+        let clock = cluster.clock()?;
+        if !game_account.is_min_ending_reached(clock.slot, clock.unix_timestamp) {
+            if game_account.min_ending_timestamp != 0 {
+                let seconds = game_account.min_ending_timestamp - clock.unix_timestamp;
+                println!("Waiting for the timestamp {} (now {})", game_account.min_ending_timestamp, clock.unix_timestamp);
+                sleep(Duration::from_secs(seconds.clamp(1, 60) as u64));
+            } else {
+                println!("Waiting for the slot {} (current slot {})", game_account.min_ending_slot, clock.slot);
+                sleep(SLOT_DURATION * (game_account.min_ending_slot - clock.slot).min(150) as u32);
+            }
+            continue;
+        }
+
+        let blockhash = cluster.client.get_latest_blockhash()?;
+        if !games::is_special_blockhash(&blockhash) {
+            sleep(SLOT_DURATION / 4);
+            continue;
+        }
+        println!("Blockhash found with repeated characters: {}", blockhash);
+
+        // The block may be gone when the bet lands, in that case it is just a regular bet and we try again
+        let signature = match cluster.bet(game, number, MIN_BET_VALUE, None) {
+            Ok((_, signature)) => signature,
+            Err(error) => {
+                println!("Bet failed, trying again: {:#}", error);
+                continue;
+            }
+        };
+        let events = cluster.events(&signature)?;
+        if let Some(GotcritterEvent::EndOfBettingPeriod(event)) =
+            events.iter().find(|event| matches!(event, GotcritterEvent::EndOfBettingPeriod(_)))
+        {
+            println!("Betting period ended: {}", signature);
+            println!("Drawn number: {}", event.drawn_number);
+            println!("Reward: {} SOL", lamports_to_sol(event.reward));
+            return Ok(());
+        }
+        println!("The block changed before the bet, trying again: {}", signature);
+    }
+}
+
+fn claim(cluster: &Cluster, game: &Pubkey, bet: &Pubkey) -> Result<()> {
+    let signature = cluster.send(&[instructions::claim_prize(game, &cluster.payer.pubkey(), bet)], &[])?;
+    let prize = cluster.events(&signature)?.into_iter().find_map(|event| match event {
+        GotcritterEvent::PrizeClaimed(event) => Some(event.prize_value),
+        _ => None,
+    });
+    match prize {
+        Some(prize) => println!("Prize of {} SOL claimed for the bet {}: {}", lamports_to_sol(prize), bet, signature),
+        None => println!("Prize claimed for the bet {}: {}", bet, signature),
+    }
+    Ok(())
+}
+
+// Claims every winning bet of the wallet on the game
+fn claim_game(cluster: &Cluster, game: &Pubkey) -> Result<()> {
+    let game_account = cluster.game(game)?;
+    let Some(drawn_number) = game_account.confirmed_drawn_number() else {
+        bail!("the betting period of the game {} has not ended yet", game);
+    };
+
+    let bets = cluster.client.get_program_accounts_with_config(
+        &gotcritter_client::ID,
+        program_accounts_config(games::bet_filters(game, &cluster.payer.pubkey())),
+    )?;
+    let mut claimed = 0;
+    for (address, account) in bets {
+        let bet = accounts::decode_bet(&account.data)?;
+        if !bet.prize_claimed && game_account.calculate_prize(&bet, drawn_number)? > 0 {
+            claim(cluster, game, &address)?;
+            claimed += 1;
+        }
+    }
+    println!("{} prizes claimed, the drawn number was {}", claimed, drawn_number);
+    Ok(())
+}
+
+// Closes the closeable public games and creates a new public game if there is none, like scripts/game-recycler.ts
+fn recycle(cluster: &Cluster, slots: u64) -> Result<()> {
+    let closeable_games = cluster.find_games(GameFilter { public: true, closeable: true, ..Default::default() })?;
+    if closeable_games.is_empty() {
+        println!("No closeable games found");
+    } else {
+        println!("Closing games: {}", closeable_games.len());
+        for (game, _) in &closeable_games {
+            println!("Selected game: {}", game);
+            close_period(cluster, game, 1)?;
+        }
+    }
+
+    let open_games = cluster.find_games(GameFilter { public: true, open: true, ..Default::default() })?;
+    if open_games.is_empty() {
+        create_game(cluster, BettingWindow::Slots { count: slots }, None, None, PrizeTiers::default())?;
+    } else {
+        println!("Public games found: {}", open_games.len());
+    }
+    Ok(())
+}
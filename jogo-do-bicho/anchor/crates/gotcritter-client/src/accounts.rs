@@ -0,0 +1,81 @@
+//! Decoders for the program accounts. They check the anchor discriminator, so an account of
+//! another type is rejected instead of being decoded into garbage.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, Discriminator, Space};
+
+use crate::{Bet, Config, CustomError, Game, ReferralAccount, Result, Syndicate, SyndicateMember};
+
+/// Decodes any Borsh account of the program from its raw data, discriminator included.
+/// Games are zero-copy, use [`decode_game`] for them.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a game with the current layout. Games created by an older version of the program
+/// are rejected with `GameNeedsMigration`, see [`needs_migration`].
+pub fn decode_game(data: &[u8]) -> Result<Game> {
+    if account_kind(data) != Some(AccountKind::Game) {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let game = data
+        .get(8..8 + std::mem::size_of::<Game>())
+        .ok_or(CustomError::GameNeedsMigration)?;
+    // The program reads the account in place, but a buffer from the RPC may not be aligned
+    Ok(bytemuck::pod_read_unaligned(game))
+}
+
+pub fn decode_bet(data: &[u8]) -> Result<Bet> {
+    decode(data)
+}
+
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    decode(data)
+}
+
+pub fn decode_referral(data: &[u8]) -> Result<ReferralAccount> {
+    decode(data)
+}
+
+pub fn decode_syndicate(data: &[u8]) -> Result<Syndicate> {
+    decode(data)
+}
+
+pub fn decode_syndicate_member(data: &[u8]) -> Result<SyndicateMember> {
+    decode(data)
+}
+
+/// The kind of a program account, useful when scanning all the accounts of the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountKind {
+    Game,
+    Bet,
+    Config,
+    Referral,
+    Syndicate,
+    SyndicateMember,
+}
+
+/// Identifies an account by its discriminator, without decoding it.
+pub fn account_kind(data: &[u8]) -> Option<AccountKind> {
+    let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+    match discriminator {
+        Game::DISCRIMINATOR => Some(AccountKind::Game),
+        Bet::DISCRIMINATOR => Some(AccountKind::Bet),
+        Config::DISCRIMINATOR => Some(AccountKind::Config),
+        ReferralAccount::DISCRIMINATOR => Some(AccountKind::Referral),
+        Syndicate::DISCRIMINATOR => Some(AccountKind::Syndicate),
+        SyndicateMember::DISCRIMINATOR => Some(AccountKind::SyndicateMember),
+        _ => None,
+    }
+}
+
+/// Whether a game or a bet was created by an older version of the program and must be migrated
+/// to the current layout. Older layouts are always smaller than the current one.
+pub fn needs_migration(data: &[u8]) -> bool {
+    match account_kind(data) {
+        Some(AccountKind::Game) => data.len() < 8 + Game::INIT_SPACE,
+        Some(AccountKind::Bet) => data.len() < 8 + Bet::INIT_SPACE,
+        _ => false,
+    }
+}
@@ -0,0 +1,112 @@
+//! Decoders for the events emitted by the program. Anchor emits each event as a
+//! `Program data: <base64>` log line, made of the event discriminator followed by the borsh data.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+pub use gotcritter::{
+    BetMigrated, BetPlaced, BetRefunded, BetWithdrawn, ConfigUpdated, EndOfBettingPeriod, EventHeader, GameCancelled, GameCreated,
+    GameMigrated, ParticipantsAdded, PauseStateChanged, PrizeClaimed, ReferralRewardsWithdrawn, ReferrerRegistered, SyndicateCreated,
+    SyndicateDeposit, SyndicateWithdrawal,
+};
+
+macro_rules! events {
+    ($($name:ident),* $(,)?) => {
+        /// Any event emitted by the program.
+        pub enum GotcritterEvent {
+            $($name($name),)*
+        }
+
+        impl GotcritterEvent {
+            /// Decodes an event from its discriminator and data. Returns `None` for unknown events.
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+                let mut data = &data[8..];
+                $(
+                    if discriminator == $name::DISCRIMINATOR {
+                        return $name::deserialize(&mut data).ok().map(GotcritterEvent::$name);
+                    }
+                )*
+                None
+            }
+
+            /// The header shared by all the events, with the game, the slot and the timestamp.
+            pub fn header(&self) -> &EventHeader {
+                match self {
+                    $(GotcritterEvent::$name(event) => &event.header,)*
+                }
+            }
+
+            /// The name of the event, as in the IDL.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(GotcritterEvent::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+impl GotcritterEvent {
+    /// The game the event refers to, `None` for the events of the program configuration and of the referrers.
+    pub fn game(&self) -> Option<Pubkey> {
+        let game = self.header().game;
+        (game != Pubkey::default()).then_some(game)
+    }
+}
+
+events!(
+    GameCreated,
+    BetPlaced,
+    PrizeClaimed,
+    EndOfBettingPeriod,
+    GameCancelled,
+    BetRefunded,
+    PauseStateChanged,
+    ReferralRewardsWithdrawn,
+    GameMigrated,
+    BetMigrated,
+    ParticipantsAdded,
+    SyndicateCreated,
+    SyndicateDeposit,
+    SyndicateWithdrawal,
+    BetWithdrawn,
+    ReferrerRegistered,
+    ConfigUpdated,
+);
+
+/// Decodes the events emitted by the program from the log messages of a transaction.
+///
+/// Only the `Program data:` lines logged while the program is the one executing are decoded,
+/// so events of other programs (or of programs invoked by this one) are ignored.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<GotcritterEvent> {
+    let program_id = gotcritter::ID.to_string();
+    let mut stack: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+        if let Some(rest) = log.strip_prefix("Program data: ") {
+            if stack.last() == Some(&program_id) {
+                if let Some(event) = STANDARD.decode(rest).ok().and_then(|data| GotcritterEvent::decode(&data)) {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (Some(id), Some(action)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if action == "invoke" {
+                stack.push(id.to_string());
+            } else if (action == "success" || action.starts_with("failed")) && stack.last().map(String::as_str) == Some(id) {
+                stack.pop();
+            }
+        } else if log == "Log truncated" {
+            break;
+        }
+    }
+
+    events
+}
@@ -0,0 +1,335 @@
+//! Typed builders for the program instructions. The PDAs are derived here, so the callers only
+//! provide the accounts that can't be derived.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use gotcritter::{accounts, instruction, BettingWindow, PrizeTiers};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: gotcritter::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Creates a game on a new `game` account, which must also sign the transaction.
+/// The game is private when `participants` is not empty, and only accepts bets from `starts_at` when set.
+/// Without `prize_tiers` the whole pool goes to the bets on the drawn number.
+pub fn create_game(
+    game: &Pubkey,
+    creator: &Pubkey,
+    betting_window: BettingWindow,
+    participants: Option<Vec<Pubkey>>,
+    starts_at: Option<i64>,
+    prize_tiers: Option<PrizeTiers>,
+) -> Instruction {
+    build(
+        accounts::CreateGame {
+            game: *game,
+            creator: *creator,
+            system_program: system_program::ID,
+        },
+        instruction::CreateGame {
+            betting_window,
+            participants,
+            starts_at,
+            prize_tiers,
+        },
+    )
+}
+
+/// Adds participants to a private game, for sets that don't fit in the `create_game` transaction.
+/// Only the creator can add them, up to `gotcritter::MAX_PARTICIPANTS`.
+pub fn add_participants(game: &Pubkey, creator: &Pubkey, participants: Vec<Pubkey>) -> Instruction {
+    build(
+        accounts::AddParticipants {
+            game: *game,
+            creator: *creator,
+        },
+        instruction::AddParticipants { participants },
+    )
+}
+
+/// Places a bet, `number_of_bets` is the current `Game::number_of_bets`, used to derive the bet address.
+pub fn place_bet(
+    game: &Pubkey,
+    bettor: &Pubkey,
+    number_of_bets: u64,
+    number: u8,
+    value: u64,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    build(
+        accounts::PlaceBet {
+            game: *game,
+            config: pda::config().0,
+            bettor: *bettor,
+            bet: pda::bet(game, bettor, number_of_bets).0,
+            system_program: system_program::ID,
+            recent_blockhashes: sysvar::recent_blockhashes::ID,
+            referral: referrer.map(|referrer| pda::referral(referrer).0),
+        },
+        instruction::PlaceBet { number, value },
+    )
+}
+
+/// Places a bet owned by `bettor` and funded by `payer`, both must sign. Meant for programs betting through CPI,
+/// where the bettor is a PDA signing with its seeds and the payer an account that can pay with a system transfer.
+pub fn place_bet_with_payer(
+    game: &Pubkey,
+    bettor: &Pubkey,
+    payer: &Pubkey,
+    number_of_bets: u64,
+    number: u8,
+    value: u64,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    build(
+        accounts::PlaceBetWithPayer {
+            game: *game,
+            config: pda::config().0,
+            bettor: *bettor,
+            payer: *payer,
+            bet: pda::bet(game, bettor, number_of_bets).0,
+            system_program: system_program::ID,
+            recent_blockhashes: sysvar::recent_blockhashes::ID,
+            referral: referrer.map(|referrer| pda::referral(referrer).0),
+        },
+        instruction::PlaceBetWithPayer { number, value },
+    )
+}
+
+/// Returns the drawn number of a game, meant to be simulated.
+pub fn drawn_number(game: &Pubkey) -> Instruction {
+    build(accounts::CheckDrawnNumber { game: *game }, instruction::DrawnNumber {})
+}
+
+/// Returns the prize of a bet, meant to be simulated.
+pub fn prize(game: &Pubkey, bet: &Pubkey) -> Instruction {
+    build(accounts::CheckPrize { game: *game, bet: *bet }, instruction::Prize {})
+}
+
+pub fn claim_prize(game: &Pubkey, bettor: &Pubkey, bet: &Pubkey) -> Instruction {
+    build(
+        accounts::ClaimPrize {
+            game: *game,
+            bettor: *bettor,
+            bet: *bet,
+            system_program: system_program::ID,
+        },
+        instruction::ClaimPrize {},
+    )
+}
+
+pub fn cancel_game(game: &Pubkey, creator: &Pubkey) -> Instruction {
+    build(
+        accounts::CancelGame {
+            game: *game,
+            creator: *creator,
+        },
+        instruction::CancelGame {},
+    )
+}
+
+pub fn initialize_config(admin: &Pubkey) -> Instruction {
+    build(
+        accounts::InitializeConfig {
+            config: pda::config().0,
+            admin: *admin,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeConfig {},
+    )
+}
+
+pub fn set_referral_fee(admin: &Pubkey, referral_fee_bps: u16) -> Instruction {
+    build(update_config(admin), instruction::SetReferralFee { referral_fee_bps })
+}
+
+pub fn set_withdrawal_penalty(admin: &Pubkey, withdrawal_penalty_bps: u16) -> Instruction {
+    build(update_config(admin), instruction::SetWithdrawalPenalty { withdrawal_penalty_bps })
+}
+
+pub fn pause(admin: &Pubkey) -> Instruction {
+    build(update_config(admin), instruction::Pause {})
+}
+
+pub fn unpause(admin: &Pubkey) -> Instruction {
+    build(update_config(admin), instruction::Unpause {})
+}
+
+fn update_config(admin: &Pubkey) -> accounts::UpdateConfig {
+    accounts::UpdateConfig {
+        config: pda::config().0,
+        admin: *admin,
+    }
+}
+
+pub fn refund_bet(game: &Pubkey, bettor: &Pubkey, bet: &Pubkey) -> Instruction {
+    build(
+        accounts::RefundBet {
+            game: *game,
+            config: pda::config().0,
+            bettor: *bettor,
+            bet: *bet,
+        },
+        instruction::RefundBet {},
+    )
+}
+
+/// Withdraws a bet before the minimum ending slot of its game, minus the penalty set on the config.
+pub fn withdraw_bet(game: &Pubkey, bettor: &Pubkey, bet: &Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawBet {
+            game: *game,
+            config: pda::config().0,
+            bettor: *bettor,
+            bet: *bet,
+        },
+        instruction::WithdrawBet {},
+    )
+}
+
+pub fn register_referrer(referrer: &Pubkey) -> Instruction {
+    build(
+        accounts::RegisterReferrer {
+            referral: pda::referral(referrer).0,
+            referrer: *referrer,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterReferrer {},
+    )
+}
+
+pub fn withdraw_referral_rewards(referrer: &Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawReferralRewards {
+            referral: pda::referral(referrer).0,
+            referrer: *referrer,
+        },
+        instruction::WithdrawReferralRewards {},
+    )
+}
+
+/// Creates a syndicate of the manager on a game, betting on `number` with the funds of its members.
+pub fn create_syndicate(game: &Pubkey, manager: &Pubkey, number: u8) -> Instruction {
+    build(
+        accounts::CreateSyndicate {
+            syndicate: pda::syndicate(game, manager).0,
+            game: *game,
+            manager: *manager,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSyndicate { number },
+    )
+}
+
+pub fn join_syndicate(syndicate: &Pubkey, member: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::JoinSyndicate {
+            syndicate: *syndicate,
+            syndicate_member: pda::syndicate_member(syndicate, member).0,
+            member: *member,
+            system_program: system_program::ID,
+        },
+        instruction::JoinSyndicate { amount },
+    )
+}
+
+pub fn deposit_to_syndicate(syndicate: &Pubkey, member: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::DepositToSyndicate {
+            syndicate: *syndicate,
+            syndicate_member: pda::syndicate_member(syndicate, member).0,
+            member: *member,
+            system_program: system_program::ID,
+        },
+        instruction::DepositToSyndicate { amount },
+    )
+}
+
+/// Places the bet of a syndicate with everything its members contributed. As in [`place_bet`],
+/// `number_of_bets` must be read from the game right before placing the bet.
+pub fn place_syndicate_bet(game: &Pubkey, manager: &Pubkey, number_of_bets: u64) -> Instruction {
+    let syndicate = pda::syndicate(game, manager).0;
+    build(
+        accounts::PlaceSyndicateBet {
+            syndicate,
+            manager: *manager,
+            game: *game,
+            config: pda::config().0,
+            bet: pda::bet(game, &syndicate, number_of_bets).0,
+            system_program: system_program::ID,
+            recent_blockhashes: sysvar::recent_blockhashes::ID,
+        },
+        instruction::PlaceSyndicateBet {},
+    )
+}
+
+/// Claims the prize of the syndicate bet into the syndicate, the rent of the bet goes back to the manager.
+pub fn claim_syndicate_prize(game: &Pubkey, manager: &Pubkey, bet: &Pubkey) -> Instruction {
+    build(
+        accounts::ClaimSyndicatePrize {
+            syndicate: pda::syndicate(game, manager).0,
+            game: *game,
+            bet: *bet,
+            manager: *manager,
+        },
+        instruction::ClaimSyndicatePrize {},
+    )
+}
+
+pub fn refund_syndicate_bet(game: &Pubkey, manager: &Pubkey, bet: &Pubkey) -> Instruction {
+    build(
+        accounts::RefundSyndicateBet {
+            syndicate: pda::syndicate(game, manager).0,
+            game: *game,
+            config: pda::config().0,
+            bet: *bet,
+            manager: *manager,
+        },
+        instruction::RefundSyndicateBet {},
+    )
+}
+
+pub fn withdraw_from_syndicate(game: &Pubkey, manager: &Pubkey, member: &Pubkey) -> Instruction {
+    let syndicate = pda::syndicate(game, manager).0;
+    build(
+        accounts::WithdrawFromSyndicate {
+            syndicate,
+            syndicate_member: pda::syndicate_member(&syndicate, member).0,
+            member: *member,
+            game: *game,
+        },
+        instruction::WithdrawFromSyndicate {},
+    )
+}
+
+/// Moves a game created by an older version of the program to the current layout, the payer covers the extra rent.
+pub fn migrate_game(game: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateGame {
+            game: *game,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateGame {},
+    )
+}
+
+/// Same as [`migrate_game`], for a bet.
+pub fn migrate_bet(bet: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateBet {
+            bet: *bet,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        instruction::MigrateBet {},
+    )
+}
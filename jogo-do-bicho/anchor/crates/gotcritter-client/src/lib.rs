@@ -0,0 +1,18 @@
+//! Rust client for the gotcritter program, the counterpart of `anchor/src/gotcritter-exports.ts`
+//! for services that can't use the TypeScript helpers.
+//!
+//! - [`pda`]: addresses of the program derived accounts;
+//! - [`instructions`]: typed builders for every instruction;
+//! - [`accounts`]: decoders for the program accounts;
+//! - [`events`]: decoders for the events emitted on the transaction logs.
+
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+
+pub use gotcritter::{
+    Bet, BettingWindow, Config, CustomError, Game, Prize, PrizeTier, PrizeTiers, ReferralAccount, Syndicate, SyndicateMember, ID,
+};
+
+pub type Result<T> = std::result::Result<T, anchor_lang::error::Error>;
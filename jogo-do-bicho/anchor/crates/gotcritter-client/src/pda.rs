@@ -0,0 +1,32 @@
+//! Addresses of the program derived accounts, they must match the seeds used by the program.
+
+use anchor_lang::prelude::Pubkey;
+
+/// The config singleton, holding the admin, the pause state and the referral fee.
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &gotcritter::ID)
+}
+
+/// A bet is identified by its game, its bettor and the number of bets on the game when it was placed,
+/// so `number_of_bets` must be read from the game right before placing the bet.
+pub fn bet(game: &Pubkey, bettor: &Pubkey, number_of_bets: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"bet", game.as_ref(), bettor.as_ref(), &number_of_bets.to_le_bytes()],
+        &gotcritter::ID,
+    )
+}
+
+/// The referral account of a referrer, where the referral rewards are credited.
+pub fn referral(referrer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"referral", referrer.as_ref()], &gotcritter::ID)
+}
+
+/// The syndicate of a manager on a game, which holds the contributions of its members and places their bet.
+pub fn syndicate(game: &Pubkey, manager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"syndicate", game.as_ref(), manager.as_ref()], &gotcritter::ID)
+}
+
+/// The contribution of a member to a syndicate.
+pub fn syndicate_member(syndicate: &Pubkey, member: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"syndicate_member", syndicate.as_ref(), member.as_ref()], &gotcritter::ID)
+}
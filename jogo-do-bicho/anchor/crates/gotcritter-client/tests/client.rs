@@ -0,0 +1,299 @@
+// Runs the instructions built by the client on an in-process bank and decodes the results
+use anchor_lang::prelude::AccountInfo;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, system_program};
+use anchor_lang::{AnchorSerialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use anchor_lang::Space;
+use gotcritter_client::accounts::{
+    account_kind, decode_bet, decode_game, decode_referral, decode_syndicate, decode_syndicate_member, needs_migration, AccountKind,
+};
+use gotcritter_client::events::{parse_logs, EventHeader, GotcritterEvent};
+use gotcritter_client::{instructions, pda, BettingWindow};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const SLOTS: BettingWindow = BettingWindow::Slots { count: 100 };
+
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    gotcritter::entry(program_id, accounts, data)
+}
+
+async fn start() -> ProgramTestContext {
+    let program_test = ProgramTest::new("gotcritter", gotcritter::ID, processor!(process_instruction));
+    let mut context = program_test.start_with_context().await;
+    let admin = context.payer.pubkey();
+    process(&mut context, &[instructions::initialize_config(&admin)], &[]).await;
+    context
+}
+
+async fn process(context: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash);
+    context.banks_client.process_transaction(transaction).await.unwrap();
+}
+
+async fn account_data(context: &mut ProgramTestContext, address: Pubkey) -> Vec<u8> {
+    context.banks_client.get_account(address).await.unwrap().unwrap().data
+}
+
+#[tokio::test]
+async fn builds_instructions_and_decodes_accounts() {
+    let mut context = start().await;
+    let creator = context.payer.pubkey();
+    let game = Keypair::new();
+
+    process(
+        &mut context,
+        &[instructions::create_game(&game.pubkey(), &creator, SLOTS, None, None, None)],
+        &[&game],
+    )
+    .await;
+
+    let data = account_data(&mut context, game.pubkey()).await;
+    assert_eq!(account_kind(&data), Some(AccountKind::Game));
+    let game_account = decode_game(&data).unwrap();
+    assert_eq!(game_account.creator, creator);
+
+    // The bet address is derived from the number of bets on the game
+    let bettor = context.payer.pubkey();
+    let place_bet = instructions::place_bet(
+        &game.pubkey(),
+        &bettor,
+        game_account.number_of_bets,
+        5,
+        LAMPORTS_PER_SOL,
+        None,
+    );
+    process(&mut context, &[place_bet], &[]).await;
+
+    let bet_address = pda::bet(&game.pubkey(), &bettor, 0).0;
+    let data = account_data(&mut context, bet_address).await;
+    assert_eq!(account_kind(&data), Some(AccountKind::Bet));
+    let bet = decode_bet(&data).unwrap();
+    assert_eq!(bet.bettor, bettor);
+    assert_eq!(bet.number, 5);
+    assert_eq!(bet.value, LAMPORTS_PER_SOL);
+
+    // A bet can't be decoded as a game
+    assert!(decode_game(&data).is_err());
+
+    // The bettor of a bet funded by a payer only signs, like a PDA of a program calling through CPI
+    let pda_bettor = Keypair::new();
+    let place_bet = instructions::place_bet_with_payer(&game.pubkey(), &pda_bettor.pubkey(), &bettor, 1, 6, LAMPORTS_PER_SOL, None);
+    process(&mut context, &[place_bet], &[&pda_bettor]).await;
+
+    let data = account_data(&mut context, pda::bet(&game.pubkey(), &pda_bettor.pubkey(), 1).0).await;
+    assert_eq!(decode_bet(&data).unwrap().bettor, pda_bettor.pubkey());
+
+    // A bet withdrawn before the minimum ending slot is closed
+    let bet_address = pda::bet(&game.pubkey(), &pda_bettor.pubkey(), 1).0;
+    process(&mut context, &[instructions::withdraw_bet(&game.pubkey(), &pda_bettor.pubkey(), &bet_address)], &[&pda_bettor]).await;
+    assert!(context.banks_client.get_account(bet_address).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn builds_participant_instructions_and_detects_older_layouts() {
+    let mut context = start().await;
+    let creator = context.payer.pubkey();
+    let game = Keypair::new();
+    let participants: Vec<_> = (0..30).map(|_| Pubkey::new_unique()).collect();
+
+    process(
+        &mut context,
+        &[
+            instructions::create_game(&game.pubkey(), &creator, SLOTS, Some(participants[..15].to_vec()), None, None),
+            instructions::add_participants(&game.pubkey(), &creator, participants[15..].to_vec()),
+        ],
+        &[&game],
+    )
+    .await;
+
+    let data = account_data(&mut context, game.pubkey()).await;
+    assert!(!needs_migration(&data));
+    assert_eq!(decode_game(&data).unwrap().participants(), participants.as_slice());
+
+    // A game with the Borsh layout is smaller, it must be migrated before being decoded
+    let legacy_data = &data[..8 + gotcritter::LegacyGame::INIT_SPACE];
+    assert!(needs_migration(legacy_data));
+    assert!(decode_game(legacy_data).is_err());
+}
+
+#[tokio::test]
+async fn builds_referral_instructions() {
+    let mut context = start().await;
+    let admin = context.payer.pubkey();
+    let referrer = Keypair::new();
+    let game = Keypair::new();
+
+    process(
+        &mut context,
+        &[
+            anchor_lang::solana_program::system_instruction::transfer(&admin, &referrer.pubkey(), LAMPORTS_PER_SOL),
+            instructions::register_referrer(&referrer.pubkey()),
+            instructions::set_referral_fee(&admin, 1_000),
+            instructions::create_game(&game.pubkey(), &admin, SLOTS, None, None, None),
+        ],
+        &[&referrer, &game],
+    )
+    .await;
+
+    let place_bet = instructions::place_bet(&game.pubkey(), &admin, 0, 1, LAMPORTS_PER_SOL, Some(&referrer.pubkey()));
+    process(&mut context, &[place_bet], &[]).await;
+
+    let referral = pda::referral(&referrer.pubkey()).0;
+    let data = account_data(&mut context, referral).await;
+    assert_eq!(account_kind(&data), Some(AccountKind::Referral));
+    assert_eq!(decode_referral(&data).unwrap().pending_rewards, LAMPORTS_PER_SOL / 10);
+
+    process(&mut context, &[instructions::withdraw_referral_rewards(&referrer.pubkey())], &[&referrer]).await;
+    let data = account_data(&mut context, referral).await;
+    assert_eq!(decode_referral(&data).unwrap().pending_rewards, 0);
+}
+
+#[tokio::test]
+async fn builds_syndicate_instructions() {
+    let mut context = start().await;
+    let manager = context.payer.pubkey();
+    let member = Keypair::new();
+    let game = Keypair::new();
+
+    process(
+        &mut context,
+        &[
+            anchor_lang::solana_program::system_instruction::transfer(&manager, &member.pubkey(), 2 * LAMPORTS_PER_SOL),
+            instructions::create_game(&game.pubkey(), &manager, SLOTS, None, None, None),
+            instructions::create_syndicate(&game.pubkey(), &manager, 9),
+        ],
+        &[&game],
+    )
+    .await;
+
+    let syndicate = pda::syndicate(&game.pubkey(), &manager).0;
+    process(
+        &mut context,
+        &[
+            instructions::join_syndicate(&syndicate, &member.pubkey(), LAMPORTS_PER_SOL / 2),
+            instructions::deposit_to_syndicate(&syndicate, &member.pubkey(), LAMPORTS_PER_SOL / 2),
+            instructions::place_syndicate_bet(&game.pubkey(), &manager, 0),
+        ],
+        &[&member],
+    )
+    .await;
+
+    let data = account_data(&mut context, syndicate).await;
+    assert_eq!(account_kind(&data), Some(AccountKind::Syndicate));
+    let syndicate_account = decode_syndicate(&data).unwrap();
+    assert_eq!(syndicate_account.bet, pda::bet(&game.pubkey(), &syndicate, 0).0);
+    assert_eq!(syndicate_account.total_contributed, LAMPORTS_PER_SOL);
+
+    let data = account_data(&mut context, pda::syndicate_member(&syndicate, &member.pubkey()).0).await;
+    assert_eq!(account_kind(&data), Some(AccountKind::SyndicateMember));
+    assert_eq!(decode_syndicate_member(&data).unwrap().contribution, LAMPORTS_PER_SOL);
+
+    let data = account_data(&mut context, syndicate_account.bet).await;
+    assert_eq!(decode_bet(&data).unwrap().bettor, syndicate);
+}
+
+// The native processor used by the tests above doesn't write the events to the transaction logs,
+// so the logs below have the same shape as the ones of the deployed program
+fn event_log<E: AnchorSerialize + Discriminator>(event: &E) -> String {
+    let mut data = E::DISCRIMINATOR.to_vec();
+    event.serialize(&mut data).unwrap();
+    format!("Program data: {}", STANDARD.encode(data))
+}
+
+fn header(game: Pubkey) -> EventHeader {
+    EventHeader { game, slot: 7, timestamp: 42, version: 1 }
+}
+
+#[test]
+fn parses_events_from_logs() {
+    let game = Pubkey::new_unique();
+    let bettor = Pubkey::new_unique();
+    let referrer = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", gotcritter::ID),
+        "Program log: Instruction: PlaceBet".to_string(),
+        format!("Program {} invoke [2]", system_program::ID),
+        format!("Program {} success", system_program::ID),
+        event_log(&gotcritter::BetPlaced {
+            header: header(game),
+            bettor,
+            number: 5,
+            value: 900,
+            bet: Pubkey::new_unique(),
+            referrer: Some(referrer),
+            referral_fee: 100,
+        }),
+        event_log(&gotcritter::ReferralRewardsWithdrawn {
+            header: header(Pubkey::default()),
+            referrer,
+            amount: 100,
+        }),
+        format!("Program {} consumed 1000 of 200000 compute units", gotcritter::ID),
+        format!("Program {} success", gotcritter::ID),
+    ];
+
+    let events = parse_logs(&logs);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].game(), Some(game));
+    assert_eq!(events[0].header().slot, 7);
+    match &events[0] {
+        GotcritterEvent::BetPlaced(event) => {
+            assert_eq!(event.bettor, bettor);
+            assert_eq!(event.number, 5);
+            assert_eq!(event.value, 900);
+            assert_eq!(event.referrer, Some(referrer));
+            assert_eq!(event.referral_fee, 100);
+        }
+        other => panic!("unexpected event {}", other.name()),
+    }
+
+    // The events of the referrers don't belong to a game
+    assert_eq!(events[1].name(), "ReferralRewardsWithdrawn");
+    assert_eq!(events[1].game(), None);
+    assert_eq!(events[1].header().timestamp, 42);
+}
+
+#[test]
+fn ignores_data_logged_by_other_programs() {
+    // The same event is logged by the program and by the program that invokes it
+    let event = gotcritter::GameCancelled {
+        header: header(Pubkey::new_unique()),
+        creator: Pubkey::new_unique(),
+    };
+    let data = event_log(&event);
+
+    let other_program = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", other_program),
+        data.clone(),
+        format!("Program {} invoke [2]", gotcritter::ID),
+        "Program log: Instruction: CancelGame".to_string(),
+        data.clone(),
+        format!("Program {} consumed 1000 of 200000 compute units", gotcritter::ID),
+        format!("Program {} success", gotcritter::ID),
+        data.clone(),
+        format!("Program {} success", other_program),
+    ];
+
+    let events = parse_logs(&logs);
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        GotcritterEvent::GameCancelled(decoded) => {
+            assert_eq!(decoded.header, event.header);
+        }
+        other => panic!("unexpected event {}", other.name()),
+    }
+}
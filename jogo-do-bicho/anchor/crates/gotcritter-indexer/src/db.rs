@@ -0,0 +1,352 @@
+// SQLite storage of the events. Every event is stored on `events`, keyed by the transaction signature
+// and its position on the logs, so applying the same transaction twice changes nothing. The other
+// tables are materialized from the events when they are first stored.
+use anyhow::Result;
+use gotcritter_client::events::{parse_logs, GotcritterEvent};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+
+use crate::RecordedTransaction;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,  -- position of the event on the transaction logs
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    game TEXT,
+    wallet TEXT,                   -- creator, bettor, closer, admin or referrer, depending on the event
+    number INTEGER,                -- bet number or drawn number
+    amount INTEGER,                -- lamports moved by the event
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_by_game ON events (game);
+CREATE INDEX IF NOT EXISTS events_by_wallet ON events (wallet);
+
+CREATE TABLE IF NOT EXISTS games (
+    address TEXT PRIMARY KEY,
+    creator TEXT NOT NULL,
+    private INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open', -- open, ended or cancelled
+    total_value INTEGER NOT NULL DEFAULT 0,
+    number_of_bets INTEGER NOT NULL DEFAULT 0,
+    closer TEXT,
+    closer_reward INTEGER,
+    ended_at INTEGER,
+    drawn_number INTEGER,
+    value_provided_to_winners INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS bets (
+    address TEXT PRIMARY KEY,
+    game TEXT NOT NULL,
+    bettor TEXT NOT NULL,
+    number INTEGER NOT NULL,
+    value INTEGER NOT NULL,        -- the value that went to the game pool
+    referrer TEXT,
+    referral_fee INTEGER NOT NULL, -- paid by the bettor on top of the value
+    placed_at INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    refunded INTEGER NOT NULL DEFAULT 0 -- by refund_bet or withdraw_bet
+);
+CREATE INDEX IF NOT EXISTS bets_by_game ON bets (game);
+CREATE INDEX IF NOT EXISTS bets_by_bettor ON bets (bettor);
+
+CREATE TABLE IF NOT EXISTS claims (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    game TEXT NOT NULL,
+    bettor TEXT NOT NULL,
+    drawn_number INTEGER NOT NULL,
+    prize INTEGER NOT NULL,
+    claimed_at INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS claims_by_bettor ON claims (bettor);
+
+-- the last transaction applied, where the next run starts from
+CREATE TABLE IF NOT EXISTS cursor (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL
+);
+";
+
+pub struct Database {
+    connection: Connection,
+}
+
+// One event of a game, as shown by `history`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub signature: String,
+    pub slot: u64,
+    pub name: String,
+    pub wallet: Option<String>,
+    pub number: Option<u8>,
+    pub amount: Option<u64>,
+    pub timestamp: i64,
+}
+
+// Everything a wallet put in and took out of the program, in lamports
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WalletPnl {
+    pub bets: u64,
+    pub wagered: u64, // bet values plus referral fees
+    pub refunded: u64, // refunds and withdrawals, without the withdrawal penalty
+    pub prizes: u64,
+    pub closer_rewards: u64,
+    pub referral_rewards: u64,
+    pub syndicate_deposits: u64, // the syndicate is the bettor of the bets placed with them
+    pub syndicate_withdrawals: u64,
+}
+
+impl WalletPnl {
+    // What the wallet won minus what it spent, negative when it lost
+    pub fn net(&self) -> i128 {
+        let received = self.refunded + self.prizes + self.closer_rewards + self.referral_rewards + self.syndicate_withdrawals;
+        received as i128 - (self.wagered + self.syndicate_deposits) as i128
+    }
+}
+
+impl Database {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Database { connection })
+    }
+
+    // The signature of the last transaction applied
+    pub fn cursor(&self) -> Result<Option<String>> {
+        Ok(self
+            .connection
+            .query_row("SELECT signature FROM cursor WHERE id = 0", [], |row| row.get(0))
+            .optional()?)
+    }
+
+    // Stores the events of the transaction and moves the cursor to it. Returns the number of new events
+    pub fn apply(&mut self, transaction: &RecordedTransaction) -> Result<usize> {
+        let db = self.connection.transaction()?;
+        let mut new_events = 0;
+
+        if !transaction.failed {
+            for (index, event) in parse_logs(&transaction.logs).iter().enumerate() {
+                if insert_event(&db, transaction, index, event)? {
+                    materialize(&db, transaction, index, event)?;
+                    new_events += 1;
+                }
+            }
+        }
+
+        db.execute(
+            "INSERT INTO cursor (id, signature, slot) VALUES (0, ?1, ?2)
+             ON CONFLICT (id) DO UPDATE SET signature = excluded.signature, slot = excluded.slot",
+            params![transaction.signature, transaction.slot as i64],
+        )?;
+        db.commit()?;
+        Ok(new_events)
+    }
+
+    // Every event of the game, in the order they happened
+    pub fn game_history(&self, game: &str) -> Result<Vec<HistoryEntry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT signature, slot, name, wallet, number, amount, timestamp FROM events
+             WHERE game = ?1 ORDER BY slot, rowid",
+        )?;
+        let entries = statement.query_map([game], |row| {
+            Ok(HistoryEntry {
+                signature: row.get(0)?,
+                slot: row.get::<_, i64>(1)? as u64,
+                name: row.get(2)?,
+                wallet: row.get(3)?,
+                number: row.get(4)?,
+                amount: row.get::<_, Option<i64>>(5)?.map(|amount| amount as u64),
+                timestamp: row.get(6)?,
+            })
+        })?;
+        Ok(entries.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn wallet_pnl(&self, wallet: &str) -> Result<WalletPnl> {
+        let (bets, wagered) = self.connection.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(value + referral_fee), 0) FROM bets WHERE bettor = ?1",
+            [wallet],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        )?;
+        let prizes = self.connection.query_row(
+            "SELECT COALESCE(SUM(prize), 0) FROM claims WHERE bettor = ?1",
+            [wallet],
+            |row| row.get::<_, i64>(0),
+        )? as u64;
+        let refunded = self.sum_event(wallet, "BetRefunded")? + self.sum_event(wallet, "BetWithdrawn")?;
+        let closer_rewards = self.sum_event(wallet, "EndOfBettingPeriod")?;
+        let referral_rewards = self.sum_event(wallet, "ReferralRewardsWithdrawn")?;
+        let syndicate_deposits = self.sum_event(wallet, "SyndicateDeposit")?;
+        let syndicate_withdrawals = self.sum_event(wallet, "SyndicateWithdrawal")?;
+
+        Ok(WalletPnl {
+            bets: bets as u64,
+            wagered: wagered as u64,
+            refunded,
+            prizes,
+            closer_rewards,
+            referral_rewards,
+            syndicate_deposits,
+            syndicate_withdrawals,
+        })
+    }
+
+    // The lamports received by the wallet on the events with the given name
+    fn sum_event(&self, wallet: &str, name: &str) -> Result<u64> {
+        let query = "SELECT COALESCE(SUM(amount), 0) FROM events WHERE wallet = ?1 AND name = ?2";
+        Ok(self.connection.query_row(query, [wallet, name], |row| row.get::<_, i64>(0))? as u64)
+    }
+
+    // Direct access for ad hoc queries
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+// Returns false when the event was already stored
+fn insert_event(db: &Transaction, transaction: &RecordedTransaction, index: usize, event: &GotcritterEvent) -> Result<bool> {
+    let (wallet, number, amount) = match event {
+        GotcritterEvent::GameCreated(e) => (Some(e.creator), None, None),
+        GotcritterEvent::ParticipantsAdded(_) => (None, None, None),
+        GotcritterEvent::BetPlaced(e) => (Some(e.bettor), Some(e.number), Some(e.value)),
+        GotcritterEvent::EndOfBettingPeriod(e) => (Some(e.closer), None, Some(e.reward)),
+        GotcritterEvent::PrizeClaimed(e) => (Some(e.bettor), Some(e.drawn_number), Some(e.prize_value)),
+        GotcritterEvent::GameCancelled(e) => (Some(e.creator), None, None),
+        GotcritterEvent::BetRefunded(e) => (Some(e.bettor), None, Some(e.value)),
+        GotcritterEvent::BetWithdrawn(e) => (Some(e.bettor), Some(e.number), Some(e.value)),
+        GotcritterEvent::PauseStateChanged(e) => (Some(e.admin), None, None),
+        GotcritterEvent::ReferralRewardsWithdrawn(e) => (Some(e.referrer), None, Some(e.amount)),
+        GotcritterEvent::GameMigrated(_) => (None, None, None),
+        GotcritterEvent::BetMigrated(_) => (None, None, None),
+        GotcritterEvent::SyndicateCreated(e) => (Some(e.manager), Some(e.number), None),
+        GotcritterEvent::SyndicateDeposit(e) => (Some(e.member), None, Some(e.amount)),
+        GotcritterEvent::SyndicateWithdrawal(e) => (Some(e.member), None, Some(e.amount)),
+        GotcritterEvent::ReferrerRegistered(e) => (Some(e.referrer), None, None),
+        GotcritterEvent::ConfigUpdated(e) => (Some(e.admin), None, None),
+    };
+    let inserted = db.execute(
+        "INSERT OR IGNORE INTO events (signature, event_index, slot, name, game, wallet, number, amount, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            transaction.signature,
+            index as i64,
+            transaction.slot as i64,
+            event.name(),
+            event.game().map(|game| game.to_string()),
+            wallet.map(|wallet| wallet.to_string()),
+            number,
+            amount.map(|amount| amount as i64),
+            event.header().timestamp,
+        ],
+    )?;
+    Ok(inserted > 0)
+}
+
+// Updates games, bets and claims with a new event
+fn materialize(db: &Transaction, transaction: &RecordedTransaction, index: usize, event: &GotcritterEvent) -> Result<()> {
+    match event {
+        GotcritterEvent::GameCreated(e) => {
+            db.execute(
+                "INSERT INTO games (address, creator, private, created_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (address) DO UPDATE SET
+                    creator = excluded.creator, private = excluded.private, created_at = excluded.created_at",
+                params![e.header.game.to_string(), e.creator.to_string(), e.private, e.header.timestamp],
+            )?;
+        }
+        GotcritterEvent::BetPlaced(e) => {
+            db.execute(
+                "INSERT INTO bets (address, game, bettor, number, value, referrer, referral_fee, placed_at, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (address) DO UPDATE SET
+                    game = excluded.game, bettor = excluded.bettor, number = excluded.number, value = excluded.value,
+                    referrer = excluded.referrer, referral_fee = excluded.referral_fee,
+                    placed_at = excluded.placed_at, signature = excluded.signature",
+                params![
+                    e.bet.to_string(),
+                    e.header.game.to_string(),
+                    e.bettor.to_string(),
+                    e.number,
+                    e.value as i64,
+                    e.referrer.map(|referrer| referrer.to_string()),
+                    e.referral_fee as i64,
+                    e.header.timestamp,
+                    transaction.signature,
+                ],
+            )?;
+            db.execute(
+                "UPDATE games SET total_value = total_value + ?2, number_of_bets = number_of_bets + 1 WHERE address = ?1",
+                params![e.header.game.to_string(), e.value as i64],
+            )?;
+        }
+        GotcritterEvent::EndOfBettingPeriod(e) => {
+            db.execute(
+                "UPDATE games SET status = 'ended', closer = ?2, closer_reward = ?3, ended_at = ?4 WHERE address = ?1",
+                params![e.header.game.to_string(), e.closer.to_string(), e.reward as i64, e.header.timestamp],
+            )?;
+        }
+        GotcritterEvent::PrizeClaimed(e) => {
+            db.execute(
+                "INSERT INTO claims (signature, event_index, game, bettor, drawn_number, prize, claimed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    transaction.signature,
+                    index as i64,
+                    e.header.game.to_string(),
+                    e.bettor.to_string(),
+                    e.drawn_number,
+                    e.prize_value as i64,
+                    e.header.timestamp,
+                ],
+            )?;
+            db.execute(
+                "UPDATE games SET drawn_number = ?2, value_provided_to_winners = value_provided_to_winners + ?3
+                 WHERE address = ?1",
+                params![e.header.game.to_string(), e.drawn_number, e.prize_value as i64],
+            )?;
+        }
+        GotcritterEvent::GameCancelled(e) => {
+            db.execute("UPDATE games SET status = 'cancelled' WHERE address = ?1", [e.header.game.to_string()])?;
+        }
+        GotcritterEvent::BetRefunded(e) => {
+            db.execute("UPDATE bets SET refunded = 1 WHERE address = ?1", [e.bet.to_string()])?;
+            db.execute(
+                "UPDATE games SET total_value = total_value - ?2 WHERE address = ?1",
+                params![e.header.game.to_string(), e.value as i64],
+            )?;
+        }
+        // The penalty stays in the game pool
+        GotcritterEvent::BetWithdrawn(e) => {
+            db.execute("UPDATE bets SET refunded = 1 WHERE address = ?1", [e.bet.to_string()])?;
+            db.execute(
+                "UPDATE games SET total_value = total_value - ?2 WHERE address = ?1",
+                params![e.header.game.to_string(), e.value as i64],
+            )?;
+        }
+        // Only kept on the events table
+        GotcritterEvent::ParticipantsAdded(_)
+        | GotcritterEvent::PauseStateChanged(_)
+        | GotcritterEvent::ReferralRewardsWithdrawn(_)
+        | GotcritterEvent::GameMigrated(_)
+        | GotcritterEvent::BetMigrated(_)
+        | GotcritterEvent::SyndicateCreated(_)
+        | GotcritterEvent::SyndicateDeposit(_)
+        | GotcritterEvent::SyndicateWithdrawal(_)
+        | GotcritterEvent::ReferrerRegistered(_)
+        | GotcritterEvent::ConfigUpdated(_) => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,11 @@
+//! Indexer for the events of the gotcritter program. It reads the logs of the program transactions,
+//! decodes the events with `gotcritter-client` and materializes them into SQLite.
+//!
+//! - [`source`]: where the transactions come from, an RPC endpoint or a recorded fixture;
+//! - [`db`]: the SQLite schema, the ingestion of the events and the queries.
+
+pub mod db;
+pub mod source;
+
+pub use db::{Database, HistoryEntry, WalletPnl};
+pub use source::{RecordedTransaction, RpcSource};
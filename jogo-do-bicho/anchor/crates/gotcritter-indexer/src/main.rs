@@ -0,0 +1,105 @@
+// Indexes the events of the gotcritter program into SQLite and queries them
+//
+// to run:
+// cargo run -p gotcritter-indexer -- run
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use gotcritter_indexer::{source, Database, RpcSource};
+use solana_sdk::native_token::lamports_to_sol;
+use std::{path::PathBuf, thread::sleep, time::Duration};
+
+#[derive(Parser)]
+#[command(version, about = "Index the gotcritter events into SQLite")]
+struct Cli {
+    /// SQLite database file, created if missing
+    #[arg(long, global = true, default_value = "gotcritter.sqlite")]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Read the new transactions of the program from an RPC endpoint
+    Run {
+        /// RPC URL of the cluster
+        #[arg(short, long, default_value = "http://localhost:8899")]
+        url: String,
+        /// Index what is new and exit, instead of polling
+        #[arg(long)]
+        once: bool,
+        /// Seconds between the polls
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+        /// Also append the transactions read to this file, to be used as a fixture
+        #[arg(long)]
+        record: Option<PathBuf>,
+    },
+    /// Index the transactions recorded on a JSON Lines file
+    Import { file: PathBuf },
+    /// Show every event of a game
+    History { game: String },
+    /// Show what a wallet bet, won and lost
+    Pnl { wallet: String },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut db = Database::open(&cli.db)?;
+
+    match cli.command {
+        Command::Run { url, once, interval, record } => {
+            let rpc = RpcSource::new(url);
+            loop {
+                let transactions = rpc.transactions_after(db.cursor()?.as_deref())?;
+                if let Some(file) = &record {
+                    source::record(file, &transactions)?;
+                }
+                index(&mut db, &transactions)?;
+                if once {
+                    break;
+                }
+                sleep(Duration::from_secs(interval));
+            }
+        }
+        Command::Import { file } => index(&mut db, &source::load_fixture(file)?)?,
+        Command::History { game } => {
+            for entry in db.game_history(&game)? {
+                let mut line = format!("slot {:>10}  {:<24}", entry.slot, entry.name);
+                if let Some(wallet) = entry.wallet {
+                    line += &format!("  {:<44}", wallet);
+                }
+                if let Some(number) = entry.number {
+                    line += &format!("  number {:>2}", number);
+                }
+                if let Some(amount) = entry.amount {
+                    line += &format!("  {} SOL", lamports_to_sol(amount));
+                }
+                println!("{}", line);
+            }
+        }
+        Command::Pnl { wallet } => {
+            let pnl = db.wallet_pnl(&wallet)?;
+            println!("Bets:             {}", pnl.bets);
+            println!("Wagered:          {} SOL", lamports_to_sol(pnl.wagered));
+            println!("Refunded:         {} SOL", lamports_to_sol(pnl.refunded));
+            println!("Prizes:           {} SOL", lamports_to_sol(pnl.prizes));
+            println!("Closer rewards:   {} SOL", lamports_to_sol(pnl.closer_rewards));
+            println!("Referral rewards: {} SOL", lamports_to_sol(pnl.referral_rewards));
+            println!("Syndicates:       {} SOL in, {} SOL out", lamports_to_sol(pnl.syndicate_deposits), lamports_to_sol(pnl.syndicate_withdrawals));
+            println!("Net:              {} SOL", pnl.net() as f64 / 1_000_000_000.0);
+        }
+    }
+
+    Ok(())
+}
+
+fn index(db: &mut Database, transactions: &[source::RecordedTransaction]) -> Result<()> {
+    let mut events = 0;
+    for transaction in transactions {
+        events += db.apply(transaction)?;
+    }
+    println!("{} transactions read, {} new events", transactions.len(), events);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+// Sources of program transactions: a live RPC endpoint, or a JSON Lines file recorded from one
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use std::io::{BufRead, BufReader, Write};
+use std::{fs::File, path::Path, str::FromStr};
+
+const SIGNATURES_PAGE_SIZE: usize = 1_000; // the maximum accepted by the RPC
+
+// The part of a transaction the indexer needs, one per line on the fixtures
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    // failed transactions are kept only to move the cursor, their events never happened
+    #[serde(default)]
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+// Reads a JSON Lines file with one transaction per line
+pub fn load_fixture(path: impl AsRef<Path>) -> Result<Vec<RecordedTransaction>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("could not open {}", path.display()))?;
+    let mut transactions = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let transaction = serde_json::from_str(&line).with_context(|| format!("{}:{}", path.display(), index + 1))?;
+        transactions.push(transaction);
+    }
+    Ok(transactions)
+}
+
+// Appends transactions to a JSON Lines file, creating it if needed
+pub fn record(path: impl AsRef<Path>, transactions: &[RecordedTransaction]) -> Result<()> {
+    let mut file = File::options().create(true).append(true).open(path)?;
+    for transaction in transactions {
+        writeln!(file, "{}", serde_json::to_string(transaction)?)?;
+    }
+    Ok(())
+}
+
+pub struct RpcSource {
+    client: RpcClient,
+}
+
+impl RpcSource {
+    pub fn new(url: String) -> Self {
+        RpcSource {
+            client: RpcClient::new_with_commitment(url, CommitmentConfig::confirmed()),
+        }
+    }
+
+    // The transactions of the program after the given signature (or since the deploy), oldest first
+    pub fn transactions_after(&self, until: Option<&str>) -> Result<Vec<RecordedTransaction>> {
+        let until = until.map(Signature::from_str).transpose()?;
+
+        // The RPC returns the newest signatures first, so the pages are fetched backwards
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.client.get_signatures_for_address_with_config(
+                &gotcritter_client::ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURES_PAGE_SIZE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )?;
+            let last_page = page.len() < SIGNATURES_PAGE_SIZE;
+            before = page.last().map(|status| Signature::from_str(&status.signature)).transpose()?;
+            signatures.extend(page);
+            if last_page {
+                break;
+            }
+        }
+
+        let mut transactions = Vec::with_capacity(signatures.len());
+        for status in signatures.into_iter().rev() {
+            let failed = status.err.is_some();
+            let logs = if failed { vec![] } else { self.logs(&Signature::from_str(&status.signature)?)? };
+            transactions.push(RecordedTransaction {
+                signature: status.signature,
+                slot: status.slot,
+                block_time: status.block_time,
+                failed,
+                logs,
+            });
+        }
+        Ok(transactions)
+    }
+
+    fn logs(&self, signature: &Signature) -> Result<Vec<String>> {
+        let transaction = self.client.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let logs: Option<Vec<String>> = transaction.transaction.meta.and_then(|meta| meta.log_messages.into());
+        Ok(logs.unwrap_or_default())
+    }
+}
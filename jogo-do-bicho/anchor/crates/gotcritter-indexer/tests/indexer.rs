@@ -0,0 +1,177 @@
+// Indexes the recorded transactions of tests/fixtures and checks the materialized tables and the queries.
+//
+// transactions.jsonl has, in order:
+// - a public game (GAME) created by CREATOR;
+// - 3 bets: BETTOR_1 on 7, BETTOR_2 on 7 referred by REFERRER and BETTOR_2 on 12;
+// - a failed bet of BETTOR_1 on 3, whose event must be ignored;
+// - the end of the betting period by CLOSER, with 7 drawn, and the claims of both winners;
+// - a private game (CANCELLED_GAME) created and cancelled by BETTOR_1;
+// - the withdrawal of the referral rewards of REFERRER.
+use gotcritter_indexer::{source::load_fixture, Database, RecordedTransaction, WalletPnl};
+
+const GAME: &str = "45Qi2p9x3yAqPQQXJj7mjjqPxAKgMTwkSWiqWVQ6WiZH";
+const CANCELLED_GAME: &str = "6XtUX4gTK1sx2DfiE8G9TF2dTnEKdveANHBjaxBwTng3";
+const CREATOR: &str = "BZscqFpDkCSvfofoCcFBhqRD8e3dEuG2SF5eYZPPHJCz";
+const BETTOR_1: &str = "HSX1axvvn4fjKEAN8PH2Fc625CkbqnYqsJYw92giEk5i";
+const BETTOR_2: &str = "8qeTqAY6PzBLLDtBL9bN2HaS9zp33WFgTcFz6UM4tTKe";
+const CLOSER: &str = "6QqMqxLKhGCBs16sT12zaSLg4ssi2sNmoMeSGsnT1v6P";
+const REFERRER: &str = "7tbj3ubegYNSGst4u4D3YjzAJqiD5Gpvtkddf6Tv3bxX";
+const FAILED_BET: &str = "EdC2QiJDJ7uKQuN4KMYfbcKAwSrnCiTe1S2em2EVLpsF";
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+fn fixture() -> Vec<RecordedTransaction> {
+    load_fixture(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/transactions.jsonl")).unwrap()
+}
+
+fn indexed() -> Database {
+    let mut db = Database::open_in_memory().unwrap();
+    for transaction in fixture() {
+        db.apply(&transaction).unwrap();
+    }
+    db
+}
+
+#[test]
+fn materializes_games_bets_and_claims() {
+    let db = indexed();
+    let connection = db.connection();
+
+    let (creator, status, total_value, number_of_bets, closer, drawn_number, paid): (
+        String,
+        String,
+        i64,
+        i64,
+        String,
+        u8,
+        i64,
+    ) = connection
+        .query_row(
+            "SELECT creator, status, total_value, number_of_bets, closer, drawn_number, value_provided_to_winners
+             FROM games WHERE address = ?1",
+            [GAME],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?)),
+        )
+        .unwrap();
+    assert_eq!(creator, CREATOR);
+    assert_eq!(status, "ended");
+    assert_eq!(total_value as u64, 2_400_000_000); // the referral fee doesn't go to the pool
+    assert_eq!(number_of_bets, 3);
+    assert_eq!(closer, CLOSER);
+    assert_eq!(drawn_number, 7);
+    assert_eq!(paid as u64, 2_389_999_999);
+
+    let (status, private): (String, bool) = connection
+        .query_row("SELECT status, private FROM games WHERE address = ?1", [CANCELLED_GAME], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(status, "cancelled");
+    assert!(private);
+
+    let bets: i64 = connection.query_row("SELECT COUNT(*) FROM bets WHERE game = ?1", [GAME], |row| row.get(0)).unwrap();
+    assert_eq!(bets, 3);
+    let failed_bets: i64 =
+        connection.query_row("SELECT COUNT(*) FROM bets WHERE address = ?1", [FAILED_BET], |row| row.get(0)).unwrap();
+    assert_eq!(failed_bets, 0);
+
+    let referrer: String = connection
+        .query_row("SELECT referrer FROM bets WHERE bettor = ?1", [BETTOR_2], |row| row.get(0))
+        .unwrap();
+    assert_eq!(referrer, REFERRER);
+
+    let claims: i64 = connection.query_row("SELECT COUNT(*) FROM claims", [], |row| row.get(0)).unwrap();
+    assert_eq!(claims, 2);
+}
+
+#[test]
+fn game_history_is_in_order() {
+    let db = indexed();
+    let history = db.game_history(GAME).unwrap();
+    let names: Vec<&str> = history.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "GameCreated",
+            "BetPlaced",
+            "BetPlaced",
+            "BetPlaced",
+            "EndOfBettingPeriod",
+            "PrizeClaimed",
+            "PrizeClaimed"
+        ]
+    );
+    assert_eq!(history[1].wallet.as_deref(), Some(BETTOR_1));
+    assert_eq!(history[1].number, Some(7));
+    assert_eq!(history[1].amount, Some(LAMPORTS_PER_SOL));
+    assert!(history.windows(2).all(|pair| pair[0].slot <= pair[1].slot));
+
+    let names: Vec<String> = db.game_history(CANCELLED_GAME).unwrap().into_iter().map(|entry| entry.name).collect();
+    assert_eq!(names, ["GameCreated", "GameCancelled"]);
+}
+
+#[test]
+fn wallet_pnl() {
+    let db = indexed();
+
+    let bettor_1 = db.wallet_pnl(BETTOR_1).unwrap();
+    assert_eq!(
+        bettor_1,
+        WalletPnl {
+            bets: 1,
+            wagered: LAMPORTS_PER_SOL,
+            prizes: 1_257_894_736,
+            ..Default::default()
+        }
+    );
+    assert_eq!(bettor_1.net(), 257_894_736);
+
+    // the referral fee is paid by the bettor
+    let bettor_2 = db.wallet_pnl(BETTOR_2).unwrap();
+    assert_eq!(bettor_2.bets, 2);
+    assert_eq!(bettor_2.wagered, 1_500_000_000);
+    assert_eq!(bettor_2.net(), 1_132_105_263 - 1_500_000_000);
+
+    assert_eq!(db.wallet_pnl(CLOSER).unwrap().closer_rewards, 10_000_000);
+    assert_eq!(db.wallet_pnl(REFERRER).unwrap().referral_rewards, 100_000_000);
+    assert_eq!(db.wallet_pnl(CREATOR).unwrap(), WalletPnl::default());
+}
+
+#[test]
+fn applying_again_changes_nothing() {
+    let mut db = indexed();
+    let transactions = fixture();
+
+    for transaction in &transactions {
+        assert_eq!(db.apply(transaction).unwrap(), 0);
+    }
+    assert_eq!(db.cursor().unwrap(), transactions.last().map(|transaction| transaction.signature.clone()));
+    assert_eq!(db.wallet_pnl(BETTOR_1).unwrap().wagered, LAMPORTS_PER_SOL);
+    assert_eq!(db.game_history(GAME).unwrap().len(), 7);
+}
+
+#[test]
+fn survives_reopening_the_database() {
+    let path = std::env::temp_dir().join(format!("gotcritter-indexer-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let transactions = fixture();
+
+    // the indexer stops halfway and starts again from the cursor
+    let (first, second) = transactions.split_at(5);
+    let mut db = Database::open(&path).unwrap();
+    for transaction in first {
+        db.apply(transaction).unwrap();
+    }
+    drop(db);
+
+    let mut db = Database::open(&path).unwrap();
+    assert_eq!(db.cursor().unwrap().as_deref(), Some(first[4].signature.as_str()));
+    for transaction in second {
+        db.apply(transaction).unwrap();
+    }
+    assert_eq!(db.game_history(GAME).unwrap().len(), 7);
+    assert_eq!(db.wallet_pnl(BETTOR_2).unwrap().prizes, 1_132_105_263);
+
+    drop(db);
+    std::fs::remove_file(&path).unwrap();
+}
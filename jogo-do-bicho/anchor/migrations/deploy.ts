@@ -4,10 +4,16 @@
 
 const anchor = require("@coral-xyz/anchor");
 import { AnchorProvider } from '@coral-xyz/anchor'
+import { getConfigAddress, getGotcritterProgram } from '../src/gotcritter-exports'
 
 module.exports = async function (provider: AnchorProvider) {
   // Configure client to use the provider.
   anchor.setProvider(provider);
 
-  // Add your deploy script here.
+  // Create the program config right after the deploy, the deployer becomes the admin
+  const program = getGotcritterProgram(provider);
+  const config = await provider.connection.getAccountInfo(getConfigAddress(program.programId));
+  if (!config) {
+    await program.methods.initializeConfig().accounts({ admin: provider.publicKey }).rpc();
+  }
 };
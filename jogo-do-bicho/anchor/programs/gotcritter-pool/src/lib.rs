@@ -0,0 +1,375 @@
+// Example of a program betting on gotcritter through CPI. Members deposit into a pool, the manager places one bet
+// with everything deposited and, if the bet wins, each member withdraws a share of the prize proportional to their deposit.
+//
+// The pool account (a PDA with data, owned by this program) is the bettor: it signs place_bet_with_payer and claim_prize
+// with its seeds and receives the prize. The deposits are kept in the vault, a PDA without data owned by the system program,
+// because only an account like that can pay with a system transfer. It signs as the payer of the bet.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use gotcritter::program::Gotcritter;
+use gotcritter::{Bet, Game, PrizeTier};
+
+declare_id!("5xk4hPt5GcuoSoyaJpyyDAVfDrdTBuydjmFd9qGKBL4x");
+
+const DISC_SIZE: usize = 8; // the discriminator size
+
+#[program]
+pub mod gotcritter_pool {
+    use super::*;
+
+    // Method to create a pool betting on a number of a game, the signer becomes the manager
+    pub fn create_pool(ctx: Context<CreatePool>, number: u8) -> Result<()> {
+        // Check if the bet number is valid, the same rule as gotcritter
+        require!((1..=25).contains(&number), PoolError::InvalidNumber);
+
+        // Check if the game still accepts bets
+        require!(!ctx.accounts.game.load()?.is_betting_period_ended(), PoolError::BettingPeriodHasEnded);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.manager = ctx.accounts.manager.key(); // the only one allowed to place the bet
+        pool.game = ctx.accounts.game.key(); // the game the pool bets on
+        pool.number = number; // the number the pool bets on
+        pool.bump = ctx.bumps.pool;
+        pool.vault_bump = ctx.bumps.vault;
+
+        Ok(())
+    }
+
+    // Method to deposit into the pool before the bet is placed, one deposit per member
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        // Check if the bet was not placed yet, after that the deposits are already in the game
+        require!(ctx.accounts.pool.bet == Pubkey::default(), PoolError::BetAlreadyPlaced);
+
+        // Transfer the deposit from the member to the vault
+        invoke(
+            &system_instruction::transfer(ctx.accounts.member.key, ctx.accounts.vault.key, amount),
+            &[
+                ctx.accounts.member.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let membership = &mut ctx.accounts.membership;
+        membership.pool = ctx.accounts.pool.key();
+        membership.member = ctx.accounts.member.key();
+        membership.amount = amount;
+
+        ctx.accounts.pool.total_deposited += amount;
+
+        Ok(())
+    }
+
+    // Method to bet everything deposited on the pool number, through CPI
+    pub fn place_bet(ctx: Context<PlacePoolBet>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+
+        // Check if the bet was not placed yet, the pool has only one bet
+        require!(pool.bet == Pubkey::default(), PoolError::BetAlreadyPlaced);
+
+        // The vault also pays the rent of the bet account, which comes back to the pool when the prize is claimed
+        let bet_rent = Rent::get()?.minimum_balance(DISC_SIZE + Bet::INIT_SPACE);
+        let value = ctx.accounts.vault.lamports().saturating_sub(bet_rent);
+
+        // The pool signs as the bettor and the vault as the payer
+        let game_key = pool.game;
+        let manager_key = pool.manager;
+        let pool_key = pool.key();
+        let pool_seeds: &[&[u8]] = &[b"pool", game_key.as_ref(), manager_key.as_ref(), &[pool.bump]];
+        let vault_seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &[pool.vault_bump]];
+        let signer_seeds = &[pool_seeds, vault_seeds];
+
+        let cpi_accounts = gotcritter::cpi::accounts::PlaceBetWithPayer {
+            game: ctx.accounts.game.to_account_info(),
+            config: ctx.accounts.config.to_account_info(),
+            bettor: ctx.accounts.pool.to_account_info(),
+            payer: ctx.accounts.vault.to_account_info(),
+            bet: ctx.accounts.bet.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            recent_blockhashes: ctx.accounts.recent_blockhashes.to_account_info(),
+            referral: None,
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.gotcritter_program.to_account_info(), cpi_accounts, signer_seeds);
+        gotcritter::cpi::place_bet_with_payer(cpi_context, pool.number, value)?;
+
+        // When the bet ends the betting period gotcritter doesn't place it, the whole transaction is reverted
+        // so the deposits stay in the vault and the members can withdraw them
+        let bet = Bet::try_deserialize(&mut &ctx.accounts.bet.try_borrow_data()?[..])?;
+        require!(bet.game == game_key, PoolError::BettingPeriodHasEnded);
+
+        ctx.accounts.pool.bet = ctx.accounts.bet.key();
+
+        Ok(())
+    }
+
+    // Method to claim the prize of the pool bet, through CPI. Anyone can call it, the prize goes to the pool
+    pub fn claim_prize(ctx: Context<ClaimPoolPrize>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let balance_before = pool.to_account_info().lamports();
+
+        let pool_seeds: &[&[u8]] = &[b"pool", pool.game.as_ref(), pool.manager.as_ref(), &[pool.bump]];
+        let signer_seeds = &[pool_seeds];
+
+        let cpi_accounts = gotcritter::cpi::accounts::ClaimPrize {
+            game: ctx.accounts.game.to_account_info(),
+            bettor: ctx.accounts.pool.to_account_info(),
+            bet: ctx.accounts.bet.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.gotcritter_program.to_account_info(), cpi_accounts, signer_seeds);
+        gotcritter::cpi::claim_prize(cpi_context)?;
+
+        // The prize and the rent of the closed bet, split among the members
+        let pool = &mut ctx.accounts.pool;
+        pool.prize = pool.to_account_info().lamports() - balance_before;
+        pool.claimed = true;
+
+        Ok(())
+    }
+
+    // Method to get the pool bet back while gotcritter is paused, through CPI. Anyone can call it, the value and
+    // the rent of the bet go to the pool and are split among the members like a prize
+    pub fn refund(ctx: Context<RefundPoolBet>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let balance_before = pool.to_account_info().lamports();
+
+        let pool_seeds: &[&[u8]] = &[b"pool", pool.game.as_ref(), pool.manager.as_ref(), &[pool.bump]];
+        let signer_seeds = &[pool_seeds];
+
+        let cpi_accounts = gotcritter::cpi::accounts::RefundBet {
+            game: ctx.accounts.game.to_account_info(),
+            config: ctx.accounts.config.to_account_info(),
+            bettor: ctx.accounts.pool.to_account_info(),
+            bet: ctx.accounts.bet.to_account_info(),
+        };
+        let cpi_context = CpiContext::new_with_signer(ctx.accounts.gotcritter_program.to_account_info(), cpi_accounts, signer_seeds);
+        gotcritter::cpi::refund_bet(cpi_context)?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.prize = pool.to_account_info().lamports() - balance_before;
+        pool.claimed = true;
+
+        Ok(())
+    }
+
+    // Method to leave the pool, closing the membership. Before the bet the deposit is returned,
+    // after the claim the member receives its share of the prize, and a lost bet returns nothing
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let amount = ctx.accounts.membership.amount;
+
+        if pool.bet == Pubkey::default() {
+            // The bet was not placed, the deposit is still in the vault
+            let pool_key = pool.key();
+            let vault_seeds: &[&[u8]] = &[b"vault", pool_key.as_ref(), &[pool.vault_bump]];
+            invoke_signed(
+                &system_instruction::transfer(ctx.accounts.vault.key, ctx.accounts.member.key, amount),
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    ctx.accounts.member.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                &[vault_seeds],
+            )?;
+
+            ctx.accounts.pool.total_deposited -= amount;
+        } else if pool.claimed {
+            // The share of the prize (or of the refund) proportional to the deposit, the rounding dust stays in the pool
+            let share = ((pool.prize as u128) * (amount as u128) / (pool.total_deposited as u128)) as u64;
+            **ctx.accounts.pool.to_account_info().try_borrow_mut_lamports()? -= share;
+            **ctx.accounts.member.to_account_info().try_borrow_mut_lamports()? += share;
+        } else {
+            // Without a claim, the member can only leave when the pool lost, a neighbour or group tier also wins
+            let game = ctx.accounts.game.load()?;
+            let drawn_number = game.confirmed_drawn_number().ok_or(PoolError::PrizeNotClaimed)?;
+            require!(game.prize_tier(pool.number, drawn_number) == PrizeTier::None, PoolError::PrizeNotClaimed);
+        }
+
+        Ok(())
+    }
+}
+
+// Games created before the zero-copy layout of gotcritter are smaller, loading them would read past the end of the data
+fn has_current_layout(game: &AccountLoader<Game>) -> bool {
+    game.as_ref().data_len() >= DISC_SIZE + Game::INIT_SPACE
+}
+
+#[derive(Accounts)]
+pub struct CreatePool<'info> {
+    // Initialize the pool with the manager as the payer, one pool per game and manager
+    #[account(
+        init,
+        payer = manager,
+        space = DISC_SIZE + Pool::INIT_SPACE, // discriminator + pool space
+        seeds = [b"pool", game.key().as_ref(), manager.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: Only the bump is stored, the vault is created by the first deposit
+    #[account(seeds = [b"vault", pool.key().as_ref()], bump)]
+    pub vault: UncheckedAccount<'info>,
+    // The gotcritter game the pool bets on
+    #[account(constraint = has_current_layout(&game) @ PoolError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    #[account(mut)]
+    pub manager: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    // The pool to deposit into
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: Holds the deposits, only its address is checked
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    // Initialize the membership with the member as the payer, one per member
+    #[account(
+        init,
+        payer = member,
+        space = DISC_SIZE + Membership::INIT_SPACE, // discriminator + membership space
+        seeds = [b"membership", pool.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub membership: Account<'info, Membership>,
+    #[account(mut)]
+    pub member: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlacePoolBet<'info> {
+    // The pool placing the bet, it signs as the bettor
+    #[account(
+        mut,
+        seeds = [b"pool", pool.game.as_ref(), pool.manager.as_ref()],
+        bump = pool.bump,
+        has_one = manager @ PoolError::Unauthorized,
+        has_one = game
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: Holds the deposits and signs as the payer of the bet
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    // The manager of the pool, the only one allowed to place the bet
+    pub manager: Signer<'info>,
+    /// CHECK: Checked by gotcritter
+    #[account(mut)]
+    pub game: UncheckedAccount<'info>,
+    /// CHECK: Checked by gotcritter
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Initialized by gotcritter, the address is derived from the pool and the number of bets of the game
+    #[account(mut)]
+    pub bet: UncheckedAccount<'info>,
+    /// CHECK: Checked by gotcritter
+    pub recent_blockhashes: UncheckedAccount<'info>,
+    pub gotcritter_program: Program<'info, Gotcritter>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPoolPrize<'info> {
+    // The pool that placed the bet, it signs as the bettor and receives the prize
+    #[account(
+        mut,
+        seeds = [b"pool", pool.game.as_ref(), pool.manager.as_ref()],
+        bump = pool.bump,
+        has_one = game,
+        has_one = bet
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: Checked by gotcritter
+    #[account(mut)]
+    pub game: UncheckedAccount<'info>,
+    /// CHECK: Checked by gotcritter, closed to the pool
+    #[account(mut)]
+    pub bet: UncheckedAccount<'info>,
+    pub gotcritter_program: Program<'info, Gotcritter>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundPoolBet<'info> {
+    // The pool that placed the bet, it signs as the bettor and receives the refund
+    #[account(
+        mut,
+        seeds = [b"pool", pool.game.as_ref(), pool.manager.as_ref()],
+        bump = pool.bump,
+        has_one = game,
+        has_one = bet
+    )]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: Checked by gotcritter
+    #[account(mut)]
+    pub game: UncheckedAccount<'info>,
+    /// CHECK: Checked by gotcritter, refunds only while it is paused
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Checked by gotcritter, closed to the pool
+    #[account(mut)]
+    pub bet: UncheckedAccount<'info>,
+    pub gotcritter_program: Program<'info, Gotcritter>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    // The pool to leave
+    #[account(mut, has_one = game)]
+    pub pool: Account<'info, Pool>,
+    /// CHECK: Holds the deposits before the bet, only its address is checked
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump = pool.vault_bump)]
+    pub vault: UncheckedAccount<'info>,
+    // The game of the pool, used to know if the bet lost
+    #[account(constraint = has_current_layout(&game) @ PoolError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    // The membership of the member, closed to them
+    #[account(
+        mut,
+        seeds = [b"membership", pool.key().as_ref(), member.key().as_ref()],
+        bump,
+        close = member
+    )]
+    pub membership: Account<'info, Membership>,
+    #[account(mut)]
+    pub member: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub manager: Pubkey,
+    pub game: Pubkey,
+    pub number: u8,
+    pub total_deposited: u64,
+    pub bet: Pubkey, // the default pubkey until the bet is placed
+    pub prize: u64, // the lamports received on the claim or the refund, including the rent of the bet
+    pub claimed: bool, // set by claim_prize and refund
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Membership {
+    pub pool: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum PoolError {
+    #[msg("Invalid number. Must be between 1 and 25")]
+    InvalidNumber,
+    #[msg("The betting period has ended")]
+    BettingPeriodHasEnded,
+    #[msg("The pool already placed its bet")]
+    BetAlreadyPlaced,
+    #[msg("Only the manager can perform this action")]
+    Unauthorized,
+    #[msg("The prize of the pool was not claimed yet")]
+    PrizeNotClaimed,
+    #[msg("The game has an older layout, it must be migrated with migrate_game")]
+    GameNeedsMigration,
+}
@@ -0,0 +1,333 @@
+// The pool program runs with gotcritter on the same in-process bank, the gotcritter helpers are shared
+#[path = "../../gotcritter/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use common::*;
+use gotcritter::{CustomError, PrizeTiers};
+use gotcritter_pool::{Membership, Pool, PoolError};
+use solana_program_test::{processor, BanksClientError, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    sysvar::recent_blockhashes,
+};
+
+const NUMBER: u8 = 7; // the number every pool of these tests bets on
+
+// Same as the gotcritter processor, anchor needs the accounts to live as long as 'info
+fn process_pool_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    gotcritter_pool::entry(program_id, accounts, data)
+}
+
+async fn start_with_pool() -> ProgramTestContext {
+    let mut program_test = program_test();
+    program_test.add_program("gotcritter_pool", gotcritter_pool::ID, processor!(process_pool_instruction));
+    start_with(program_test).await
+}
+
+fn pool_address(game: &Pubkey, manager: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pool", game.as_ref(), manager.as_ref()], &gotcritter_pool::ID).0
+}
+
+fn vault_address(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", pool.as_ref()], &gotcritter_pool::ID).0
+}
+
+fn membership_address(pool: &Pubkey, member: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"membership", pool.as_ref(), member.as_ref()], &gotcritter_pool::ID).0
+}
+
+fn pool_error(error: PoolError) -> u32 {
+    error.into()
+}
+
+async fn fetch_pool(context: &mut ProgramTestContext, pool: Pubkey) -> Pool {
+    let account = context.banks_client.get_account(pool).await.unwrap().expect("pool not found");
+    Pool::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+async fn create_pool(context: &mut ProgramTestContext, game: Pubkey, manager: &Keypair) -> Pubkey {
+    let pool = pool_address(&game, &manager.pubkey());
+    let instruction = Instruction {
+        program_id: gotcritter_pool::ID,
+        accounts: gotcritter_pool::accounts::CreatePool {
+            pool,
+            vault: vault_address(&pool),
+            game,
+            manager: manager.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter_pool::instruction::CreatePool { number: NUMBER }.data(),
+    };
+    process(context, &[instruction], &[manager]).await.unwrap();
+    pool
+}
+
+async fn deposit(context: &mut ProgramTestContext, pool: Pubkey, member: &Keypair, amount: u64) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter_pool::ID,
+        accounts: gotcritter_pool::accounts::Deposit {
+            pool,
+            vault: vault_address(&pool),
+            membership: membership_address(&pool, &member.pubkey()),
+            member: member.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter_pool::instruction::Deposit { amount }.data(),
+    };
+    process(context, &[instruction], &[member]).await
+}
+
+async fn place_pool_bet(context: &mut ProgramTestContext, pool: Pubkey, manager: &Keypair) -> Result<Pubkey, BanksClientError> {
+    let game = fetch_pool(context, pool).await.game;
+    let number_of_bets = fetch_game(context, game).await.number_of_bets;
+    let bet = bet_address(&game, &pool, number_of_bets);
+    let instruction = Instruction {
+        program_id: gotcritter_pool::ID,
+        accounts: gotcritter_pool::accounts::PlacePoolBet {
+            pool,
+            vault: vault_address(&pool),
+            manager: manager.pubkey(),
+            game,
+            config: config_address(),
+            bet,
+            recent_blockhashes: recent_blockhashes::ID,
+            gotcritter_program: gotcritter::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter_pool::instruction::PlaceBet {}.data(),
+    };
+    process(context, &[instruction], &[manager]).await?;
+    Ok(bet)
+}
+
+async fn claim_pool_prize(context: &mut ProgramTestContext, pool: Pubkey) -> Result<(), BanksClientError> {
+    let pool_account = fetch_pool(context, pool).await;
+    let instruction = Instruction {
+        program_id: gotcritter_pool::ID,
+        accounts: gotcritter_pool::accounts::ClaimPoolPrize {
+            pool,
+            game: pool_account.game,
+            bet: pool_account.bet,
+            gotcritter_program: gotcritter::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter_pool::instruction::ClaimPrize {}.data(),
+    };
+    process(context, &[instruction], &[]).await
+}
+
+async fn refund_pool_bet(context: &mut ProgramTestContext, pool: Pubkey) -> Result<(), BanksClientError> {
+    let pool_account = fetch_pool(context, pool).await;
+    let instruction = Instruction {
+        program_id: gotcritter_pool::ID,
+        accounts: gotcritter_pool::accounts::RefundPoolBet {
+            pool,
+            game: pool_account.game,
+            config: config_address(),
+            bet: pool_account.bet,
+            gotcritter_program: gotcritter::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter_pool::instruction::Refund {}.data(),
+    };
+    process(context, &[instruction], &[]).await
+}
+
+async fn withdraw(context: &mut ProgramTestContext, pool: Pubkey, member: &Keypair) -> Result<(), BanksClientError> {
+    let game = fetch_pool(context, pool).await.game;
+    let instruction = Instruction {
+        program_id: gotcritter_pool::ID,
+        accounts: gotcritter_pool::accounts::Withdraw {
+            pool,
+            vault: vault_address(&pool),
+            game,
+            membership: membership_address(&pool, &member.pubkey()),
+            member: member.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter_pool::instruction::Withdraw {}.data(),
+    };
+    process(context, &[instruction], &[member]).await
+}
+
+#[tokio::test]
+async fn bets_as_a_pool_and_splits_the_prize() {
+    let mut context = start_with_pool().await;
+    let creator = context.payer.insecure_clone();
+    let manager = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let big_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let small_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let other_bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let pool = create_pool(&mut context, game, &manager).await;
+    deposit(&mut context, pool, &big_member, 3 * LAMPORTS_PER_SOL).await.unwrap();
+    deposit(&mut context, pool, &small_member, LAMPORTS_PER_SOL).await.unwrap();
+
+    // Only the manager places the bet, with everything deposited but the rent of the bet
+    let result = place_pool_bet(&mut context, pool, &big_member).await.map(|_| ());
+    assert_eq!(custom_error(result), pool_error(PoolError::Unauthorized));
+    let bet = place_pool_bet(&mut context, pool, &manager).await.unwrap();
+    let bet_rent = balance(&mut context, bet).await;
+    let bet_account = fetch_bet(&mut context, bet).await;
+    assert_eq!(bet_account.bettor, pool);
+    assert_eq!(bet_account.number, NUMBER);
+    assert_eq!(bet_account.value, 4 * LAMPORTS_PER_SOL - bet_rent);
+    assert_eq!(balance(&mut context, vault_address(&pool)).await, 0);
+
+    // No more deposits after the bet
+    let late_member = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let result = deposit(&mut context, pool, &late_member, MIN_BET_VALUE).await;
+    assert_eq!(custom_error(result), pool_error(PoolError::BetAlreadyPlaced));
+
+    fill_game(&mut context, game, &other_bettor).await;
+    finish_game(&mut context, game, NUMBER).await;
+
+    // Anyone can claim, the prize goes to the pool
+    let game_account = fetch_game(&mut context, game).await;
+    let prize = game_account.calculate_prize(&bet_account, NUMBER).unwrap();
+    claim_pool_prize(&mut context, pool).await.unwrap();
+    let pool_account = fetch_pool(&mut context, pool).await;
+    assert!(pool_account.claimed);
+    assert_eq!(pool_account.prize, prize + bet_rent);
+
+    // Each member receives the share of their deposit and the rent of the membership
+    let membership_rent = balance(&mut context, membership_address(&pool, &big_member.pubkey())).await;
+    let big_balance = balance(&mut context, big_member.pubkey()).await;
+    withdraw(&mut context, pool, &big_member).await.unwrap();
+    assert_eq!(
+        balance(&mut context, big_member.pubkey()).await,
+        big_balance + pool_account.prize * 3 / 4 + membership_rent
+    );
+
+    let small_balance = balance(&mut context, small_member.pubkey()).await;
+    withdraw(&mut context, pool, &small_member).await.unwrap();
+    assert_eq!(
+        balance(&mut context, small_member.pubkey()).await,
+        small_balance + pool_account.prize / 4 + membership_rent
+    );
+    assert!(!account_exists(&mut context, membership_address(&pool, &small_member.pubkey())).await);
+}
+
+#[tokio::test]
+async fn returns_deposits_before_the_bet_and_nothing_after_losing() {
+    let mut context = start_with_pool().await;
+    let creator = context.payer.insecure_clone();
+    let manager = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let leaving_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let other_bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let pool = create_pool(&mut context, game, &manager).await;
+    deposit(&mut context, pool, &leaving_member, 2 * LAMPORTS_PER_SOL).await.unwrap();
+    deposit(&mut context, pool, &member, LAMPORTS_PER_SOL).await.unwrap();
+
+    // Before the bet the deposit is returned
+    let balance_before = balance(&mut context, leaving_member.pubkey()).await;
+    let membership_rent = balance(&mut context, membership_address(&pool, &leaving_member.pubkey())).await;
+    withdraw(&mut context, pool, &leaving_member).await.unwrap();
+    assert_eq!(
+        balance(&mut context, leaving_member.pubkey()).await,
+        balance_before + 2 * LAMPORTS_PER_SOL + membership_rent
+    );
+    assert_eq!(fetch_pool(&mut context, pool).await.total_deposited, LAMPORTS_PER_SOL);
+
+    let bet = place_pool_bet(&mut context, pool, &manager).await.unwrap();
+    let result = place_pool_bet(&mut context, pool, &manager).await.map(|_| ());
+    assert_eq!(custom_error(result), pool_error(PoolError::BetAlreadyPlaced));
+
+    // While the game is open, the member can't leave
+    let result = withdraw(&mut context, pool, &member).await;
+    assert_eq!(custom_error(result), pool_error(PoolError::PrizeNotClaimed));
+
+    fill_game(&mut context, game, &other_bettor).await;
+    finish_game(&mut context, game, NUMBER % 25 + 1).await;
+
+    // The pool lost, there is nothing to claim and the member only gets the rent of the membership back
+    let result = claim_pool_prize(&mut context, pool).await;
+    assert_eq!(custom_error(result), error_code(CustomError::NoPrize));
+    let balance_before = balance(&mut context, member.pubkey()).await;
+    let membership = membership_address(&pool, &member.pubkey());
+    let membership_rent = balance(&mut context, membership).await;
+    let membership_account = context.banks_client.get_account(membership).await.unwrap().unwrap();
+    assert_eq!(Membership::try_deserialize(&mut membership_account.data.as_slice()).unwrap().amount, LAMPORTS_PER_SOL);
+    withdraw(&mut context, pool, &member).await.unwrap();
+    assert_eq!(balance(&mut context, member.pubkey()).await, balance_before + membership_rent);
+    assert!(account_exists(&mut context, bet).await);
+}
+
+#[tokio::test]
+async fn waits_for_the_claim_when_a_prize_tier_wins() {
+    let mut context = start_with_pool().await;
+    let creator = context.payer.insecure_clone();
+    let manager = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let other_bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let prize_tiers = PrizeTiers { neighbour_bps: 2_000, group_bps: 0 };
+    let game = create_tiered_game(&mut context, &creator, 100, prize_tiers).await.unwrap();
+    let pool = create_pool(&mut context, game, &manager).await;
+    deposit(&mut context, pool, &member, LAMPORTS_PER_SOL).await.unwrap();
+    place_pool_bet(&mut context, pool, &manager).await.unwrap();
+
+    fill_game(&mut context, game, &other_bettor).await;
+    finish_game(&mut context, game, NUMBER + 1).await;
+
+    // The pool number is a neighbour of the drawn number, so the pool didn't lose
+    let result = withdraw(&mut context, pool, &member).await;
+    assert_eq!(custom_error(result), pool_error(PoolError::PrizeNotClaimed));
+
+    claim_pool_prize(&mut context, pool).await.unwrap();
+    assert!(fetch_pool(&mut context, pool).await.prize > 0);
+    withdraw(&mut context, pool, &member).await.unwrap();
+}
+
+#[tokio::test]
+async fn refunds_the_pool_bet_while_gotcritter_is_paused() {
+    let mut context = start_with_pool().await;
+    let admin = context.payer.insecure_clone();
+    let manager = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let big_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let small_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+    let pool = create_pool(&mut context, game, &manager).await;
+    deposit(&mut context, pool, &big_member, 3 * LAMPORTS_PER_SOL).await.unwrap();
+    deposit(&mut context, pool, &small_member, LAMPORTS_PER_SOL).await.unwrap();
+    let bet = place_pool_bet(&mut context, pool, &manager).await.unwrap();
+
+    // Refunds only while paused
+    let result = refund_pool_bet(&mut context, pool).await;
+    assert_eq!(custom_error(result), error_code(CustomError::ProgramNotPaused));
+
+    set_paused(&mut context, &admin, true).await.unwrap();
+    refund_pool_bet(&mut context, pool).await.unwrap();
+    assert!(!account_exists(&mut context, bet).await);
+
+    // The value and the rent of the bet are everything deposited, split like a prize
+    let pool_account = fetch_pool(&mut context, pool).await;
+    assert!(pool_account.claimed);
+    assert_eq!(pool_account.prize, 4 * LAMPORTS_PER_SOL);
+
+    let membership_rent = balance(&mut context, membership_address(&pool, &big_member.pubkey())).await;
+    let big_balance = balance(&mut context, big_member.pubkey()).await;
+    withdraw(&mut context, pool, &big_member).await.unwrap();
+    assert_eq!(balance(&mut context, big_member.pubkey()).await, big_balance + 3 * LAMPORTS_PER_SOL + membership_rent);
+
+    let small_balance = balance(&mut context, small_member.pubkey()).await;
+    withdraw(&mut context, pool, &small_member).await.unwrap();
+    assert_eq!(balance(&mut context, small_member.pubkey()).await, small_balance + LAMPORTS_PER_SOL + membership_rent);
+}
@@ -0,0 +1,203 @@
+// Drives random sequences of place_bet and claim_prize against an in-process bank and checks
+// that the game stays solvent: it can always pay the unclaimed prizes and keep its rent.
+//
+// to run (requires nightly):
+// cargo fuzz run bets_and_claims
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use arbitrary::Arbitrary;
+use common::*;
+use gotcritter::{Bet, CustomError};
+use libfuzzer_sys::fuzz_target;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use std::sync::{Mutex, OnceLock};
+use tokio::runtime::Runtime;
+
+const BETTORS: usize = 4;
+const BETTOR_FUNDS: u64 = 10_000 * LAMPORTS_PER_SOL;
+
+#[derive(Arbitrary, Debug)]
+enum Action {
+    // Bet on a number, invalid numbers and values are expected to be rejected
+    PlaceBet { bettor: u8, number: u8, value: u8 },
+    // Move past the minimum ending slot and bet on a Special Block
+    EndBettingPeriod { closer: u8 },
+    // Claim one of the bets placed so far
+    ClaimPrize { bet: u8 },
+    // Move the clock forward
+    WarpSlots(u8),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    betting_period_slots: u8,
+    actions: Vec<Action>,
+}
+
+struct Harness {
+    runtime: Runtime,
+    state: Mutex<Option<(ProgramTestContext, Vec<Keypair>)>>,
+}
+
+// Starting a bank is slow, so it is shared between the runs and each run plays on a new game
+fn harness() -> &'static Harness {
+    static HARNESS: OnceLock<Harness> = OnceLock::new();
+    HARNESS.get_or_init(|| Harness {
+        runtime: tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap(),
+        state: Mutex::new(None),
+    })
+}
+
+struct PlacedBet {
+    address: Pubkey,
+    bettor: usize,
+    bet: Bet,
+    claimed: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let harness = harness();
+    let mut state = harness.state.lock().unwrap();
+    harness.runtime.block_on(async {
+        // The bettors are reused, a new bank is started when the payer can't fund them anymore
+        let payer_balance = match state.as_mut() {
+            Some((context, _)) => {
+                let payer = context.payer.pubkey();
+                balance(context, payer).await
+            }
+            None => 0,
+        };
+        if payer_balance < (BETTORS as u64 + 1) * BETTOR_FUNDS {
+            let mut context = start().await;
+            let mut bettors = Vec::with_capacity(BETTORS);
+            for _ in 0..BETTORS {
+                bettors.push(funded_keypair(&mut context, BETTOR_FUNDS).await);
+            }
+            *state = Some((context, bettors));
+        }
+
+        let (context, bettors) = state.as_mut().unwrap();
+        run(context, bettors, input).await;
+    });
+});
+
+async fn run(context: &mut ProgramTestContext, bettors: &[Keypair], input: Input) {
+    let creator = context.payer.insecure_clone();
+    let game = create_game(context, &creator, input.betting_period_slots as u64, None).await.unwrap();
+    let mut bets: Vec<PlacedBet> = Vec::new();
+
+    for action in input.actions {
+        match action {
+            Action::PlaceBet { bettor, number, value } => {
+                let bettor = bettor as usize % BETTORS;
+                let number = number % 27; // 0 and 26 are invalid
+                let value = MIN_BET_VALUE * (value as u64 % 100); // 0 is invalid
+                place_and_track(context, game, bettors, bettor, number, value, &mut bets).await;
+            }
+            Action::EndBettingPeriod { closer } => {
+                let min_ending_slot = fetch_game(context, game).await.min_ending_slot;
+                if current_slot(context).await < min_ending_slot {
+                    context.warp_to_slot(min_ending_slot).unwrap();
+                }
+                set_recent_blockhash(context, special_blockhash(closer));
+                let closer = closer as usize % BETTORS;
+                place_and_track(context, game, bettors, closer, 1, MIN_BET_VALUE, &mut bets).await;
+            }
+            Action::ClaimPrize { bet } => {
+                if bets.is_empty() {
+                    continue;
+                }
+                let index = bet as usize % bets.len();
+                let game_account = fetch_game(context, game).await;
+                let placed = &bets[index];
+                let result = claim_prize(context, game, &bettors[placed.bettor], placed.address).await;
+
+                let expected_prize = if game_account.is_betting_period_ended() {
+                    let drawn_number = game_account.calculate_drawn_number().unwrap();
+                    game_account.calculate_prize(&placed.bet, drawn_number).unwrap()
+                } else {
+                    0
+                };
+
+                if game_account.is_betting_period_ended() && expected_prize > 0 && !placed.claimed {
+                    // A winning bet must always be paid
+                    result.unwrap();
+                    bets[index].claimed = true;
+                } else if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+                    _,
+                    InstructionError::Custom(code),
+                ))) = result
+                {
+                    assert_ne!(code, u32::from(CustomError::InsufficientBalance));
+                } else {
+                    panic!("claim should have failed: {:?}", result);
+                }
+            }
+            Action::WarpSlots(slots) => {
+                let slot = current_slot(context).await;
+                context.warp_to_slot(slot + slots as u64 + 1).unwrap();
+            }
+        }
+
+        check_invariants(context, game, &bets).await;
+    }
+}
+
+async fn place_and_track(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettors: &[Keypair],
+    bettor: usize,
+    number: u8,
+    value: u64,
+    bets: &mut Vec<PlacedBet>,
+) {
+    let number_of_bets = fetch_game(context, game).await.number_of_bets;
+    let result = place_bet(context, game, &bettors[bettor], number, value).await;
+
+    // The bet is only registered when it doesn't end the betting period
+    if fetch_game(context, game).await.number_of_bets > number_of_bets {
+        let address = result.unwrap();
+        bets.push(PlacedBet {
+            address,
+            bettor,
+            bet: fetch_bet(context, address).await,
+            claimed: false,
+        });
+    }
+}
+
+async fn check_invariants(context: &mut ProgramTestContext, game: Pubkey, bets: &[PlacedBet]) {
+    let game_account = fetch_game(context, game).await;
+
+    // The totals always match the registered bets
+    let registered: u64 = bets.iter().map(|placed| placed.bet.value).sum();
+    assert_eq!(game_account.total_value, registered);
+    assert_eq!(game_account.bets_per_number.iter().sum::<u64>(), registered);
+    assert_eq!(game_account.number_of_bets, bets.len() as u64);
+
+    // The game always keeps its rent and enough balance to pay every unclaimed prize
+    let account = context.banks_client.get_account(game).await.unwrap().unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap().minimum_balance(account.data.len());
+    let mut unclaimed_prizes = 0;
+    if game_account.is_betting_period_ended() {
+        let drawn_number = game_account.calculate_drawn_number().unwrap();
+        for placed in bets.iter().filter(|placed| !placed.claimed) {
+            unclaimed_prizes += game_account.calculate_prize(&placed.bet, drawn_number).unwrap();
+        }
+        assert!(
+            game_account.value_provided_to_winners + unclaimed_prizes
+                <= game_account.total_value.saturating_sub(ENDING_BET_PERIOD_REWARD)
+        );
+    }
+    assert!(account.lamports >= rent + unclaimed_prizes);
+}
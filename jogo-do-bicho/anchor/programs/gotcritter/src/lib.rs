@@ -1,41 +1,109 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use anchor_lang::solana_program::system_instruction;
 use anchor_lang::solana_program::program::invoke;
-use anchor_lang::solana_program::hash::{hash, Hash};
+use anchor_lang::solana_program::hash::hash;
+use bytemuck::Zeroable;
+#[allow(deprecated)] // there is no documented alternative to RecentBlockhashes yet, see the README
 use anchor_lang::solana_program::sysvar::recent_blockhashes::RecentBlockhashes;
-use hex;
 
 declare_id!("GsxEDNRJbGhMADyosnm9R2HW6tL4VS2vrpwVhBZkFQaV");
 
 const DISC_SIZE: usize = 8; // the discriminator size
 const ENDING_BET_PERIOD_REWARD: u64 = 10_000_000; // 0.01 SOL em lamports
 const MIN_BET_VALUE: u64 = 10_000_000; // 0.01 SOL em lamports
+const MAX_REFERRAL_FEE_BPS: u16 = 1_000; // 10% of the bet, in basis points
+const DEFAULT_WITHDRAWAL_PENALTY_BPS: u16 = 500; // 5% of the bet, kept in the game pool when the bet is withdrawn
+const MAX_WITHDRAWAL_PENALTY_BPS: u16 = 5_000; // 50% of the bet, in basis points
+const MAX_SECONDARY_PRIZE_BPS: u16 = 5_000; // 50% of the pool for the neighbour and group tiers, the exact number keeps the rest
+const GAME_VERSION: u8 = 1; // the current layout of Game, the zero-copy one. Version 0 is the Borsh layout of LegacyGame
+const EVENT_VERSION: u8 = 1; // the layout of the events, version 0 had the game and the timestamp on each event
+const BET_VERSION: u8 = 1; // the current layout of Bet, version 0 is the layout before the version field
+const GAME_SPACE: usize = DISC_SIZE + Game::INIT_SPACE; // discriminator + game space
+const LEGACY_GAME_SPACE: usize = DISC_SIZE + LegacyGame::INIT_SPACE; // the space of the games of the first deployment
+pub const MAX_PARTICIPANTS: usize = 64; // the maximum of participants of a private game
 
 #[program]
 pub mod gotcritter {
     use super::*;
 
-    // Method to create a new game
-    pub fn create_game(ctx: Context<CreateGame>, betting_period_slots: u64, participants: Option<Vec<Pubkey>>) -> Result<()> {
-        // Initialize the game account with the provided data
-        let game = &mut ctx.accounts.game;
+    // Method to create a new game, bets are accepted from starts_at (right away when None) until the betting window is over
+    // and a Special Block ends the betting period. Without prize_tiers the whole pool goes to the drawn number
+    pub fn create_game(
+        ctx: Context<CreateGame>,
+        betting_window: BettingWindow,
+        participants: Option<Vec<Pubkey>>,
+        starts_at: Option<i64>,
+        prize_tiers: Option<PrizeTiers>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        // Initialize the game account with the provided data, every field not set here starts at zero
+        let mut game = ctx.accounts.game.load_init()?;
         game.creator = *ctx.accounts.creator.key;
-        game.participants = participants.unwrap_or_default(); // participants are the ones that can bet on the private game, if none are provided, the game is public
-        game.total_value = 0; // total sum of the values of bets on the game
-        game.min_ending_slot = Clock::get()?.slot + betting_period_slots; // the minimum slot for the betting period to end
-        game.combined_hash = [0; 32]; // the combined hash of each blockhash of the bets on the game, used to calculate the drawn number
-        game.bets_per_number = [0; 25]; // the count of bets on each number
-        game.betting_period_ended = false; // if the betting period has ended, meaning no more bets can be placed and the prizes can be claimed
-        game.drawn_number_confirmed = None; // the drawn number when the betting period ends, used to avoid recalculating the drawn number
-        game.number_of_bets = 0; // the number of bets on the game, used to generate the bet id
-        game.value_provided_to_winners = 0; // the sum of the values of the prizes claimed and paid to the winners
+        game.set_participants(&participants.unwrap_or_default())?; // participants are the ones that can bet on the private game, if none are provided, the game is public
+        game.starts_at = starts_at.unwrap_or_default(); // the unix timestamp before which bets are rejected
+        match betting_window {
+            BettingWindow::Slots { count } => {
+                // The slots are counted from the creation, so a later start must set a deadline instead
+                require!(game.starts_at <= clock.unix_timestamp, CustomError::InvalidBettingWindow);
+
+                game.min_ending_slot = clock.slot + count; // the minimum slot for the betting period to end
+            }
+            BettingWindow::Until { timestamp } => {
+                // Check if the deadline is in the future and after the start
+                require!(
+                    timestamp > clock.unix_timestamp && timestamp > game.starts_at,
+                    CustomError::InvalidBettingWindow
+                );
+
+                game.min_ending_slot = clock.slot; // the slots don't matter, only the deadline
+                game.min_ending_timestamp = timestamp; // the minimum unix timestamp for the betting period to end
+            }
+        }
+        let prize_tiers = prize_tiers.unwrap_or_default();
+        require!(
+            prize_tiers.neighbour_bps as u32 + prize_tiers.group_bps as u32 <= MAX_SECONDARY_PRIZE_BPS as u32,
+            CustomError::InvalidPrizeTiers
+        );
+        game.neighbour_prize_bps = prize_tiers.neighbour_bps; // the slice of the pool for the bets next to the drawn number
+        game.group_prize_bps = prize_tiers.group_bps; // the slice of the pool for the other bets on the group of the drawn number
+        game.version = GAME_VERSION; // the layout of the account, used by migrate_game
 
         // Emit an event informing that a new game was created
         emit!(GameCreated {
-            game: game.key(),
+            header: EventHeader::new(ctx.accounts.game.key())?,
             creator: ctx.accounts.creator.key(),
-            private: !game.participants.is_empty(),
-            timestamp: Clock::get()?.unix_timestamp,
+            private: game.is_private(),
+            starts_at: game.starts_at,
+            min_ending_slot: game.min_ending_slot,
+            min_ending_timestamp: game.min_ending_timestamp,
+            neighbour_prize_bps: game.neighbour_prize_bps,
+            group_prize_bps: game.group_prize_bps,
+        });
+
+        Ok(())
+    }
+
+    // Method to add participants to a private game, for sets that don't fit in the create_game transaction
+    pub fn add_participants(ctx: Context<AddParticipants>, participants: Vec<Pubkey>) -> Result<()> {
+        let mut game = ctx.accounts.game.load_mut()?;
+
+        // A public game would become private, locking out who already bet on it
+        require!(game.is_private(), CustomError::PublicGame);
+
+        // Check if the betting period is still open
+        require!(!game.is_betting_period_ended(), CustomError::BettingPeriodHasEnded);
+
+        let mut all_participants = game.participants().to_vec();
+        all_participants.extend_from_slice(&participants);
+        game.set_participants(&all_participants)?;
+
+        // Emit an event informing that participants were added
+        emit!(ParticipantsAdded {
+            header: EventHeader::new(ctx.accounts.game.key())?,
+            added: participants.len() as u16,
+            participants_count: game.participants_count,
         });
 
         Ok(())
@@ -43,219 +111,1127 @@ pub mod gotcritter {
 
     // Method to place a bet
     pub fn place_bet(ctx: Context<PlaceBet>, number: u8, value: u64) -> Result<()> {
+        let bettor = ctx.accounts.bettor.to_account_info();
+        process_bet(
+            &ctx.accounts.game,
+            &ctx.accounts.config,
+            &bettor,
+            BetFunds::Transfer(&bettor), // the bettor pays for its own bet
+            &mut ctx.accounts.bet,
+            &ctx.accounts.system_program,
+            &ctx.accounts.recent_blockhashes,
+            &mut ctx.accounts.referral,
+            number,
+            value,
+        )
+    }
+
+    // CPI entrypoint to place a bet, for bettors that can't pay with a system transfer (e.g. a PDA holding data
+    // of another program). The bettor only signs, with seeds when it is a PDA, and the payer funds the bet
+    pub fn place_bet_with_payer(ctx: Context<PlaceBetWithPayer>, number: u8, value: u64) -> Result<()> {
+        process_bet(
+            &ctx.accounts.game,
+            &ctx.accounts.config,
+            &ctx.accounts.bettor.to_account_info(),
+            BetFunds::Transfer(&ctx.accounts.payer.to_account_info()),
+            &mut ctx.accounts.bet,
+            &ctx.accounts.system_program,
+            &ctx.accounts.recent_blockhashes,
+            &mut ctx.accounts.referral,
+            number,
+            value,
+        )
+    }
+
+    // Method to check the prize of a bet
+    pub fn drawn_number(ctx: Context<CheckDrawnNumber>) -> Result<u8> {
+        let game = ctx.accounts.game.load()?;
+        let drawn_number = game.calculate_drawn_number()?;        
+        Ok(drawn_number)
+    }
+
+    // Method to check the prize of a bet and the tier it hit
+    pub fn prize(ctx: Context<CheckPrize>) -> Result<Prize> {
+        let game = ctx.accounts.game.load()?;
+        let bet = &ctx.accounts.bet;
+
+        // Calculate the drawn number
+        let drawn_number = game.calculate_drawn_number()?;
+
+        // Calculate the prize
+        let prize = game.calculate_prize(bet, drawn_number)?;
+        
+        Ok(Prize {
+            tier: game.prize_tier(bet.number, drawn_number),
+            value: prize,
+        })
+    }
+
+    // Method to claim the prize of a bet
+    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+        pay_prize(&ctx.accounts.game, &ctx.accounts.bet, &ctx.accounts.bettor.to_account_info())?;
+        Ok(())
+    }
+
+    // Method to cancel a game that didn't receive any bet yet, closing it and refunding the rent to the creator
+    pub fn cancel_game(ctx: Context<CancelGame>) -> Result<()> {
+        let game = ctx.accounts.game.load()?;
+
+        // Check if there is no bet left on the game, otherwise the bettors would lose their funds. number_of_bets
+        // also counts the withdrawn and refunded bets, the pool of each number doesn't
+        require!(game.bets_per_number.iter().all(|&value| value == 0), CustomError::GameHasBets);
+
+        // Emit an event informing that the game was cancelled, the account is closed by anchor after this method
+        emit!(GameCancelled {
+            header: EventHeader::new(ctx.accounts.game.key())?,
+            creator: ctx.accounts.creator.key(),
+        });
+
+        Ok(())
+    }
+
+    // Method to create the program configuration, the signer becomes the admin.
+    // It should be called right after the deploy (see migrations/deploy.ts)
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key(); // the only one allowed to pause and unpause the program
+        config.paused = false; // when paused, no bets can be placed and open games can only be refunded
+        config.referral_fee_bps = 0; // the slice of each bet credited to the referrer, disabled until the admin sets it
+        config.withdrawal_penalty_bps = DEFAULT_WITHDRAWAL_PENALTY_BPS; // the slice of a withdrawn bet kept in the game pool
+
+        emit_config_updated(config)
+    }
+
+    // Method to change the slice of each bet credited to the referrer, in basis points
+    pub fn set_referral_fee(ctx: Context<UpdateConfig>, referral_fee_bps: u16) -> Result<()> {
+        // Check if the fee is not above the maximum allowed
+        require!(referral_fee_bps <= MAX_REFERRAL_FEE_BPS, CustomError::InvalidReferralFee);
+
+        ctx.accounts.config.referral_fee_bps = referral_fee_bps;
+
+        emit_config_updated(&ctx.accounts.config)
+    }
+
+    // Method to change the slice of a withdrawn bet kept in the game pool, in basis points
+    pub fn set_withdrawal_penalty(ctx: Context<UpdateConfig>, withdrawal_penalty_bps: u16) -> Result<()> {
+        // Check if the penalty is not above the maximum allowed
+        require!(withdrawal_penalty_bps <= MAX_WITHDRAWAL_PENALTY_BPS, CustomError::InvalidWithdrawalPenalty);
+
+        ctx.accounts.config.withdrawal_penalty_bps = withdrawal_penalty_bps;
+
+        emit_config_updated(&ctx.accounts.config)
+    }
+
+    // Method to pause the program in case of emergency, blocking new bets and allowing refunds on open games
+    pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
+        set_paused(ctx, true)
+    }
+
+    // Method to unpause the program, allowing new bets again
+    pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
+        set_paused(ctx, false)
+    }
+
+    // Method to refund a bet of an open game while the program is paused
+    pub fn refund_bet(ctx: Context<RefundBet>) -> Result<()> {
+        refund(&ctx.accounts.game, &ctx.accounts.config, &ctx.accounts.bet, &ctx.accounts.bettor.to_account_info())
+    }
+
+    // Method to withdraw a bet before the minimum ending slot, the bettor gets the value back minus the penalty,
+    // which stays in the game pool for the winners
+    pub fn withdraw_bet(ctx: Context<WithdrawBet>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let bet = &ctx.accounts.bet;
+        let mut game_data = ctx.accounts.game.load_mut()?;
+
+        // Check if the program is not paused, while paused the bets are refunded without penalty by refund_bet
+        require!(!config.paused, CustomError::ProgramPaused);
+
         // Check if the betting period is still open
-        require!(!ctx.accounts.game.betting_period_ended, CustomError::BettingPeriodHasEnded);
+        require!(!game_data.is_betting_period_ended(), CustomError::BettingPeriodHasEnded);
+
+        // Check if the betting period can't end yet. After the minimum ending slot (or timestamp) any bet may end it,
+        // so the bets on each number must stay as they were when the all-numbers-filled rule was checked
+        let clock = Clock::get()?;
+        require!(!game_data.is_min_ending_reached(clock.slot, clock.unix_timestamp), CustomError::WithdrawalPeriodHasEnded);
+
+        // Calculate the penalty, which stays in the game account
+        let penalty = ((bet.value as u128) * (config.withdrawal_penalty_bps as u128) / 10_000) as u64;
+        let withdrawn_value = bet.value - penalty;
+
+        // Transfer the bet value minus the penalty back to the bettor, the bet account rent is returned when anchor closes it
+        **ctx.accounts.game.to_account_info().try_borrow_mut_lamports()? -= withdrawn_value;
+        **ctx.accounts.bettor.to_account_info().try_borrow_mut_lamports()? += withdrawn_value;
 
-        // Get the most recent blockhash
-        let recent_blockhashes = RecentBlockhashes::from_account_info(&ctx.accounts.recent_blockhashes)?;
-        let recent_blockhash = recent_blockhashes.get(0).ok_or(ProgramError::InvalidAccountData)?.blockhash;
+        // Remove the bet from its number, a number left without bets must be bet on again before the game can end.
+        // The penalty is kept in total_value, so it is split among the winners
+        game_data.bets_per_number[(bet.number - 1) as usize] -= bet.value;
+        // Games migrated from the Borsh layout start without the count of bettors, so it saturates at zero
+        game_data.bettors_per_number[(bet.number - 1) as usize] = game_data.bettors_per_number[(bet.number - 1) as usize].saturating_sub(1);
+        game_data.total_value -= withdrawn_value;
+
+        // Emit an event informing that the bet was withdrawn
+        emit!(BetWithdrawn {
+            header: EventHeader::new(ctx.accounts.game.key())?,
+            bettor: bet.bettor,
+            bet: bet.key(),
+            number: bet.number,
+            value: withdrawn_value,
+            penalty,
+        });
+
+        Ok(())
+    }
+
+    // Method to create the referral account of a referrer, it must exist before being used on bets
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        referral.referrer = ctx.accounts.referrer.key(); // the one who receives the rewards
+        referral.pending_rewards = 0; // rewards credited and not withdrawn yet
+        referral.total_rewards = 0; // sum of all rewards ever credited
+
+        // Emit an event informing that the referrer can be used on bets
+        emit!(ReferrerRegistered {
+            header: EventHeader::without_game()?,
+            referrer: referral.referrer,
+        });
+
+        Ok(())
+    }
+
+    // Method to withdraw the referral rewards credited by the bets
+    pub fn withdraw_referral_rewards(ctx: Context<WithdrawReferralRewards>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        let amount = referral.pending_rewards;
+
+        // Check if there is something to withdraw
+        require!(amount > 0, CustomError::NoReferralRewards);
+
+        // Transfer the rewards to the referrer, the rent of the referral account is kept
+        **referral.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.referrer.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        referral.pending_rewards = 0;
+
+        // Emit an event informing that the rewards were withdrawn
+        emit!(ReferralRewardsWithdrawn {
+            header: EventHeader::without_game()?,
+            referrer: ctx.accounts.referrer.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Method to create a syndicate, which pools the funds of its members in one bet on a number of the game.
+    // The signer becomes the manager, the only one allowed to place the bet
+    pub fn create_syndicate(ctx: Context<CreateSyndicate>, number: u8) -> Result<()> {
+        let game = ctx.accounts.game.load()?;
+
+        // Check if the bet number is valid
+        require!((1..=25).contains(&number), CustomError::InvalidNumber);
+
+        // Check if the betting period is still open
+        require!(!game.is_betting_period_ended(), CustomError::BettingPeriodHasEnded);
+
+        // The syndicate is the bettor, on a private game its address must be one of the participants
+        require!(game.can_bet(&ctx.accounts.syndicate.key()), CustomError::ParticipantNotAllowed);
+
+        let syndicate = &mut ctx.accounts.syndicate;
+        syndicate.game = ctx.accounts.game.key(); // the game the syndicate bets on
+        syndicate.manager = ctx.accounts.manager.key(); // the one who places the bet
+        syndicate.number = number; // the number the syndicate bets on
+        syndicate.bet = Pubkey::default(); // set when the bet is placed
+        syndicate.bump = ctx.bumps.syndicate;
+
+        // Emit an event informing that a syndicate was created
+        emit!(SyndicateCreated {
+            header: EventHeader::new(syndicate.game)?,
+            syndicate: syndicate.key(),
+            manager: syndicate.manager,
+            number,
+        });
+
+        Ok(())
+    }
+
+    // Method to join a syndicate with a first contribution, creating the member account
+    pub fn join_syndicate(ctx: Context<JoinSyndicate>, amount: u64) -> Result<()> {
+        let member = &mut ctx.accounts.syndicate_member;
+        member.syndicate = ctx.accounts.syndicate.key();
+        member.member = ctx.accounts.member.key();
+        member.bump = ctx.bumps.syndicate_member;
+        ctx.accounts.syndicate.members += 1;
+
+        contribute(
+            &mut ctx.accounts.syndicate,
+            &mut ctx.accounts.syndicate_member,
+            &ctx.accounts.member,
+            &ctx.accounts.system_program,
+            amount,
+        )
+    }
+
+    // Method to add to the contribution of a member, while the bet is not placed
+    pub fn deposit_to_syndicate(ctx: Context<DepositToSyndicate>, amount: u64) -> Result<()> {
+        contribute(
+            &mut ctx.accounts.syndicate,
+            &mut ctx.accounts.syndicate_member,
+            &ctx.accounts.member,
+            &ctx.accounts.system_program,
+            amount,
+        )
+    }
+
+    // Method to bet everything contributed to the syndicate on its number. The manager pays the rent of the bet account
+    // and receives it back when the bet is closed
+    pub fn place_syndicate_bet(ctx: Context<PlaceSyndicateBet>) -> Result<()> {
+        // Check if the bet was not placed yet, a syndicate has only one bet
+        require!(ctx.accounts.syndicate.bet == Pubkey::default(), CustomError::SyndicateBetAlreadyPlaced);
+
+        let syndicate = ctx.accounts.syndicate.to_account_info();
+        process_bet(
+            &ctx.accounts.game,
+            &ctx.accounts.config,
+            &syndicate,
+            BetFunds::ProgramAccount(&syndicate), // the contributions are kept on the syndicate account
+            &mut ctx.accounts.bet,
+            &ctx.accounts.system_program,
+            &ctx.accounts.recent_blockhashes,
+            &mut None,
+            ctx.accounts.syndicate.number,
+            ctx.accounts.syndicate.total_contributed,
+        )?;
+
+        // A bet that ends the betting period is not placed, the contributions would be spread on the game
+        require!(ctx.accounts.bet.game == ctx.accounts.game.key(), CustomError::BettingPeriodHasEnded);
+
+        ctx.accounts.syndicate.bet = ctx.accounts.bet.key();
+
+        Ok(())
+    }
+
+    // Method to claim the prize of the syndicate bet, anyone can call it. The prize is credited to the syndicate,
+    // where each member withdraws their share
+    pub fn claim_syndicate_prize(ctx: Context<ClaimSyndicatePrize>) -> Result<()> {
+        let prize = pay_prize(&ctx.accounts.game, &ctx.accounts.bet, &ctx.accounts.syndicate.to_account_info())?;
+
+        let syndicate = &mut ctx.accounts.syndicate;
+        syndicate.prize = prize; // split among the members in proportion to their contributions
+        syndicate.prize_claimed = true;
+
+        Ok(())
+    }
+
+    // Method to refund the syndicate bet of an open game while the program is paused, anyone can call it.
+    // The value goes back to the syndicate and the members can withdraw their contributions
+    pub fn refund_syndicate_bet(ctx: Context<RefundSyndicateBet>) -> Result<()> {
+        refund(&ctx.accounts.game, &ctx.accounts.config, &ctx.accounts.bet, &ctx.accounts.syndicate.to_account_info())?;
+
+        ctx.accounts.syndicate.bet = Pubkey::default(); // as if the bet had never been placed
+
+        Ok(())
+    }
+
+    // Method to leave a syndicate, closing the member account. Before the bet the contribution is returned,
+    // after the claim the share of the prize, and nothing when the bet lost
+    pub fn withdraw_from_syndicate(ctx: Context<WithdrawFromSyndicate>) -> Result<()> {
+        let syndicate = &mut ctx.accounts.syndicate;
+        let contribution = ctx.accounts.syndicate_member.contribution;
+
+        let amount = if syndicate.bet == Pubkey::default() {
+            // The bet was not placed (or was refunded), the contribution is still on the syndicate
+            syndicate.total_contributed -= contribution;
+            syndicate.members -= 1;
+            contribution
+        } else if syndicate.prize_claimed {
+            // The share of the prize proportional to the contribution, the rounding dust stays on the syndicate
+            ((syndicate.prize as u128) * (contribution as u128) / (syndicate.total_contributed as u128)) as u64
+        } else {
+            // Without a claim, a member can only leave when the bet lost
+            require!(ctx.accounts.game.owner == &crate::ID, anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+            let data = ctx.accounts.game.try_borrow_data()?;
+            let game = Game::try_deserialize(&mut data.get(..GAME_SPACE).ok_or(CustomError::GameNeedsMigration)?)?;
+            let drawn_number = game.confirmed_drawn_number().ok_or(CustomError::GameNotFinished)?;
+            require!(game.prize_tier(syndicate.number, drawn_number) == PrizeTier::None, CustomError::SyndicatePrizeNotClaimed);
+            0
+        };
+
+        // Transfer the amount to the member, the rent of the member account is returned when anchor closes it
+        **syndicate.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.member.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        // Emit an event informing that the member left the syndicate
+        emit!(SyndicateWithdrawal {
+            header: EventHeader::new(syndicate.game)?,
+            syndicate: syndicate.key(),
+            member: ctx.accounts.member.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Method to move a game created by an older version of the program to the current layout
+    pub fn migrate_game(ctx: Context<MigrateGame>) -> Result<()> {
+        let game_info = ctx.accounts.game.to_account_info();
+
+        // The Borsh layout is smaller than the zero-copy one, and only the exact space of the first deployment is
+        // decoded, any other account would be read as a game
+        require!(game_info.data_len() < GAME_SPACE, CustomError::AlreadyMigrated);
+        require!(
+            game_info.data_len() == LEGACY_GAME_SPACE,
+            anchor_lang::error::ErrorCode::AccountDidNotDeserialize
+        );
+
+        let legacy = {
+            let data = game_info.try_borrow_data()?;
+            require!(
+                data[..DISC_SIZE] == Game::DISCRIMINATOR[..],
+                anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+            );
+            LegacyGame::deserialize(&mut &data[DISC_SIZE..])
+                .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?
+        };
+
+        grow_account(&game_info, &ctx.accounts.payer, &ctx.accounts.system_program, GAME_SPACE)?;
+
+        // The discriminator is the same on both layouts, only the data after it is rewritten
+        let mut data = game_info.try_borrow_mut_data()?;
+        let game: &mut Game = bytemuck::from_bytes_mut(&mut data[DISC_SIZE..GAME_SPACE]);
+        *game = Game::from_legacy(&legacy)?;
+
+        // Emit an event informing that the game was migrated
+        emit!(GameMigrated {
+            header: EventHeader::new(game_info.key())?,
+            previous_version: 0,
+            current_version: GAME_VERSION,
+        });
+
+        Ok(())
+    }
+
+    // Method to move a bet created by an older version of the program to the current layout
+    pub fn migrate_bet(ctx: Context<MigrateBet>) -> Result<()> {
+        let bet_info = ctx.accounts.bet.to_account_info();
+        grow_account(&bet_info, &ctx.accounts.payer, &ctx.accounts.system_program, DISC_SIZE + Bet::INIT_SPACE)?;
+
+        // Same as in migrate_game
+        let mut bet = Bet::try_deserialize(&mut &bet_info.try_borrow_data()?[..])?;
+        require!(bet.version < BET_VERSION, CustomError::AlreadyMigrated);
+        let previous_version = bet.version;
+        bet.version = BET_VERSION;
+        bet.try_serialize(&mut &mut bet_info.try_borrow_mut_data()?[..])?;
+
+        // Emit an event informing that the bet was migrated
+        emit!(BetMigrated {
+            header: EventHeader::new(bet.game)?,
+            bet: bet_info.key(),
+            previous_version,
+            current_version: BET_VERSION,
+        });
+
+        Ok(())
+    }
+}
+
+// Where the value of a bet comes from
+enum BetFunds<'a, 'info> {
+    // A signer paying with a system transfer, like a wallet
+    Transfer(&'a AccountInfo<'info>),
+    // An account of this program, debited directly, like a syndicate
+    ProgramAccount(&'a AccountInfo<'info>),
+}
+
+impl<'info> BetFunds<'_, 'info> {
+    fn pay(&self, to: &AccountInfo<'info>, amount: u64, system_program: &Program<'info, System>) -> Result<()> {
+        match self {
+            BetFunds::Transfer(payer) => invoke(
+                &system_instruction::transfer(payer.key, to.key, amount),
+                &[(*payer).clone(), to.clone(), system_program.to_account_info()],
+            )?,
+            BetFunds::ProgramAccount(account) => {
+                **account.try_borrow_mut_lamports()? -= amount;
+                **to.try_borrow_mut_lamports()? += amount;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Places a bet on the game, shared by every instruction that bets. The funds pay the bet and the referral fee,
+// the bettor owns the bet and receives the reward when its bet ends the betting period
+#[allow(deprecated, clippy::too_many_arguments)]
+fn process_bet<'info>(
+    game: &AccountLoader<'info, Game>,
+    config: &Config,
+    bettor: &AccountInfo<'info>,
+    funds: BetFunds<'_, 'info>,
+    bet: &mut Account<'info, Bet>,
+    system_program: &Program<'info, System>,
+    recent_blockhashes: &AccountInfo<'info>,
+    referral: &mut Option<Account<'info, ReferralAccount>>,
+    number: u8,
+    value: u64,
+) -> Result<()> {
+    // Check if the program is not paused by the admin
+    require!(!config.paused, CustomError::ProgramPaused);
+
+    // Get the most recent blockhash
+    let recent_blockhashes = RecentBlockhashes::from_account_info(recent_blockhashes)?;
+    let recent_blockhash = recent_blockhashes.first().ok_or(CustomError::MissingBlockhash)?.blockhash;
+
+    // The game is borrowed only while it is read or written, the transfers below need the account free
+    let drawn_number = {
+        let mut game_data = game.load_mut()?;
+
+        // Check if the betting period is still open
+        require!(!game_data.is_betting_period_ended(), CustomError::BettingPeriodHasEnded);
+
+        // Check if the game has reached its scheduled start
+        let clock = Clock::get()?;
+        require!(game_data.has_started(clock.unix_timestamp), CustomError::GameNotStarted);
 
         // Reminder: there will be only one bet per game per blockhash
         // (game is a mutable pda and only one transaction handling it can happen per block)
 
         // Update the combined hash adding the recent blockhash
-        let mut combined = ctx.accounts.game.combined_hash.to_vec();
+        let mut combined = game_data.combined_hash.to_vec();
         combined.extend_from_slice(&recent_blockhash.to_bytes());
-        ctx.accounts.game.combined_hash = hash(&combined).to_bytes();
-        
-        // if we are at the minimum ending slot or beyond
-        if Clock::get()?.slot >= ctx.accounts.game.min_ending_slot {            
+        game_data.combined_hash = hash(&combined).to_bytes();
+
+        // if we are at the minimum ending slot (or timestamp) or beyond
+        if game_data.is_min_ending_reached(clock.slot, clock.unix_timestamp) {
             // Check if there is at least one bet for each number
-            let all_bets_filled = ctx.accounts.game.bets_per_number.iter().all(|&bet| bet > 0);
+            let all_bets_filled = game_data.bets_per_number.iter().all(|&bet| bet > 0);
 
             if all_bets_filled {
                 // Check if the betting period should end (the last 2 digits of the blockhash must be the same)
                 let recent_blockhash_hex = hex::encode(recent_blockhash.to_bytes());
                 let last_digits = &recent_blockhash_hex[recent_blockhash_hex.len()-2..];
-                ctx.accounts.game.betting_period_ended = last_digits.chars().nth(0) == last_digits.chars().nth(1);
+                game_data.betting_period_ended = (last_digits.chars().next() == last_digits.chars().nth(1)) as u8;
             }
         }
 
-        // If the betting period ended
-        if ctx.accounts.game.betting_period_ended {
-            // Confirm the drawn number
-            ctx.accounts.game.drawn_number_confirmed = Some(ctx.accounts.game.calculate_drawn_number()?);
+        // If the betting period ended, confirm the drawn number
+        if game_data.is_betting_period_ended() {
+            game_data.drawn_number_confirmed = game_data.calculate_drawn_number()?;
+        } else {
+            // Check if the game is open or if the bettor is in the participants list
+            require!(game_data.can_bet(bettor.key), CustomError::ParticipantNotAllowed);
+        }
 
-            // Check if the game has enough balance to pay the ending bet period reward
-            let game_balance = ctx.accounts.game.to_account_info().lamports();
-            let reward_amount = if game_balance >= ENDING_BET_PERIOD_REWARD {
-                ENDING_BET_PERIOD_REWARD
-            } else {
-                game_balance
-            };
+        game_data.confirmed_drawn_number()
+    };
 
-            if reward_amount > 0 {
-                // Transfer the reward to the closer of the betting period
-                **ctx.accounts.game.to_account_info().try_borrow_mut_lamports()? -= reward_amount;
-                **ctx.accounts.bettor.to_account_info().try_borrow_mut_lamports()? += reward_amount;
+    // If the betting period ended
+    if let Some(drawn_number) = drawn_number {
+        // Check if the game has enough balance to pay the ending bet period reward
+        let game_balance = game.to_account_info().lamports();
+        let reward_amount = if game_balance >= ENDING_BET_PERIOD_REWARD {
+            ENDING_BET_PERIOD_REWARD
+        } else {
+            game_balance
+        };
+
+        if reward_amount > 0 {
+            // Transfer the reward to the closer of the betting period
+            **game.to_account_info().try_borrow_mut_lamports()? -= reward_amount;
+            **bettor.try_borrow_mut_lamports()? += reward_amount;
+        }
+
+        // Emit an event informing that the betting period ended
+        emit!(EndOfBettingPeriod {
+            header: EventHeader::new(game.key())?,
+            closer: bettor.key(),
+            reward: reward_amount,
+            drawn_number,
+        });
+    } else {
+        // if the betting period is not ended
+
+        // Check if the bet number is valid
+        require!((1..=25).contains(&number), CustomError::InvalidNumber);
+        
+        // Check if the bet value is valid (minimum of 0.01 SOL)
+        require!(value >= MIN_BET_VALUE, CustomError::InvalidValue);
+
+        // Calculate the slice of the bet that goes to the referrer, if there is one
+        let referral_fee = match referral {
+            Some(referral) => {
+                require!(referral.referrer != bettor.key(), CustomError::SelfReferral);
+                ((value as u128) * (config.referral_fee_bps as u128) / 10_000) as u64
             }
+            None => 0,
+        };
+        let bet_value = value - referral_fee; // the value that goes to the game pool
 
-            // Emit an event informing that the betting period ended
-            emit!(EndOfBettingPeriod {
-                game: ctx.accounts.game.key(),
-                closer: ctx.accounts.bettor.key(),
-                reward: reward_amount,
-                timestamp: Clock::get()?.unix_timestamp,
-            });
-        } else {
-            // if the betting period is not ended
+        // Transfer the bet value from the payer to the game account
+        funds.pay(&game.to_account_info(), bet_value, system_program)?;
 
-            // Check if the game is open or if the bettor is in the participants list
-            require!(
-                ctx.accounts.game.participants.is_empty() || ctx.accounts.game.participants.contains(ctx.accounts.bettor.key),
-                CustomError::GameClosed
-            );
-            
-            // Check if the bet number is valid
-            require!(number >= 1 && number <= 25, CustomError::InvalidNumber);
-            
-            // Check if the bet value is valid (minimum of 0.01 SOL)
-            require!(value >= MIN_BET_VALUE, CustomError::InvalidValue);
-
-            // Transfer the bet value from the bettor to the game account
-            let transfer_instruction = system_instruction::transfer(
-                ctx.accounts.bettor.key,
-                ctx.accounts.game.to_account_info().key,
-                value,
-            );
-            invoke(
-                &transfer_instruction,
-                &[
-                    ctx.accounts.bettor.to_account_info(),
-                    ctx.accounts.game.to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
-            )?;
-            
-            ctx.accounts.game.bets_per_number[(number - 1) as usize] += value; // Update the sum of bets for the chosen number
-            ctx.accounts.game.total_value += value; // Update the total value of bets on the game
-            ctx.accounts.game.number_of_bets += 1; // Update the number of bets on the game
-
-            // Create the bet account
-            let bet = &mut ctx.accounts.bet;
-            bet.game = ctx.accounts.game.key(); // The game the bet belongs to
-            bet.bettor = *ctx.accounts.bettor.key; // The bettor
-            bet.value = value; // The value of the bet
-            bet.number = number; // The number of the bet
-
-            // Emit an event informing that a bet was placed
-            emit!(BetPlaced {
-                game: ctx.accounts.game.key(),
-                bettor: ctx.accounts.bettor.key(),
-                number,
-                value,
-                timestamp: Clock::get()?.unix_timestamp,
-                bet: bet.key(),
-            });
+        // Transfer the referral fee from the payer to the referral account, to be withdrawn by the referrer later
+        if let Some(referral) = referral {
+            if referral_fee > 0 {
+                funds.pay(&referral.to_account_info(), referral_fee, system_program)?;
+
+                referral.pending_rewards += referral_fee; // Update the rewards available to withdraw
+                referral.total_rewards += referral_fee; // Update the sum of all rewards ever credited
+            }
         }
 
-        Ok(())
-    }
+        let mut game_data = game.load_mut()?;
+        game_data.bets_per_number[(number - 1) as usize] += bet_value; // Update the sum of bets for the chosen number
+        game_data.bettors_per_number[(number - 1) as usize] += 1; // Update the count of bets for the chosen number
+        game_data.total_value += bet_value; // Update the total value of bets on the game
+        game_data.number_of_bets += 1; // Update the number of bets on the game
 
-    // Method to check the prize of a bet
-    pub fn drawn_number(ctx: Context<CheckDrawnNumber>) -> Result<u8> {
-        let game = &ctx.accounts.game;
-        let drawn_number = game.calculate_drawn_number()?;        
-        Ok(drawn_number)
+        // Create the bet account
+        bet.game = game.key(); // The game the bet belongs to
+        bet.bettor = *bettor.key; // The bettor
+        bet.value = bet_value; // The value of the bet, without the referral fee
+        bet.number = number; // The number of the bet
+        bet.version = BET_VERSION; // The layout of the account, used by migrate_bet
+
+        // Emit an event informing that a bet was placed
+        emit!(BetPlaced {
+            header: EventHeader::new(game.key())?,
+            bettor: bettor.key(),
+            number,
+            value: bet_value,
+            bet: bet.key(),
+            referrer: referral.as_ref().map(|referral| referral.referrer),
+            referral_fee,
+        });
     }
 
-    // Method to check the prize of a bet
-    pub fn prize(ctx: Context<CheckPrize>) -> Result<u64> {
-        let game = &ctx.accounts.game;
-        let bet = &ctx.accounts.bet;
+    Ok(())
+}
 
-        // Calculate the drawn number
-        let drawn_number = game.calculate_drawn_number()?;
+// Pays the prize of a bet to the recipient, shared by claim_prize and claim_syndicate_prize.
+// Returns the lamports transferred, the bet is closed by anchor after the instruction
+fn pay_prize<'info>(game: &AccountLoader<'info, Game>, bet: &Account<'info, Bet>, recipient: &AccountInfo<'info>) -> Result<u64> {
+    let mut game_data = game.load_mut()?;
 
-        // Calculate the prize
-        let prize = game.calculate_prize(bet, drawn_number)?;
-        
-        Ok(prize)
+    // Check if the betting period has ended
+    require!(game_data.is_betting_period_ended(), CustomError::GameNotFinished);
+
+    // Check if the prize has already been claimed
+    require!(!bet.prize_claimed, CustomError::PrizeAlreadyClaimed);
+
+    // Calculate the drawn number
+    let drawn_number = game_data.calculate_drawn_number()?;
+
+    // Calculate the prize
+    let prize = game_data.calculate_prize(bet, drawn_number)?;
+
+    // Check if there is a prize for the bet
+    require!(prize > 0, CustomError::NoPrize);
+
+    // Check if the game has enough balance to pay the prize, but theoretically the error should never happen
+    let game_info = game.to_account_info();
+    let game_balance = game_info.lamports();
+    let prize_to_transfer = if game_balance >= prize {
+        prize
+    } else {
+        game_balance
+    };
+
+    // Check if the prize to transfer is zero
+    require!(prize_to_transfer > 0, CustomError::InsufficientBalance);
+
+    // Transfer the prize to the recipient
+    **game_info.try_borrow_mut_lamports()? -= prize_to_transfer;
+    **recipient.try_borrow_mut_lamports()? += prize_to_transfer;
+
+    // Update the total value provided to winners
+    game_data.value_provided_to_winners += prize;
+
+    // Emit an event informing that the prize was claimed
+    emit!(PrizeClaimed {
+        header: EventHeader::new(game_info.key())?,
+        bettor: bet.bettor,
+        bet: bet.key(),
+        drawn_number,
+        tier: game_data.prize_tier(bet.number, drawn_number),
+        prize_value: prize,
+    });
+
+    Ok(prize_to_transfer)
+}
+
+// Returns the value of a bet of an open game while the program is paused, shared by refund_bet and refund_syndicate_bet.
+// The bet is closed by anchor after the instruction
+fn refund<'info>(
+    game: &AccountLoader<'info, Game>,
+    config: &Config,
+    bet: &Account<'info, Bet>,
+    recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    let mut game_data = game.load_mut()?;
+
+    // Check if the program is paused, refunds are only available in this mode
+    require!(config.paused, CustomError::ProgramNotPaused);
+
+    // Check if the betting period is still open, finished games must claim their prizes
+    require!(!game_data.is_betting_period_ended(), CustomError::BettingPeriodHasEnded);
+
+    // Transfer the bet value back to the recipient, the bet account rent is returned when anchor closes it
+    **game.to_account_info().try_borrow_mut_lamports()? -= bet.value;
+    **recipient.try_borrow_mut_lamports()? += bet.value;
+
+    // Remove the bet from the game totals, number_of_bets is kept because it is used to generate the bet id
+    game_data.bets_per_number[(bet.number - 1) as usize] -= bet.value;
+    // Games migrated from the Borsh layout start without the count of bettors, so it saturates at zero
+    game_data.bettors_per_number[(bet.number - 1) as usize] = game_data.bettors_per_number[(bet.number - 1) as usize].saturating_sub(1);
+    game_data.total_value -= bet.value;
+
+    // Emit an event informing that the bet was refunded
+    emit!(BetRefunded {
+        header: EventHeader::new(game.key())?,
+        bettor: bet.bettor,
+        bet: bet.key(),
+        value: bet.value,
+    });
+
+    Ok(())
+}
+
+// Moves a contribution from the member to the syndicate, shared by join_syndicate and deposit_to_syndicate
+fn contribute<'info>(
+    syndicate: &mut Account<'info, Syndicate>,
+    syndicate_member: &mut Account<'info, SyndicateMember>,
+    member: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    // Check if the bet was not placed yet, after that the contributions are already in the game
+    require!(syndicate.bet == Pubkey::default(), CustomError::SyndicateBetAlreadyPlaced);
+
+    // Check if there is something to contribute
+    require!(amount > 0, CustomError::InvalidValue);
+
+    // Transfer the contribution to the syndicate account, which holds it until the bet
+    invoke(
+        &system_instruction::transfer(member.key, &syndicate.key(), amount),
+        &[member.to_account_info(), syndicate.to_account_info(), system_program.to_account_info()],
+    )?;
+
+    syndicate_member.contribution += amount;
+    syndicate.total_contributed += amount;
+
+    // Emit an event informing that a member contributed
+    emit!(SyndicateDeposit {
+        header: EventHeader::new(syndicate.game)?,
+        syndicate: syndicate.key(),
+        member: member.key(),
+        amount,
+        total_contributed: syndicate.total_contributed,
+    });
+
+    Ok(())
+}
+
+// Reallocs an account to the space of the current layout, the payer covers the extra rent
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<()> {
+    let current_space = account.data_len();
+    if current_space >= space {
+        return Ok(());
     }
 
-    // Method to claim the prize of a bet
-    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
-        let game = &mut ctx.accounts.game;
-        let bet = &ctx.accounts.bet;
+    // Only the rent of the extra space is paid, the balance of the account (e.g. the bets of a game) is not touched
+    let rent = Rent::get()?;
+    let extra_rent = rent.minimum_balance(space).saturating_sub(rent.minimum_balance(current_space));
+    if extra_rent > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, extra_rent),
+            &[payer.to_account_info(), account.clone(), system_program.to_account_info()],
+        )?;
+    }
+
+    account.realloc(space, true)?;
+    Ok(())
+}
 
-        // Check if the betting period has ended
-        require!(game.betting_period_ended, CustomError::GameNotFinished);
+// Games created before the zero-copy layout are smaller, loading them would read past the end of the data
+fn has_current_layout(game: &AccountLoader<Game>) -> bool {
+    game.as_ref().data_len() >= GAME_SPACE
+}
 
-        // Check if the prize has already been claimed
-        require!(!bet.prize_claimed, CustomError::PrizeAlreadyClaimed);        
+// The number of bets of the game is part of the bet seeds, which are derived before the game constraints are checked
+fn number_of_bets_seed(game: &AccountLoader<Game>) -> Result<[u8; 8]> {
+    require!(has_current_layout(game), CustomError::GameNeedsMigration);
+    Ok(game.load()?.number_of_bets.to_le_bytes())
+}
 
-        // Calculate the drawn number
-        let drawn_number = game.calculate_drawn_number()?;
+fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
 
-        // Calculate the prize
-        let prize = game.calculate_prize(bet, drawn_number)?;
+    // Emit an event informing that the pause state changed
+    emit!(PauseStateChanged {
+        header: EventHeader::without_game()?,
+        admin: ctx.accounts.admin.key(),
+        paused,
+    });
 
-        // Check if there is a prize for the bet
-        require!(prize > 0, CustomError::NoPrize);
+    Ok(())
+}
+
+// Emits the current values of the program configuration, after any change on them
+fn emit_config_updated(config: &Config) -> Result<()> {
+    emit!(ConfigUpdated {
+        header: EventHeader::without_game()?,
+        admin: config.admin,
+        referral_fee_bps: config.referral_fee_bps,
+        withdrawal_penalty_bps: config.withdrawal_penalty_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateGame<'info> {
+    // Initialize the game account with the creator (signer) as the payer
+    #[account(
+        init,
+        payer = creator,
+        space = GAME_SPACE, // discriminator + game space
+    )]
+    pub game: AccountLoader<'info, Game>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddParticipants<'info> {
+    // The private game to add the participants to
+    #[account(
+        mut,
+        constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration,
+        constraint = game.load()?.creator == creator.key() @ CustomError::InvalidCreator
+    )]
+    pub game: AccountLoader<'info, Game>,
+    // The creator of the game, the only one allowed to add participants
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBet<'info> {
+    // The game the bet is on. It will be updated with calculated values
+    #[account(mut)]
+    pub game: AccountLoader<'info, Game>,
+    // The program configuration, used to check if betting is paused
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    // The bettor
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    // Initialize the bet account with the bettor as the payer
+    #[account(
+        init,
+        payer = bettor,
+        space = DISC_SIZE + Bet::INIT_SPACE, // discriminator + bet space
+        seeds = [
+            b"bet",
+            game.key().as_ref(),
+            bettor.key().as_ref(),
+            number_of_bets_seed(&game)?.as_ref()
+        ],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: This account is not written in this instruction
+    #[account(address = anchor_lang::solana_program::sysvar::recent_blockhashes::ID)]
+    pub recent_blockhashes: AccountInfo<'info>,
+    // The optional referral account of who brought the bettor, credited with a slice of the bet
+    #[account(mut)]
+    pub referral: Option<Account<'info, ReferralAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBetWithPayer<'info> {
+    // The game the bet is on. It will be updated with calculated values
+    #[account(mut)]
+    pub game: AccountLoader<'info, Game>,
+    // The program configuration, used to check if betting is paused
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    // The bettor, the owner of the bet. It can be a PDA of the calling program, signing with its seeds.
+    // Mutable because it receives the reward when its bet ends the betting period
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    // Funds the bet, the referral fee and the bet account rent, it must be able to pay with a system transfer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // Initialize the bet account with the payer as the payer, the seeds are the same as in place_bet
+    #[account(
+        init,
+        payer = payer,
+        space = DISC_SIZE + Bet::INIT_SPACE, // discriminator + bet space
+        seeds = [
+            b"bet",
+            game.key().as_ref(),
+            bettor.key().as_ref(),
+            number_of_bets_seed(&game)?.as_ref()
+        ],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: This account is not written in this instruction
+    #[account(address = anchor_lang::solana_program::sysvar::recent_blockhashes::ID)]
+    pub recent_blockhashes: AccountInfo<'info>,
+    // The optional referral account of who brought the bettor, credited with a slice of the bet
+    #[account(mut)]
+    pub referral: Option<Account<'info, ReferralAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct CheckDrawnNumber<'info> {
+    // The game to check the drawn number
+    #[account(constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+}
+
+#[derive(Accounts)]
+pub struct CheckPrize<'info> {
+    // The game the bet is on
+    #[account(
+        constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration,
+        constraint = game.key() == bet.game @ CustomError::BetDoesNotBelongToGame
+    )]
+    pub game: AccountLoader<'info, Game>,
+    // The bet to check the prize
+    pub bet: Account<'info, Bet>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    // The game to claim the prize, mutable because we will deduce it's balance to pay the prize and we will update it
+    #[account(mut, constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    // The bettor to receive the prize, mutable because we will add the prize to it's balance
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    // The bet to claim the prize, mutable because we will close it
+    #[account(
+        mut,
+        constraint = bet.bettor == bettor.key() @ CustomError::BetDoesNotBelongToBettor,
+        constraint = bet.game == game.key() @ CustomError::BetDoesNotBelongToGame,
+        close = bettor
+    )]
+    pub bet: Account<'info, Bet>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelGame<'info> {
+    // The game to cancel, closed to the creator so the rent is refunded
+    #[account(
+        mut,
+        constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration,
+        constraint = game.load()?.creator == creator.key() @ CustomError::InvalidCreator,
+        close = creator
+    )]
+    pub game: AccountLoader<'info, Game>,
+    // The creator of the game, the only one allowed to cancel it
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // The program configuration, there is only one per program
+    #[account(
+        init,
+        payer = admin,
+        space = DISC_SIZE + Config::INIT_SPACE, // discriminator + config space
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        // Check if the game has enough balance to pay the prize, but theoretically the error should never happen
-        let game_balance = game.to_account_info().lamports();
-        let prize_to_transfer = if game_balance >= prize {
-            prize
-        } else {
-            game_balance
-        };
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    // The program configuration to update
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = admin @ CustomError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
 
-        // Check if the prize to transfer is zero
-        require!(prize_to_transfer > 0, CustomError::InsufficientBalance);
+#[derive(Accounts)]
+pub struct RefundBet<'info> {
+    // The game the bet is on, mutable because we will deduce the refund from it's balance and totals
+    #[account(mut, constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    // The program configuration, used to check if the program is paused
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    // The bettor to receive the refund
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    // The bet to refund, mutable because we will close it
+    #[account(
+        mut,
+        constraint = bet.bettor == bettor.key() @ CustomError::BetDoesNotBelongToBettor,
+        constraint = bet.game == game.key() @ CustomError::BetDoesNotBelongToGame,
+        close = bettor
+    )]
+    pub bet: Account<'info, Bet>,
+}
 
-        // Transfer the prize to the bettor
-        **game.to_account_info().try_borrow_mut_lamports()? -= prize_to_transfer;
-        **ctx.accounts.bettor.to_account_info().try_borrow_mut_lamports()? += prize_to_transfer;
+#[derive(Accounts)]
+pub struct WithdrawBet<'info> {
+    // The game the bet is on, mutable because we will deduce the withdrawn value from it's balance and totals
+    #[account(mut, constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    // The program configuration, with the withdrawal penalty
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    // The bettor to receive the withdrawn value
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    // The bet to withdraw, mutable because we will close it
+    #[account(
+        mut,
+        constraint = bet.bettor == bettor.key() @ CustomError::BetDoesNotBelongToBettor,
+        constraint = bet.game == game.key() @ CustomError::BetDoesNotBelongToGame,
+        close = bettor
+    )]
+    pub bet: Account<'info, Bet>,
+}
 
-        // Update the total value provided to winners
-        game.value_provided_to_winners += prize;
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    // Initialize the referral account with the referrer as the payer, one per referrer
+    #[account(
+        init,
+        payer = referrer,
+        space = DISC_SIZE + ReferralAccount::INIT_SPACE, // discriminator + referral space
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, ReferralAccount>,
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        // Emit an event informing that the prize was claimed
-        emit!(PrizeClaimed {
-            game: game.key(),
-            bettor: ctx.accounts.bettor.key(),
-            drawn_number,
-            prize_value: prize,
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+#[derive(Accounts)]
+pub struct WithdrawReferralRewards<'info> {
+    // The referral account holding the rewards, mutable because we will deduce the rewards from it's balance
+    #[account(
+        mut,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump,
+        has_one = referrer @ CustomError::Unauthorized
+    )]
+    pub referral: Account<'info, ReferralAccount>,
+    // The referrer to receive the rewards
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct CreateSyndicate<'info> {
+    // Initialize the syndicate with the manager as the payer, one per game and manager
+    #[account(
+        init,
+        payer = manager,
+        space = DISC_SIZE + Syndicate::INIT_SPACE, // discriminator + syndicate space
+        seeds = [b"syndicate", game.key().as_ref(), manager.key().as_ref()],
+        bump
+    )]
+    pub syndicate: Account<'info, Syndicate>,
+    // The game the syndicate bets on
+    #[account(constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    #[account(mut)]
+    pub manager: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateGame<'info> {
-    // Initialize the game account with the creator (signer) as the payer
+pub struct JoinSyndicate<'info> {
+    // The syndicate to join, mutable because it receives the contribution
+    #[account(mut, seeds = [b"syndicate", syndicate.game.as_ref(), syndicate.manager.as_ref()], bump = syndicate.bump)]
+    pub syndicate: Account<'info, Syndicate>,
+    // Initialize the member account with the member as the payer, one per syndicate and member
     #[account(
         init,
-        payer = creator,
-        space = DISC_SIZE + Game::INIT_SPACE, // discriminator + game space
+        payer = member,
+        space = DISC_SIZE + SyndicateMember::INIT_SPACE, // discriminator + syndicate member space
+        seeds = [b"syndicate_member", syndicate.key().as_ref(), member.key().as_ref()],
+        bump
     )]
-    pub game: Account<'info, Game>,
+    pub syndicate_member: Account<'info, SyndicateMember>,
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub member: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PlaceBet<'info> {
-    // The game the bet is on. It will be updated with calculated values
+pub struct DepositToSyndicate<'info> {
+    // The syndicate of the member, mutable because it receives the contribution
+    #[account(mut, seeds = [b"syndicate", syndicate.game.as_ref(), syndicate.manager.as_ref()], bump = syndicate.bump)]
+    pub syndicate: Account<'info, Syndicate>,
+    // The member account, where the contribution is tracked
+    #[account(
+        mut,
+        seeds = [b"syndicate_member", syndicate.key().as_ref(), member.key().as_ref()],
+        bump = syndicate_member.bump
+    )]
+    pub syndicate_member: Account<'info, SyndicateMember>,
     #[account(mut)]
-    pub game: Account<'info, Game>,
-    // The bettor
+    pub member: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceSyndicateBet<'info> {
+    // The syndicate placing the bet, it is the bettor and funds the bet with the contributions
+    #[account(
+        mut,
+        seeds = [b"syndicate", syndicate.game.as_ref(), syndicate.manager.as_ref()],
+        bump = syndicate.bump,
+        has_one = game @ CustomError::SyndicateDoesNotBelongToGame,
+        has_one = manager @ CustomError::NotSyndicateManager
+    )]
+    pub syndicate: Account<'info, Syndicate>,
+    // The manager of the syndicate, pays the rent of the bet account
     #[account(mut)]
-    pub bettor: Signer<'info>,
-    // Initialize the bet account with the bettor as the payer
+    pub manager: Signer<'info>,
+    // The game the bet is on. It will be updated with calculated values
+    #[account(mut)]
+    pub game: AccountLoader<'info, Game>,
+    // The program configuration, used to check if betting is paused
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    // Initialize the bet account with the manager as the payer, the seeds are the same as in place_bet
     #[account(
         init,
-        payer = bettor,
+        payer = manager,
         space = DISC_SIZE + Bet::INIT_SPACE, // discriminator + bet space
         seeds = [
             b"bet",
             game.key().as_ref(),
-            bettor.key().as_ref(),
-            &game.number_of_bets.to_le_bytes()
+            syndicate.key().as_ref(),
+            number_of_bets_seed(&game)?.as_ref()
         ],
         bump
     )]
@@ -267,44 +1243,130 @@ pub struct PlaceBet<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CheckDrawnNumber<'info> {
-    // The game to check the drawn number
-    pub game: Account<'info, Game>,
+pub struct ClaimSyndicatePrize<'info> {
+    // The syndicate that placed the bet, mutable because it receives the prize
+    #[account(
+        mut,
+        seeds = [b"syndicate", syndicate.game.as_ref(), syndicate.manager.as_ref()],
+        bump = syndicate.bump,
+        has_one = game @ CustomError::SyndicateDoesNotBelongToGame,
+        has_one = manager @ CustomError::NotSyndicateManager,
+        constraint = syndicate.bet == bet.key() @ CustomError::BetDoesNotBelongToSyndicate
+    )]
+    pub syndicate: Account<'info, Syndicate>,
+    // The game to claim the prize, mutable because we will deduce it's balance to pay the prize and we will update it
+    #[account(mut, constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    // The bet to claim the prize, closed to the manager who paid its rent
+    #[account(mut, close = manager)]
+    pub bet: Account<'info, Bet>,
+    /// CHECK: Only receives the rent of the bet, checked by has_one
+    #[account(mut)]
+    pub manager: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CheckPrize<'info> {
-    // The game the bet is on
+pub struct RefundSyndicateBet<'info> {
+    // The syndicate that placed the bet, mutable because it receives the refund
     #[account(
-        constraint = game.key() == bet.game @ CustomError::BetDoesNotBelongToGame
+        mut,
+        seeds = [b"syndicate", syndicate.game.as_ref(), syndicate.manager.as_ref()],
+        bump = syndicate.bump,
+        has_one = game @ CustomError::SyndicateDoesNotBelongToGame,
+        has_one = manager @ CustomError::NotSyndicateManager,
+        constraint = syndicate.bet == bet.key() @ CustomError::BetDoesNotBelongToSyndicate
     )]
-    pub game: Account<'info, Game>,
-    // The bet to check the prize
+    pub syndicate: Account<'info, Syndicate>,
+    // The game the bet is on, mutable because we will deduce the refund from it's balance and totals
+    #[account(mut, constraint = has_current_layout(&game) @ CustomError::GameNeedsMigration)]
+    pub game: AccountLoader<'info, Game>,
+    // The program configuration, used to check if the program is paused
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+    // The bet to refund, closed to the manager who paid its rent
+    #[account(mut, close = manager)]
     pub bet: Account<'info, Bet>,
+    /// CHECK: Only receives the rent of the bet, checked by has_one
+    #[account(mut)]
+    pub manager: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimPrize<'info> {
-    // The game to claim the prize, mutable because we will deduce it's balance to pay the prize and we will update it
-    #[account(mut)]
-    pub game: Account<'info, Game>,
-    // The bettor to receive the prize, mutable because we will add the prize to it's balance
-    #[account(mut)]
-    pub bettor: Signer<'info>,
-    // The bet to claim the prize, mutable because we will close it
+pub struct WithdrawFromSyndicate<'info> {
+    // The syndicate to leave, mutable because we will deduce the withdrawal from it's balance
+    #[account(mut, seeds = [b"syndicate", syndicate.game.as_ref(), syndicate.manager.as_ref()], bump = syndicate.bump)]
+    pub syndicate: Account<'info, Syndicate>,
+    // The member account, closed to the member
     #[account(
         mut,
-        constraint = bet.bettor == bettor.key() @ CustomError::BetDoesNotBelongToBettor,
-        constraint = bet.game == game.key() @ CustomError::BetDoesNotBelongToGame,
-        close = bettor
+        seeds = [b"syndicate_member", syndicate.key().as_ref(), member.key().as_ref()],
+        bump = syndicate_member.bump,
+        close = member
     )]
-    pub bet: Account<'info, Bet>,
+    pub syndicate_member: Account<'info, SyndicateMember>,
+    #[account(mut)]
+    pub member: Signer<'info>,
+    /// CHECK: Only read when the bet was not claimed, to know if it lost. It may be closed when it was cancelled
+    #[account(address = syndicate.game @ CustomError::SyndicateDoesNotBelongToGame)]
+    pub game: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGame<'info> {
+    /// CHECK: An older layout can't be read as Game before the realloc, the discriminator is checked by the instruction
+    #[account(mut, owner = crate::ID)]
+    pub game: AccountInfo<'info>,
+    // Pays the rent of the extra space, anyone can migrate a game
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
-#[account]
+#[derive(Accounts)]
+pub struct MigrateBet<'info> {
+    /// CHECK: An older layout can't be read as Bet before the realloc, the discriminator is checked by the instruction
+    #[account(mut, owner = crate::ID)]
+    pub bet: AccountInfo<'info>,
+    // Pays the rent of the extra space, anyone can migrate a bet
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Zero-copy, so place_bet doesn't deserialize and serialize the whole account. The fields are ordered
+// to have no padding (bool and Option are not Pod, so flags are u8) and new fields must take bytes from reserved.
+// `unsafe` only skips the Pod derive, the repr is kept and the padding is checked by the assert below
+#[account(zero_copy(unsafe))]
+#[repr(C)]
 #[derive(InitSpace)]
 pub struct Game {
+    pub creator: Pubkey,
+    pub combined_hash: [u8; 32],
+    pub total_value: u64,
+    pub min_ending_slot: u64,
+    pub number_of_bets: u64,
+    pub value_provided_to_winners: u64,
+    pub bets_per_number: [u64; 25],
+    pub bettors_per_number: [u32; 25], // the count of bets on each number
+    pub participants_count: u16, // zero on public games
+    pub betting_period_ended: u8, // 0 or 1
+    pub drawn_number_confirmed: u8, // 0 until the betting period ends
+    pub version: u8,
+    pub padding: [u8; 7], // keeps the timestamps below aligned, taken from reserved like them
+    pub starts_at: i64, // unix timestamp before which bets are rejected, zero when the game starts right away
+    pub min_ending_timestamp: i64, // zero when the betting window is in slots
+    pub neighbour_prize_bps: u16, // zero on games created before the prize tiers, so the drawn number takes the whole pool
+    pub group_prize_bps: u16,
+    pub reserved: [u8; 44],
+    pub participants: [Pubkey; MAX_PARTICIPANTS], // only the first participants_count are used
+}
+
+// Game is Pod only without padding, when its size is the sum of the size of the fields
+const _: () = assert!(Game::INIT_SPACE == std::mem::size_of::<Game>());
+
+// The Borsh layout of Game on the first deployment, before the zero-copy one, only read by migrate_game
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyGame {
     pub creator: Pubkey,
     #[max_len(10)] // maximum of 10 participants
     pub participants: Vec<Pubkey>,
@@ -318,6 +1380,38 @@ pub struct Game {
     pub value_provided_to_winners: u64,
 }
 
+// The minimum duration of the betting period of a new game
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BettingWindow {
+    // A number of slots after the creation, the duration of a slot varies so the time is an estimate
+    Slots { count: u64 },
+    // A unix timestamp, compared with the clock of the cluster
+    Until { timestamp: i64 },
+}
+
+// The slices of the pool for the bets that missed the drawn number, in basis points. The drawn number gets the rest
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrizeTiers {
+    pub neighbour_bps: u16, // split among the bets on the numbers right before and after the drawn number
+    pub group_bps: u16, // split among the other bets on the group of 5 numbers of the drawn number (1 to 5, 6 to 10...)
+}
+
+// The tier of the pool a bet hit, a bet only hits the best one
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrizeTier {
+    None,
+    Exact,
+    Neighbour,
+    Group,
+}
+
+// The result of the prize method
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prize {
+    pub tier: PrizeTier,
+    pub value: u64,
+}
+
 #[account]
 #[derive(Default, InitSpace)]
 pub struct Bet {
@@ -326,12 +1420,54 @@ pub struct Bet {
     pub value: u64,
     pub number: u8,
     pub prize_claimed: bool,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub referral_fee_bps: u16,
+    pub withdrawal_penalty_bps: u16,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralAccount {
+    pub referrer: Pubkey,
+    pub pending_rewards: u64,
+    pub total_rewards: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Syndicate {
+    pub game: Pubkey,
+    pub manager: Pubkey,
+    pub number: u8,
+    pub members: u32,
+    pub total_contributed: u64, // the value of the bet, the sum of the contributions of the members
+    pub bet: Pubkey, // the default pubkey until the bet is placed
+    pub prize: u64, // credited by claim_syndicate_prize
+    pub prize_claimed: bool,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SyndicateMember {
+    pub syndicate: Pubkey,
+    pub member: Pubkey,
+    pub contribution: u64,
+    pub bump: u8,
 }
 
 #[error_code]
 pub enum CustomError {
-    #[msg("The game is closed for new participants")]
-    GameClosed,
+    #[msg("Only the participants of a private game can bet on it")]
+    ParticipantNotAllowed,
     #[msg("Invalid number. Must be between 1 and 25")]
     InvalidNumber,
     #[msg("The betting period has ended")]
@@ -350,48 +1486,273 @@ pub enum CustomError {
     InsufficientBalance,
     #[msg("Invalid creator")]
     InvalidCreator,
-    #[msg("A aposta não pertence a este jogo")]
+    #[msg("The bet does not belong to this game")]
     BetDoesNotBelongToGame,
+    #[msg("The game already has bets and can't be cancelled")]
+    GameHasBets,
+    #[msg("The program is paused, no bets can be placed")]
+    ProgramPaused,
+    #[msg("Refunds are only available while the program is paused")]
+    ProgramNotPaused,
+    #[msg("Only the admin can perform this action")]
+    Unauthorized,
+    #[msg("Invalid referral fee. The maximum is 1000 basis points (10%)")]
+    InvalidReferralFee,
+    #[msg("The bettor can't be their own referrer")]
+    SelfReferral,
+    #[msg("There are no referral rewards to withdraw")]
+    NoReferralRewards,
+    #[msg("The account already has the current layout")]
+    AlreadyMigrated,
+    #[msg("The game has an older layout, it must be migrated with migrate_game")]
+    GameNeedsMigration,
+    #[msg("Too many participants. The maximum is 64")]
+    TooManyParticipants,
+    #[msg("Participants can only be added to private games")]
+    PublicGame,
+    #[msg("Only the manager of the syndicate can place its bet")]
+    NotSyndicateManager,
+    #[msg("The syndicate already placed its bet")]
+    SyndicateBetAlreadyPlaced,
+    #[msg("The bet does not belong to the syndicate")]
+    BetDoesNotBelongToSyndicate,
+    #[msg("The prize of the syndicate bet was not claimed yet")]
+    SyndicatePrizeNotClaimed,
+    #[msg("Invalid withdrawal penalty. The maximum is 5000 basis points (50%)")]
+    InvalidWithdrawalPenalty,
+    #[msg("Bets can only be withdrawn before the end of the betting window")]
+    WithdrawalPeriodHasEnded,
+    #[msg("Invalid betting window. The deadline must be in the future and after the start, a later start needs a deadline")]
+    InvalidBettingWindow,
+    #[msg("The game has not started yet")]
+    GameNotStarted,
+    #[msg("The recent blockhashes sysvar has no blockhash")]
+    MissingBlockhash,
+    #[msg("The syndicate does not belong to this game")]
+    SyndicateDoesNotBelongToGame,
+    #[msg("The neighbour and group prize tiers can take at most 50% of the pool")]
+    InvalidPrizeTiers,
+}
+
+// The first field of every event, so clients can order and route the notifications the same way for all of them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventHeader {
+    pub game: Pubkey, // the default pubkey on events of the program configuration, which don't belong to a game
+    pub slot: u64,
+    pub timestamp: i64,
+    pub version: u8, // the layout of the events, bumped when their fields change
+}
+
+impl EventHeader {
+    fn new(game: Pubkey) -> Result<Self> {
+        let clock = Clock::get()?;
+        Ok(EventHeader { game, slot: clock.slot, timestamp: clock.unix_timestamp, version: EVENT_VERSION })
+    }
+
+    fn without_game() -> Result<Self> {
+        EventHeader::new(Pubkey::default())
+    }
 }
 
 #[event]
 pub struct GameCreated {
-    pub game: Pubkey,
+    pub header: EventHeader,
     pub creator: Pubkey,
     pub private: bool,
-    pub timestamp: i64,
+    pub starts_at: i64,
+    pub min_ending_slot: u64,
+    pub min_ending_timestamp: i64, // zero when the betting window is in slots
+    pub neighbour_prize_bps: u16,
+    pub group_prize_bps: u16,
+}
+
+#[event]
+pub struct ParticipantsAdded {
+    pub header: EventHeader,
+    pub added: u16,
+    pub participants_count: u16,
 }
 
 #[event]
 pub struct BetPlaced {
-    pub game: Pubkey,
+    pub header: EventHeader,
     pub bettor: Pubkey,
     pub number: u8,
     pub value: u64,
-    pub timestamp: i64,
     pub bet: Pubkey,
+    pub referrer: Option<Pubkey>,
+    pub referral_fee: u64,
 }
 
 #[event]
 pub struct PrizeClaimed {
-    pub game: Pubkey,
+    pub header: EventHeader,
     pub bettor: Pubkey,
+    pub bet: Pubkey,
     pub drawn_number: u8,
+    pub tier: PrizeTier,
     pub prize_value: u64,
-    pub timestamp: i64,
 }
 
 #[event]
 pub struct EndOfBettingPeriod {
-    pub game: Pubkey,
+    pub header: EventHeader,
     pub closer: Pubkey,
     pub reward: u64,
-    pub timestamp: i64,
+    pub drawn_number: u8,
+}
+
+#[event]
+pub struct GameCancelled {
+    pub header: EventHeader,
+    pub creator: Pubkey,
+}
+
+#[event]
+pub struct BetRefunded {
+    pub header: EventHeader,
+    pub bettor: Pubkey,
+    pub bet: Pubkey,
+    pub value: u64,
+}
+
+#[event]
+pub struct BetWithdrawn {
+    pub header: EventHeader,
+    pub bettor: Pubkey,
+    pub bet: Pubkey,
+    pub number: u8,
+    pub value: u64, // returned to the bettor
+    pub penalty: u64, // kept in the game pool
+}
+
+#[event]
+pub struct PauseStateChanged {
+    pub header: EventHeader,
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct ReferralRewardsWithdrawn {
+    pub header: EventHeader,
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+#[event]
+pub struct ReferrerRegistered {
+    pub header: EventHeader,
+    pub referrer: Pubkey,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub header: EventHeader,
+    pub admin: Pubkey,
+    pub referral_fee_bps: u16,
+    pub withdrawal_penalty_bps: u16,
+}
+
+#[event]
+pub struct GameMigrated {
+    pub header: EventHeader,
+    pub previous_version: u8,
+    pub current_version: u8,
+}
+#[event]
+pub struct BetMigrated {
+    pub header: EventHeader,
+    pub bet: Pubkey,
+    pub previous_version: u8,
+    pub current_version: u8,
+}
+#[event]
+pub struct SyndicateCreated {
+    pub header: EventHeader,
+    pub syndicate: Pubkey,
+    pub manager: Pubkey,
+    pub number: u8,
+}
+#[event]
+pub struct SyndicateDeposit {
+    pub header: EventHeader,
+    pub syndicate: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
+    pub total_contributed: u64,
+}
+#[event]
+pub struct SyndicateWithdrawal {
+    pub header: EventHeader,
+    pub syndicate: Pubkey,
+    pub member: Pubkey,
+    pub amount: u64,
 }
 
 impl Game {
+    // Builds the current layout from the Borsh one, bettors_per_number starts at zero because that layout didn't count them
+    // (the bets can't be listed here), withdrawals and refunds saturate it instead of underflowing
+    pub fn from_legacy(legacy: &LegacyGame) -> Result<Game> {
+        let mut game = Game::zeroed();
+        game.creator = legacy.creator;
+        game.set_participants(&legacy.participants)?;
+        game.total_value = legacy.total_value;
+        game.min_ending_slot = legacy.min_ending_slot;
+        game.combined_hash = legacy.combined_hash;
+        game.bets_per_number = legacy.bets_per_number;
+        game.betting_period_ended = legacy.betting_period_ended as u8;
+        game.drawn_number_confirmed = legacy.drawn_number_confirmed.unwrap_or(0);
+        game.number_of_bets = legacy.number_of_bets;
+        game.value_provided_to_winners = legacy.value_provided_to_winners;
+        game.version = GAME_VERSION;
+        Ok(game)
+    }
+
+    // The participants of a private game, empty on public games
+    pub fn participants(&self) -> &[Pubkey] {
+        &self.participants[..self.participants_count as usize]
+    }
+
+    pub fn set_participants(&mut self, participants: &[Pubkey]) -> Result<()> {
+        require!(participants.len() <= MAX_PARTICIPANTS, CustomError::TooManyParticipants);
+        self.participants[..participants.len()].copy_from_slice(participants);
+        self.participants_count = participants.len() as u16;
+        Ok(())
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.participants_count > 0
+    }
+
+    // Anyone can bet on a public game, only the participants on a private one
+    pub fn can_bet(&self, bettor: &Pubkey) -> bool {
+        !self.is_private() || self.participants().contains(bettor)
+    }
+
+    pub fn is_betting_period_ended(&self) -> bool {
+        self.betting_period_ended != 0
+    }
+
+    // The drawn number confirmed when the betting period ended
+    pub fn has_started(&self, timestamp: i64) -> bool {
+        timestamp >= self.starts_at
+    }
+
+    // Whether the betting window is over, from then on a Special Block ends the betting period
+    pub fn is_min_ending_reached(&self, slot: u64, timestamp: i64) -> bool {
+        if self.min_ending_timestamp != 0 {
+            timestamp >= self.min_ending_timestamp
+        } else {
+            slot >= self.min_ending_slot
+        }
+    }
+
+    pub fn confirmed_drawn_number(&self) -> Option<u8> {
+        (self.drawn_number_confirmed != 0).then_some(self.drawn_number_confirmed)
+    }
+
     pub fn calculate_drawn_number(&self) -> Result<u8> {
-        let drawn_number = if let Some(confirmed) = self.drawn_number_confirmed {
+        let drawn_number = if let Some(confirmed) = self.confirmed_drawn_number() {
             confirmed // uses the confirmed drawn number if it exists
         } else {
             // Calculate the drawn number using the combined hash
@@ -405,17 +1766,74 @@ impl Game {
         Ok(drawn_number)
     }
 
+    // The tier hit by a bet on the number, None when the bet missed or the tier has no slice of the pool.
+    // A neighbour in the group of the drawn number falls back to the group tier when the neighbour tier is off
+    pub fn prize_tier(&self, number: u8, drawn_number: u8) -> PrizeTier {
+        let tier = if number == drawn_number {
+            PrizeTier::Exact
+        } else if number.abs_diff(drawn_number) == 1 && self.neighbour_prize_bps > 0 {
+            PrizeTier::Neighbour
+        } else if (number - 1) / 5 == (drawn_number - 1) / 5 && self.group_prize_bps > 0 {
+            PrizeTier::Group
+        } else {
+            PrizeTier::None
+        };
+
+        if self.tier_bps(tier) == 0 {
+            PrizeTier::None
+        } else {
+            tier
+        }
+    }
+
+    // The slice of the pool of a tier, in basis points
+    pub fn tier_bps(&self, tier: PrizeTier) -> u16 {
+        match tier {
+            PrizeTier::None => 0,
+            PrizeTier::Exact => 10_000 - self.neighbour_prize_bps - self.group_prize_bps,
+            PrizeTier::Neighbour => self.neighbour_prize_bps,
+            PrizeTier::Group => self.group_prize_bps,
+        }
+    }
+
+    // The total value of the bets on the numbers of a tier
+    fn tier_bets(&self, tier: PrizeTier, drawn_number: u8) -> u64 {
+        (1..=25u8)
+            .filter(|&number| self.prize_tier(number, drawn_number) == tier)
+            .map(|number| self.bets_per_number[(number - 1) as usize])
+            .sum()
+    }
+
+    // The part of the pool split among the bets of a tier. The slice of a tier without bets goes to the drawn number
+    fn tier_value(&self, tier: PrizeTier, drawn_number: u8, pool: u64) -> u64 {
+        let slice = |tier: PrizeTier| {
+            if self.tier_bets(tier, drawn_number) == 0 {
+                0
+            } else {
+                ((pool as u128) * (self.tier_bps(tier) as u128) / 10_000) as u64
+            }
+        };
+
+        match tier {
+            PrizeTier::None => 0,
+            PrizeTier::Exact => pool - slice(PrizeTier::Neighbour) - slice(PrizeTier::Group),
+            PrizeTier::Neighbour | PrizeTier::Group => slice(tier),
+        }
+    }
+
     pub fn calculate_prize(&self, bet: &Bet, drawn_number: u8) -> Result<u64> {
-        let total_bet_on_number = self.bets_per_number[(drawn_number - 1) as usize]; // the total value of bets on the drawn number
+        let tier = self.prize_tier(bet.number, drawn_number);
+        let total_bet_on_tier = self.tier_bets(tier, drawn_number); // the total value of bets on the numbers of the tier
 
-        let prize = if total_bet_on_number <= 0 || bet.number != drawn_number {
-            0 // no prize if there is no bet on the drawn number or the bet is not on the drawn number
+        let prize = if total_bet_on_tier == 0 || tier == PrizeTier::None {
+            0 // no prize if there is no bet on the tier or the bet missed every tier
         } else {
             // Deduct the ENDING_BET_PERIOD_REWARD from the total_value before calculating the prize
             let adjusted_total_value = self.total_value.saturating_sub(ENDING_BET_PERIOD_REWARD);
+            let tier_value = self.tier_value(tier, drawn_number, adjusted_total_value);
             
             // Use u128 for intermediate calculation to avoid overflow
-            let intermediate_result = (adjusted_total_value as u128) * (bet.value as u128) / (total_bet_on_number as u128);
+            let intermediate_result = (tier_value as u128) * (bet.value as u128) / (total_bet_on_tier as u128);
             
             // Convert back to u64, capping at u64::MAX if necessary
             intermediate_result.min(u64::MAX as u128) as u64 // this is safe because the max value of u64 in lamports is way more than sol's total supply
@@ -0,0 +1,743 @@
+// Helpers shared by the integration tests, they run the program on an in-process bank
+#![allow(dead_code, deprecated)] // each test file uses a different set of helpers, RecentBlockhashes is deprecated
+
+use std::cell::Cell;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize, Discriminator, InstructionData, Space, ToAccountMetas};
+use gotcritter::{Bet, BettingWindow, Config, Game, LegacyGame, PrizeTiers, ReferralAccount, Syndicate};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program,
+    sysvar::{
+        clock::Clock,
+        recent_blockhashes::{self, IterItem, RecentBlockhashes},
+    },
+    transaction::{Transaction, TransactionError},
+};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const ENDING_BET_PERIOD_REWARD: u64 = 10_000_000; // same as the program
+pub const MIN_BET_VALUE: u64 = 10_000_000; // same as the program
+pub const GAME_SPACE: usize = 8 + Game::INIT_SPACE; // discriminator + game space
+pub const LEGACY_GAME_SPACE: usize = 8 + LegacyGame::INIT_SPACE; // the Borsh layout of the first deployment
+pub const BET_SPACE_V0: usize = 8 + Bet::INIT_SPACE - 1 - 32; // before the version and the reserved space
+
+thread_local! {
+    // The compute units of the last transaction processed by the test running on this thread
+    static LAST_COMPUTE_UNITS: Cell<u64> = const { Cell::new(0) };
+}
+
+// Anchor's entrypoint requires the accounts to live as long as the 'info lifetime,
+// which is more than the program-test processor signature gives us
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    gotcritter::entry(program_id, accounts, data)
+}
+
+// Starts a bank with the program loaded. It runs the native processor by default,
+// or the built program when BPF_OUT_DIR/SBF_OUT_DIR is set (e.g. `cargo test-sbf`)
+pub async fn start() -> ProgramTestContext {
+    start_with(program_test()).await
+}
+
+// The program test with only this program, programs calling it through CPI are added to it
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("gotcritter", gotcritter::ID, processor!(process_instruction))
+}
+
+pub async fn start_with(program_test: ProgramTest) -> ProgramTestContext {
+    let mut context = program_test.start_with_context().await;
+
+    // Every bet needs the config, the payer becomes the admin
+    let admin = context.payer.insecure_clone();
+    initialize_config(&mut context, &admin).await.unwrap();
+
+    context
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+
+    // A new blockhash avoids rejecting identical transactions sent in sequence
+    let blockhash = context.get_new_latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &all_signers, blockhash);
+    let processed = context.banks_client.process_transaction_with_metadata(transaction).await?;
+    if let Some(metadata) = processed.metadata {
+        LAST_COMPUTE_UNITS.set(metadata.compute_units_consumed);
+    }
+    processed.result.map_err(BanksClientError::TransactionError)
+}
+
+// Only meaningful on the built program, the native processor doesn't consume compute units like it
+pub fn last_compute_units() -> u64 {
+    LAST_COMPUTE_UNITS.get()
+}
+
+// Returns the custom error code of a failed transaction, anchor errors start at 6000
+pub fn custom_error(result: Result<(), BanksClientError>) -> u32 {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) => code,
+        other => panic!("expected a custom error, got {:?}", other),
+    }
+}
+
+pub fn error_code(error: gotcritter::CustomError) -> u32 {
+    error.into()
+}
+
+pub async fn funded_keypair(context: &mut ProgramTestContext, lamports: u64) -> Keypair {
+    let keypair = Keypair::new();
+    let instruction = system_instruction::transfer(&context.payer.pubkey(), &keypair.pubkey(), lamports);
+    process(context, &[instruction], &[]).await.unwrap();
+    keypair
+}
+
+pub async fn balance(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    context.banks_client.get_balance(address).await.unwrap()
+}
+
+pub async fn account_exists(context: &mut ProgramTestContext, address: Pubkey) -> bool {
+    context.banks_client.get_account(address).await.unwrap().is_some()
+}
+
+pub async fn fetch_game(context: &mut ProgramTestContext, game: Pubkey) -> Game {
+    let account = context.banks_client.get_account(game).await.unwrap().expect("game not found");
+    Game::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn fetch_bet(context: &mut ProgramTestContext, bet: Pubkey) -> Bet {
+    let account = context.banks_client.get_account(bet).await.unwrap().expect("bet not found");
+    Bet::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn fetch_config(context: &mut ProgramTestContext) -> Config {
+    let account = context.banks_client.get_account(config_address()).await.unwrap().expect("config not found");
+    Config::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn fetch_referral(context: &mut ProgramTestContext, referral: Pubkey) -> ReferralAccount {
+    let account = context.banks_client.get_account(referral).await.unwrap().expect("referral not found");
+    ReferralAccount::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn fetch_syndicate(context: &mut ProgramTestContext, syndicate: Pubkey) -> Syndicate {
+    let account = context.banks_client.get_account(syndicate).await.unwrap().expect("syndicate not found");
+    Syndicate::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn current_slot(context: &mut ProgramTestContext) -> u64 {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().slot
+}
+
+// Replaces the most recent blockhash seen by the program. The bank also registers unique
+// blockhashes on the background, but those always end with "00", so they are Special Blocks too
+pub async fn current_timestamp(context: &mut ProgramTestContext) -> i64 {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+}
+
+// Moves the clock of the bank to the timestamp, the slot is kept
+pub async fn set_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    context.set_sysvar(&Clock { unix_timestamp, ..clock });
+}
+
+pub fn set_recent_blockhash(context: &mut ProgramTestContext, blockhash: Hash) {
+    let recent_blockhashes = RecentBlockhashes::from_iter([IterItem(0, &blockhash, 5_000)]);
+    context.set_sysvar(&recent_blockhashes);
+}
+
+// A blockhash where the last two hex characters are the same
+pub fn special_blockhash(seed: u8) -> Hash {
+    let mut bytes = [seed; 32];
+    bytes[31] = 0x77;
+    Hash::new_from_array(bytes)
+}
+
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &gotcritter::ID).0
+}
+
+pub fn referral_address(referrer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"referral", referrer.as_ref()], &gotcritter::ID).0
+}
+
+pub fn bet_address(game: &Pubkey, bettor: &Pubkey, number_of_bets: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"bet", game.as_ref(), bettor.as_ref(), &number_of_bets.to_le_bytes()],
+        &gotcritter::ID,
+    )
+    .0
+}
+
+pub fn syndicate_address(game: &Pubkey, manager: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"syndicate", game.as_ref(), manager.as_ref()], &gotcritter::ID).0
+}
+
+pub fn syndicate_member_address(syndicate: &Pubkey, member: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"syndicate_member", syndicate.as_ref(), member.as_ref()], &gotcritter::ID).0
+}
+
+// Rewrites the game as if its betting period had ended on the given number. The drawn number comes from the
+// blockhashes, which the bank also changes on the background, so ending it with place_bet can't choose it
+pub async fn finish_game(context: &mut ProgramTestContext, game: Pubkey, drawn_number: u8) {
+    let mut account = context.banks_client.get_account(game).await.unwrap().unwrap();
+    let mut game_account = Game::try_deserialize(&mut account.data.as_slice()).unwrap();
+    game_account.betting_period_ended = 1;
+    game_account.drawn_number_confirmed = drawn_number;
+    account.data[8..].copy_from_slice(bytemuck::bytes_of(&game_account));
+    context.set_account(&game, &account.into());
+}
+
+// Rewrites an account with the data of an older layout, as if it had been created by an older version of the program.
+// The rent of the space removed goes to the context payer (the bank checks that no lamports disappear),
+// so the migration must pay it again
+pub async fn rewrite_with_older_layout(context: &mut ProgramTestContext, address: Pubkey, data: Vec<u8>) {
+    let mut account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let removed_rent = rent.minimum_balance(account.data.len()) - rent.minimum_balance(data.len());
+
+    account.lamports -= removed_rent;
+    account.data = data;
+    context.set_account(&address, &account.into());
+
+    let payer = context.payer.pubkey();
+    let mut payer_account = context.banks_client.get_account(payer).await.unwrap().unwrap();
+    payer_account.lamports += removed_rent;
+    context.set_account(&payer, &payer_account.into());
+}
+
+// The version and the reserved space are the last fields of Bet, so the version 0 is a prefix of the current layout
+pub fn bet_data_v0(bet: &Bet) -> Vec<u8> {
+    let mut data = Vec::new();
+    bet.try_serialize(&mut data).unwrap();
+    data.truncate(BET_SPACE_V0);
+    data
+}
+
+// The same game with the Borsh layout, which has the same discriminator as the zero-copy one
+pub fn legacy_game_data(game: &Game) -> Vec<u8> {
+    let legacy = LegacyGame {
+        creator: game.creator,
+        participants: game.participants().to_vec(),
+        total_value: game.total_value,
+        min_ending_slot: game.min_ending_slot,
+        combined_hash: game.combined_hash,
+        bets_per_number: game.bets_per_number,
+        betting_period_ended: game.is_betting_period_ended(),
+        drawn_number_confirmed: game.confirmed_drawn_number(),
+        number_of_bets: game.number_of_bets,
+        value_provided_to_winners: game.value_provided_to_winners,
+    };
+    let mut data = Game::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(LEGACY_GAME_SPACE, 0);
+    data
+}
+
+pub async fn migrate_game(context: &mut ProgramTestContext, game: Pubkey, payer: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::MigrateGame {
+            game,
+            payer: payer.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::MigrateGame {}.data(),
+    };
+    process(context, &[instruction], &[payer]).await
+}
+
+pub async fn migrate_bet(context: &mut ProgramTestContext, bet: Pubkey, payer: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::MigrateBet {
+            bet,
+            payer: payer.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::MigrateBet {}.data(),
+    };
+    process(context, &[instruction], &[payer]).await
+}
+
+pub async fn add_participants(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    creator: &Keypair,
+    participants: Vec<Pubkey>,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::AddParticipants {
+            game,
+            creator: creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::AddParticipants { participants }.data(),
+    };
+    process(context, &[instruction], &[creator]).await
+}
+
+pub async fn initialize_config(context: &mut ProgramTestContext, admin: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::InitializeConfig {
+            config: config_address(),
+            admin: admin.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::InitializeConfig {}.data(),
+    };
+    process(context, &[instruction], &[admin]).await
+}
+
+pub async fn create_game(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    betting_period_slots: u64,
+    participants: Option<Vec<Pubkey>>,
+) -> Result<Pubkey, BanksClientError> {
+    create_scheduled_game(context, creator, BettingWindow::Slots { count: betting_period_slots }, participants, None).await
+}
+
+pub async fn create_scheduled_game(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    betting_window: BettingWindow,
+    participants: Option<Vec<Pubkey>>,
+    starts_at: Option<i64>,
+) -> Result<Pubkey, BanksClientError> {
+    let data = gotcritter::instruction::CreateGame { betting_window, participants, starts_at, prize_tiers: None };
+    send_create_game(context, creator, data).await
+}
+
+pub async fn create_tiered_game(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    betting_period_slots: u64,
+    prize_tiers: PrizeTiers,
+) -> Result<Pubkey, BanksClientError> {
+    let data = gotcritter::instruction::CreateGame {
+        betting_window: BettingWindow::Slots { count: betting_period_slots },
+        participants: None,
+        starts_at: None,
+        prize_tiers: Some(prize_tiers),
+    };
+    send_create_game(context, creator, data).await
+}
+
+async fn send_create_game(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    data: gotcritter::instruction::CreateGame,
+) -> Result<Pubkey, BanksClientError> {
+    let game = Keypair::new();
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::CreateGame {
+            game: game.pubkey(),
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: data.data(),
+    };
+    process(context, &[instruction], &[creator, &game]).await?;
+    Ok(game.pubkey())
+}
+
+// Places a bet and returns the bet address, which is derived from the number of bets on the game
+pub async fn place_bet(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettor: &Keypair,
+    number: u8,
+    value: u64,
+) -> Result<Pubkey, BanksClientError> {
+    place_bet_with_referral(context, game, bettor, number, value, None).await
+}
+
+pub async fn place_bet_with_referral(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettor: &Keypair,
+    number: u8,
+    value: u64,
+    referral: Option<Pubkey>,
+) -> Result<Pubkey, BanksClientError> {
+    let number_of_bets = fetch_game(context, game).await.number_of_bets;
+    let bet = bet_address(&game, &bettor.pubkey(), number_of_bets);
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::PlaceBet {
+            game,
+            config: config_address(),
+            bettor: bettor.pubkey(),
+            bet,
+            system_program: system_program::ID,
+            recent_blockhashes: recent_blockhashes::ID,
+            referral,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::PlaceBet { number, value }.data(),
+    };
+    process(context, &[instruction], &[bettor]).await?;
+    Ok(bet)
+}
+
+// Places a bet owned by the bettor and funded by the payer, returns the bet address
+pub async fn place_bet_with_payer(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettor: &Keypair,
+    payer: &Keypair,
+    number: u8,
+    value: u64,
+) -> Result<Pubkey, BanksClientError> {
+    let number_of_bets = fetch_game(context, game).await.number_of_bets;
+    let bet = bet_address(&game, &bettor.pubkey(), number_of_bets);
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::PlaceBetWithPayer {
+            game,
+            config: config_address(),
+            bettor: bettor.pubkey(),
+            payer: payer.pubkey(),
+            bet,
+            system_program: system_program::ID,
+            recent_blockhashes: recent_blockhashes::ID,
+            referral: None,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::PlaceBetWithPayer { number, value }.data(),
+    };
+    process(context, &[instruction], &[bettor, payer]).await?;
+    Ok(bet)
+}
+
+pub async fn claim_prize(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettor: &Keypair,
+    bet: Pubkey,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::ClaimPrize {
+            game,
+            bettor: bettor.pubkey(),
+            bet,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::ClaimPrize {}.data(),
+    };
+    process(context, &[instruction], &[bettor]).await
+}
+
+pub async fn cancel_game(context: &mut ProgramTestContext, game: Pubkey, creator: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::CancelGame {
+            game,
+            creator: creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::CancelGame {}.data(),
+    };
+    process(context, &[instruction], &[creator]).await
+}
+
+pub async fn set_paused(context: &mut ProgramTestContext, admin: &Keypair, paused: bool) -> Result<(), BanksClientError> {
+    let accounts = gotcritter::accounts::UpdateConfig {
+        config: config_address(),
+        admin: admin.pubkey(),
+    }
+    .to_account_metas(None);
+    let data = if paused {
+        gotcritter::instruction::Pause {}.data()
+    } else {
+        gotcritter::instruction::Unpause {}.data()
+    };
+    let instruction = Instruction { program_id: gotcritter::ID, accounts, data };
+    process(context, &[instruction], &[admin]).await
+}
+
+pub async fn refund_bet(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettor: &Keypair,
+    bet: Pubkey,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::RefundBet {
+            game,
+            config: config_address(),
+            bettor: bettor.pubkey(),
+            bet,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::RefundBet {}.data(),
+    };
+    process(context, &[instruction], &[bettor]).await
+}
+
+pub async fn set_referral_fee(
+    context: &mut ProgramTestContext,
+    admin: &Keypair,
+    referral_fee_bps: u16,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::UpdateConfig {
+            config: config_address(),
+            admin: admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::SetReferralFee { referral_fee_bps }.data(),
+    };
+    process(context, &[instruction], &[admin]).await
+}
+
+pub async fn set_withdrawal_penalty(
+    context: &mut ProgramTestContext,
+    admin: &Keypair,
+    withdrawal_penalty_bps: u16,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::UpdateConfig {
+            config: config_address(),
+            admin: admin.pubkey(),
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::SetWithdrawalPenalty { withdrawal_penalty_bps }.data(),
+    };
+    process(context, &[instruction], &[admin]).await
+}
+
+pub async fn withdraw_bet(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    bettor: &Keypair,
+    bet: Pubkey,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::WithdrawBet {
+            game,
+            config: config_address(),
+            bettor: bettor.pubkey(),
+            bet,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::WithdrawBet {}.data(),
+    };
+    process(context, &[instruction], &[bettor]).await
+}
+
+pub async fn register_referrer(context: &mut ProgramTestContext, referrer: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::RegisterReferrer {
+            referral: referral_address(&referrer.pubkey()),
+            referrer: referrer.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::RegisterReferrer {}.data(),
+    };
+    process(context, &[instruction], &[referrer]).await
+}
+
+pub async fn withdraw_referral_rewards(context: &mut ProgramTestContext, referrer: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::WithdrawReferralRewards {
+            referral: referral_address(&referrer.pubkey()),
+            referrer: referrer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::WithdrawReferralRewards {}.data(),
+    };
+    process(context, &[instruction], &[referrer]).await
+}
+
+// Bets the minimum value on every number, so the betting period is allowed to end
+pub async fn fill_game(context: &mut ProgramTestContext, game: Pubkey, bettor: &Keypair) -> Vec<Pubkey> {
+    let mut bets = Vec::with_capacity(25);
+    for number in 1..=25 {
+        bets.push(place_bet(context, game, bettor, number, MIN_BET_VALUE).await.unwrap());
+    }
+    bets
+}
+
+// Places a bet on a Special Block after the minimum ending slot, which ends the betting period
+pub async fn end_betting_period(context: &mut ProgramTestContext, game: Pubkey, closer: &Keypair) {
+    let min_ending_slot = fetch_game(context, game).await.min_ending_slot;
+    if current_slot(context).await < min_ending_slot {
+        context.warp_to_slot(min_ending_slot).unwrap();
+    }
+
+    set_recent_blockhash(context, special_blockhash(1));
+    place_bet(context, game, closer, 1, MIN_BET_VALUE).await.unwrap();
+
+    assert!(fetch_game(context, game).await.is_betting_period_ended());
+}
+
+pub async fn create_syndicate(
+    context: &mut ProgramTestContext,
+    game: Pubkey,
+    manager: &Keypair,
+    number: u8,
+) -> Result<Pubkey, BanksClientError> {
+    let syndicate = syndicate_address(&game, &manager.pubkey());
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::CreateSyndicate {
+            syndicate,
+            game,
+            manager: manager.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::CreateSyndicate { number }.data(),
+    };
+    process(context, &[instruction], &[manager]).await?;
+    Ok(syndicate)
+}
+
+pub async fn join_syndicate(
+    context: &mut ProgramTestContext,
+    syndicate: Pubkey,
+    member: &Keypair,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::JoinSyndicate {
+            syndicate,
+            syndicate_member: syndicate_member_address(&syndicate, &member.pubkey()),
+            member: member.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::JoinSyndicate { amount }.data(),
+    };
+    process(context, &[instruction], &[member]).await
+}
+
+pub async fn deposit_to_syndicate(
+    context: &mut ProgramTestContext,
+    syndicate: Pubkey,
+    member: &Keypair,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::DepositToSyndicate {
+            syndicate,
+            syndicate_member: syndicate_member_address(&syndicate, &member.pubkey()),
+            member: member.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::DepositToSyndicate { amount }.data(),
+    };
+    process(context, &[instruction], &[member]).await
+}
+
+// Places the syndicate bet and returns its address, derived from the syndicate as the bettor
+pub async fn place_syndicate_bet(
+    context: &mut ProgramTestContext,
+    syndicate: Pubkey,
+    manager: &Keypair,
+) -> Result<Pubkey, BanksClientError> {
+    let game = fetch_syndicate(context, syndicate).await.game;
+    let number_of_bets = fetch_game(context, game).await.number_of_bets;
+    let bet = bet_address(&game, &syndicate, number_of_bets);
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::PlaceSyndicateBet {
+            syndicate,
+            manager: manager.pubkey(),
+            game,
+            config: config_address(),
+            bet,
+            system_program: system_program::ID,
+            recent_blockhashes: recent_blockhashes::ID,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::PlaceSyndicateBet {}.data(),
+    };
+    process(context, &[instruction], &[manager]).await?;
+    Ok(bet)
+}
+
+pub async fn claim_syndicate_prize(context: &mut ProgramTestContext, syndicate: Pubkey) -> Result<(), BanksClientError> {
+    let syndicate_account = fetch_syndicate(context, syndicate).await;
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::ClaimSyndicatePrize {
+            syndicate,
+            game: syndicate_account.game,
+            bet: syndicate_account.bet,
+            manager: syndicate_account.manager,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::ClaimSyndicatePrize {}.data(),
+    };
+    process(context, &[instruction], &[]).await
+}
+
+pub async fn refund_syndicate_bet(context: &mut ProgramTestContext, syndicate: Pubkey) -> Result<(), BanksClientError> {
+    let syndicate_account = fetch_syndicate(context, syndicate).await;
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::RefundSyndicateBet {
+            syndicate,
+            game: syndicate_account.game,
+            config: config_address(),
+            bet: syndicate_account.bet,
+            manager: syndicate_account.manager,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::RefundSyndicateBet {}.data(),
+    };
+    process(context, &[instruction], &[]).await
+}
+
+pub async fn withdraw_from_syndicate(
+    context: &mut ProgramTestContext,
+    syndicate: Pubkey,
+    member: &Keypair,
+) -> Result<(), BanksClientError> {
+    let game = fetch_syndicate(context, syndicate).await.game;
+    let instruction = Instruction {
+        program_id: gotcritter::ID,
+        accounts: gotcritter::accounts::WithdrawFromSyndicate {
+            syndicate,
+            syndicate_member: syndicate_member_address(&syndicate, &member.pubkey()),
+            member: member.pubkey(),
+            game,
+        }
+        .to_account_metas(None),
+        data: gotcritter::instruction::WithdrawFromSyndicate {}.data(),
+    };
+    process(context, &[instruction], &[member]).await
+}
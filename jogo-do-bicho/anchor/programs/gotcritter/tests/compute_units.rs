@@ -0,0 +1,70 @@
+// Compute units of the main instructions, to compare layouts and to know how much is left when place_bet
+// is composed with other instructions. The native processor used by the other tests doesn't consume compute
+// units like the SBF program, so this one only runs against the built program:
+//
+// cargo test-sbf --test compute_units -- --ignored --nocapture
+mod common;
+
+use common::*;
+use solana_sdk::signature::{Keypair, Signer};
+
+const DEFAULT_INSTRUCTION_BUDGET: u64 = 200_000; // compute units of an instruction without a compute budget request
+
+#[tokio::test]
+#[ignore = "needs the built program, run with cargo test-sbf -- --ignored"]
+async fn reports_compute_units() {
+    assert!(
+        std::env::var("SBF_OUT_DIR").is_ok() || std::env::var("BPF_OUT_DIR").is_ok(),
+        "the native processor doesn't measure compute units, run with cargo test-sbf"
+    );
+
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let referrer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let mut report = Vec::new();
+
+    // A public game, from the first bet to the claim
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+    report.push(("create_game (public)", last_compute_units()));
+
+    let mut bets = vec![place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE).await.unwrap()];
+    report.push(("place_bet (first bet)", last_compute_units()));
+    for number in 2..=25 {
+        bets.push(place_bet(&mut context, game, &bettor, number, MIN_BET_VALUE).await.unwrap());
+    }
+    report.push(("place_bet (25th bet)", last_compute_units()));
+
+    register_referrer(&mut context, &referrer).await.unwrap();
+    set_referral_fee(&mut context, &admin, 500).await.unwrap();
+    place_bet_with_referral(&mut context, game, &bettor, 1, MIN_BET_VALUE, Some(referral_address(&referrer.pubkey())))
+        .await
+        .unwrap();
+    report.push(("place_bet (with referral)", last_compute_units()));
+
+    end_betting_period(&mut context, game, &bettor).await;
+    report.push(("place_bet (ends the betting period)", last_compute_units()));
+
+    let drawn_number = fetch_game(&mut context, game).await.confirmed_drawn_number().unwrap();
+    claim_prize(&mut context, game, &bettor, bets[(drawn_number - 1) as usize]).await.unwrap();
+    report.push(("claim_prize", last_compute_units()));
+
+    // A private game with every participant, the bettor is the last one to be searched
+    let mut participants: Vec<_> = (0..63).map(|_| Keypair::new().pubkey()).collect();
+    participants.push(bettor.pubkey());
+    let private_game = create_game(&mut context, &admin, 100, Some(participants[..20].to_vec())).await.unwrap();
+    report.push(("create_game (20 participants)", last_compute_units()));
+    add_participants(&mut context, private_game, &admin, participants[20..40].to_vec()).await.unwrap();
+    add_participants(&mut context, private_game, &admin, participants[40..].to_vec()).await.unwrap();
+    report.push(("add_participants (24, up to 64)", last_compute_units()));
+    place_bet(&mut context, private_game, &bettor, 1, MIN_BET_VALUE).await.unwrap();
+    report.push(("place_bet (64 participants)", last_compute_units()));
+
+    // A markdown table, to paste in the Compute Units section of the README next to the previous measurement
+    println!("| {:<40} | {:>7} |", "Instruction", "Units");
+    println!("| {:<40} | {:>7} |", "-".repeat(40), "-".repeat(7));
+    for (instruction, units) in report {
+        println!("| {:<40} | {:>7} |", format!("`{}`", instruction), units);
+        assert!(units < DEFAULT_INSTRUCTION_BUDGET, "{} doesn't fit the default budget", instruction);
+    }
+}
@@ -0,0 +1,904 @@
+mod common;
+
+use common::*;
+use gotcritter::{BettingWindow, CustomError, PrizeTier, PrizeTiers};
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signer}};
+
+#[tokio::test]
+async fn creates_a_public_game() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+
+    let slot = current_slot(&mut context).await;
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let game = fetch_game(&mut context, game).await;
+
+    assert_eq!(game.creator, creator.pubkey());
+    assert!(game.participants().is_empty());
+    assert_eq!(game.min_ending_slot, slot + 100);
+    assert_eq!(game.total_value, 0);
+    assert_eq!(game.number_of_bets, 0);
+    assert_eq!(game.bets_per_number, [0; 25]);
+    assert_eq!(game.bettors_per_number, [0; 25]);
+    assert!(!game.is_betting_period_ended());
+    assert_eq!(game.confirmed_drawn_number(), None);
+    assert_eq!(game.version, 1);
+}
+
+#[tokio::test]
+async fn places_a_bet() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let bet = place_bet(&mut context, game, &bettor, 7, LAMPORTS_PER_SOL).await.unwrap();
+
+    let game_account = fetch_game(&mut context, game).await;
+    assert_eq!(game_account.bets_per_number[6], LAMPORTS_PER_SOL);
+    assert_eq!(game_account.bettors_per_number[6], 1);
+    assert_eq!(game_account.total_value, LAMPORTS_PER_SOL);
+    assert_eq!(game_account.number_of_bets, 1);
+
+    let bet = fetch_bet(&mut context, bet).await;
+    assert_eq!(bet.game, game);
+    assert_eq!(bet.bettor, bettor.pubkey());
+    assert_eq!(bet.number, 7);
+    assert_eq!(bet.value, LAMPORTS_PER_SOL);
+    assert!(!bet.prize_claimed);
+    assert_eq!(bet.version, 1);
+}
+
+#[tokio::test]
+async fn places_a_bet_funded_by_a_payer() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = Keypair::new(); // only signs, like a PDA of a program calling through CPI
+    let payer = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let bet = place_bet_with_payer(&mut context, game, &bettor, &payer, 7, LAMPORTS_PER_SOL).await.unwrap();
+
+    // The payer funds the bet and its rent, the bet belongs to the bettor
+    let bet_rent = balance(&mut context, bet).await;
+    assert_eq!(balance(&mut context, payer.pubkey()).await, 9 * LAMPORTS_PER_SOL - bet_rent);
+    assert_eq!(balance(&mut context, bettor.pubkey()).await, 0);
+    assert_eq!(fetch_bet(&mut context, bet).await.bettor, bettor.pubkey());
+    assert_eq!(fetch_game(&mut context, game).await.bets_per_number[6], LAMPORTS_PER_SOL);
+
+    // The bet is the same as any other, only the bettor can refund it
+    set_paused(&mut context, &creator, true).await.unwrap();
+    let result = refund_bet(&mut context, game, &payer, bet).await;
+    assert_eq!(custom_error(result), error_code(CustomError::BetDoesNotBelongToBettor));
+    refund_bet(&mut context, game, &bettor, bet).await.unwrap();
+    assert_eq!(balance(&mut context, bettor.pubkey()).await, LAMPORTS_PER_SOL + bet_rent);
+}
+
+#[tokio::test]
+async fn rejects_invalid_bets() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+
+    let result = place_bet(&mut context, game, &bettor, 0, LAMPORTS_PER_SOL).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidNumber));
+
+    let result = place_bet(&mut context, game, &bettor, 26, LAMPORTS_PER_SOL).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidNumber));
+
+    let result = place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE - 1).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidValue));
+}
+
+#[tokio::test]
+async fn rejects_bets_from_outside_a_private_game() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let participant = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let outsider = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, Some(vec![participant.pubkey()])).await.unwrap();
+    assert_eq!(fetch_game(&mut context, game).await.participants(), [participant.pubkey()]);
+
+    let result = place_bet(&mut context, game, &outsider, 1, LAMPORTS_PER_SOL).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::ParticipantNotAllowed));
+
+    place_bet(&mut context, game, &participant, 1, LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(fetch_game(&mut context, game).await.number_of_bets, 1);
+}
+
+#[tokio::test]
+async fn does_not_end_before_the_min_ending_slot() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    fill_game(&mut context, game, &bettor).await;
+
+    // A Special Block before the min ending slot is just a regular bet
+    set_recent_blockhash(&mut context, special_blockhash(1));
+    place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE).await.unwrap();
+
+    let game_account = fetch_game(&mut context, game).await;
+    assert!(!game_account.is_betting_period_ended());
+    assert_eq!(game_account.number_of_bets, 26);
+
+    // After the min ending slot, the next Special Block ends the betting period
+    context.warp_to_slot(game_account.min_ending_slot).unwrap();
+    set_recent_blockhash(&mut context, special_blockhash(2));
+    place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE).await.unwrap();
+
+    assert!(fetch_game(&mut context, game).await.is_betting_period_ended());
+}
+
+#[tokio::test]
+async fn does_not_end_without_bets_on_all_numbers() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 1, None).await.unwrap();
+    for number in 1..=24 {
+        place_bet(&mut context, game, &bettor, number, MIN_BET_VALUE).await.unwrap();
+    }
+
+    let min_ending_slot = fetch_game(&mut context, game).await.min_ending_slot;
+    context.warp_to_slot(min_ending_slot + 1).unwrap();
+    set_recent_blockhash(&mut context, special_blockhash(1));
+    place_bet(&mut context, game, &bettor, 24, MIN_BET_VALUE).await.unwrap();
+
+    assert!(!fetch_game(&mut context, game).await.is_betting_period_ended());
+}
+
+#[tokio::test]
+async fn schedules_games_by_unix_timestamp() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    // The deadline must be in the future and after the start
+    let now = current_timestamp(&mut context).await;
+    let result = create_scheduled_game(&mut context, &creator, BettingWindow::Until { timestamp: now }, None, None).await;
+    assert_eq!(custom_error(result.map(|_| ())), error_code(CustomError::InvalidBettingWindow));
+    let window = BettingWindow::Until { timestamp: now + 3_600 };
+    let result = create_scheduled_game(&mut context, &creator, window, None, Some(now + 3_600)).await;
+    assert_eq!(custom_error(result.map(|_| ())), error_code(CustomError::InvalidBettingWindow));
+
+    // A slot window counts from the creation, it can't wait for a later start
+    let result = create_scheduled_game(&mut context, &creator, BettingWindow::Slots { count: 100 }, None, Some(now + 600)).await;
+    assert_eq!(custom_error(result.map(|_| ())), error_code(CustomError::InvalidBettingWindow));
+
+    let game = create_scheduled_game(&mut context, &creator, window, None, Some(now + 600)).await.unwrap();
+    let game_account = fetch_game(&mut context, game).await;
+    assert_eq!(game_account.starts_at, now + 600);
+    assert_eq!(game_account.min_ending_timestamp, now + 3_600);
+
+    // No bets before the scheduled start
+    let result = place_bet(&mut context, game, &bettor, 1, LAMPORTS_PER_SOL).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::GameNotStarted));
+
+    set_timestamp(&mut context, now + 600).await;
+    fill_game(&mut context, game, &bettor).await;
+
+    // The slots don't matter, a Special Block before the deadline is just a regular bet
+    context.warp_to_slot(game_account.min_ending_slot + 1_000).unwrap();
+    set_timestamp(&mut context, now + 3_599).await;
+    set_recent_blockhash(&mut context, special_blockhash(1));
+    place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE).await.unwrap();
+    assert!(!fetch_game(&mut context, game).await.is_betting_period_ended());
+
+    // From the deadline on, the next Special Block ends the betting period
+    set_timestamp(&mut context, now + 3_600).await;
+    set_recent_blockhash(&mut context, special_blockhash(2));
+    place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE).await.unwrap();
+    assert!(fetch_game(&mut context, game).await.is_betting_period_ended());
+}
+
+#[tokio::test]
+async fn ends_the_betting_period_and_rewards_the_closer() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let closer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    fill_game(&mut context, game, &bettor).await;
+
+    let game_balance_before = balance(&mut context, game).await;
+    end_betting_period(&mut context, game, &closer).await;
+
+    let game_account = fetch_game(&mut context, game).await;
+    let drawn_number = game_account.confirmed_drawn_number().expect("drawn number not confirmed");
+    assert!((1..=25).contains(&drawn_number));
+    assert_eq!(game_account.calculate_drawn_number().unwrap(), drawn_number);
+
+    // The closing bet is not registered, it only pays the reward
+    assert_eq!(game_account.number_of_bets, 25);
+    assert_eq!(game_account.total_value, 25 * MIN_BET_VALUE);
+    assert_eq!(balance(&mut context, game).await, game_balance_before - ENDING_BET_PERIOD_REWARD);
+
+    // No more bets are accepted
+    let result = place_bet(&mut context, game, &bettor, 1, MIN_BET_VALUE).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::BettingPeriodHasEnded));
+}
+
+#[tokio::test]
+async fn splits_the_prize_among_the_winners() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let small_bettor = funded_keypair(&mut context, 50 * LAMPORTS_PER_SOL).await;
+    let big_bettor = funded_keypair(&mut context, 100 * LAMPORTS_PER_SOL).await;
+
+    // Both bettors bet on every number, the big one with 3 times the value
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let mut small_bets = Vec::new();
+    let mut big_bets = Vec::new();
+    for number in 1..=25 {
+        small_bets.push(place_bet(&mut context, game, &small_bettor, number, LAMPORTS_PER_SOL).await.unwrap());
+        big_bets.push(place_bet(&mut context, game, &big_bettor, number, 3 * LAMPORTS_PER_SOL).await.unwrap());
+    }
+
+    let closer = context.payer.insecure_clone();
+    end_betting_period(&mut context, game, &closer).await;
+
+    let game_account = fetch_game(&mut context, game).await;
+    let drawn_number = game_account.confirmed_drawn_number().unwrap();
+    let pool = 100 * LAMPORTS_PER_SOL - ENDING_BET_PERIOD_REWARD;
+
+    let small_winning_bet = small_bets[(drawn_number - 1) as usize];
+    let big_winning_bet = big_bets[(drawn_number - 1) as usize];
+    let small_bet = fetch_bet(&mut context, small_winning_bet).await;
+    let big_bet = fetch_bet(&mut context, big_winning_bet).await;
+    assert_eq!(game_account.calculate_prize(&small_bet, drawn_number).unwrap(), pool / 4);
+    assert_eq!(game_account.calculate_prize(&big_bet, drawn_number).unwrap(), pool * 3 / 4);
+
+    // Losing bets have no prize
+    let losing_number = drawn_number % 25 + 1;
+    let losing_bet = fetch_bet(&mut context, small_bets[(losing_number - 1) as usize]).await;
+    assert_eq!(game_account.calculate_prize(&losing_bet, drawn_number).unwrap(), 0);
+    let result = claim_prize(&mut context, game, &small_bettor, small_bets[(losing_number - 1) as usize]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::NoPrize));
+
+    // The winners receive the prize plus the rent of the closed bet
+    let small_balance_before = balance(&mut context, small_bettor.pubkey()).await;
+    let small_bet_rent = balance(&mut context, small_winning_bet).await;
+    claim_prize(&mut context, game, &small_bettor, small_winning_bet).await.unwrap();
+    assert_eq!(
+        balance(&mut context, small_bettor.pubkey()).await,
+        small_balance_before + pool / 4 + small_bet_rent
+    );
+
+    claim_prize(&mut context, game, &big_bettor, big_winning_bet).await.unwrap();
+
+    let game_account = fetch_game(&mut context, game).await;
+    assert_eq!(game_account.value_provided_to_winners, pool / 4 + pool * 3 / 4);
+    assert!(!account_exists(&mut context, small_winning_bet).await);
+}
+
+#[tokio::test]
+async fn splits_the_pool_among_the_prize_tiers() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 30 * LAMPORTS_PER_SOL).await;
+
+    // The neighbour and group tiers can't take more than half of the pool
+    let result = create_tiered_game(&mut context, &creator, 100, PrizeTiers { neighbour_bps: 3_000, group_bps: 2_001 }).await;
+    assert_eq!(custom_error(result.map(|_| ())), error_code(CustomError::InvalidPrizeTiers));
+
+    let tiers = PrizeTiers { neighbour_bps: 1_000, group_bps: 1_000 };
+    let game = create_tiered_game(&mut context, &creator, 100, tiers).await.unwrap();
+    let mut bets = Vec::new();
+    for number in 1..=25 {
+        bets.push(place_bet(&mut context, game, &bettor, number, LAMPORTS_PER_SOL).await.unwrap());
+    }
+    finish_game(&mut context, game, 7).await;
+
+    // 80% to the 7, 10% to the 6 and the 8, and 10% to the rest of its group (9 and 10)
+    let game_account = fetch_game(&mut context, game).await;
+    let pool = 25 * LAMPORTS_PER_SOL - ENDING_BET_PERIOD_REWARD;
+    let expected = [
+        (7, PrizeTier::Exact, pool - 2 * (pool / 10)),
+        (6, PrizeTier::Neighbour, pool / 10 / 2),
+        (8, PrizeTier::Neighbour, pool / 10 / 2),
+        (9, PrizeTier::Group, pool / 10 / 2),
+        (10, PrizeTier::Group, pool / 10 / 2),
+        (5, PrizeTier::None, 0),
+        (11, PrizeTier::None, 0),
+    ];
+    for (number, tier, prize) in expected {
+        let bet = fetch_bet(&mut context, bets[number as usize - 1]).await;
+        assert_eq!(game_account.prize_tier(number, 7), tier);
+        assert_eq!(game_account.calculate_prize(&bet, 7).unwrap(), prize);
+    }
+
+    // Bets on a tier are claimed like the ones on the drawn number
+    let bettor_balance = balance(&mut context, bettor.pubkey()).await;
+    let bet_rent = balance(&mut context, bets[8]).await;
+    claim_prize(&mut context, game, &bettor, bets[8]).await.unwrap();
+    assert_eq!(balance(&mut context, bettor.pubkey()).await, bettor_balance + pool / 10 / 2 + bet_rent);
+    let result = claim_prize(&mut context, game, &bettor, bets[10]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::NoPrize));
+}
+
+#[tokio::test]
+async fn does_not_claim_twice() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let bets = fill_game(&mut context, game, &bettor).await;
+
+    // Claiming before the end of the betting period is not allowed
+    let result = claim_prize(&mut context, game, &bettor, bets[0]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::GameNotFinished));
+
+    end_betting_period(&mut context, game, &creator).await;
+
+    let drawn_number = fetch_game(&mut context, game).await.confirmed_drawn_number().unwrap();
+    let winning_bet = bets[(drawn_number - 1) as usize];
+    claim_prize(&mut context, game, &bettor, winning_bet).await.unwrap();
+
+    // The bet is closed when the prize is claimed, so the second claim can't load it
+    let result = claim_prize(&mut context, game, &bettor, winning_bet).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::AccountNotInitialized)
+    );
+}
+
+#[tokio::test]
+async fn does_not_claim_someone_else_bet() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let thief = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let bets = fill_game(&mut context, game, &bettor).await;
+    end_betting_period(&mut context, game, &creator).await;
+
+    let drawn_number = fetch_game(&mut context, game).await.confirmed_drawn_number().unwrap();
+    let result = claim_prize(&mut context, game, &thief, bets[(drawn_number - 1) as usize]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::BetDoesNotBelongToBettor));
+}
+
+#[tokio::test]
+async fn cancels_a_game_without_bets() {
+    let mut context = start().await;
+    let creator = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let empty_game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let game_with_bets = create_game(&mut context, &creator, 100, None).await.unwrap();
+    place_bet(&mut context, game_with_bets, &bettor, 1, MIN_BET_VALUE).await.unwrap();
+
+    // Only the creator can cancel
+    let result = cancel_game(&mut context, empty_game, &bettor).await;
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidCreator));
+
+    let result = cancel_game(&mut context, game_with_bets, &creator).await;
+    assert_eq!(custom_error(result), error_code(CustomError::GameHasBets));
+
+    let rent = balance(&mut context, empty_game).await;
+    let creator_balance_before = balance(&mut context, creator.pubkey()).await;
+    cancel_game(&mut context, empty_game, &creator).await.unwrap();
+
+    assert!(!account_exists(&mut context, empty_game).await);
+    assert_eq!(balance(&mut context, creator.pubkey()).await, creator_balance_before + rent);
+}
+
+#[tokio::test]
+async fn cancels_a_game_whose_bets_were_withdrawn() {
+    let mut context = start().await;
+    let creator = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let withdrawn_bet = place_bet(&mut context, game, &bettor, 1, LAMPORTS_PER_SOL).await.unwrap();
+    let remaining_bet = place_bet(&mut context, game, &bettor, 2, LAMPORTS_PER_SOL).await.unwrap();
+    withdraw_bet(&mut context, game, &bettor, withdrawn_bet).await.unwrap();
+
+    let result = cancel_game(&mut context, game, &creator).await;
+    assert_eq!(custom_error(result), error_code(CustomError::GameHasBets));
+
+    // The penalty of the withdrawn bets has no winner to go to, it is closed to the creator with the rent
+    withdraw_bet(&mut context, game, &bettor, remaining_bet).await.unwrap();
+    let game_balance = balance(&mut context, game).await;
+    let creator_balance_before = balance(&mut context, creator.pubkey()).await;
+    cancel_game(&mut context, game, &creator).await.unwrap();
+
+    assert!(!account_exists(&mut context, game).await);
+    assert_eq!(balance(&mut context, creator.pubkey()).await, creator_balance_before + game_balance);
+}
+
+#[tokio::test]
+async fn refunds_open_games_while_paused() {
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+    let bet = place_bet(&mut context, game, &bettor, 3, LAMPORTS_PER_SOL).await.unwrap();
+
+    // Refunds are not available while the program is running
+    let result = refund_bet(&mut context, game, &bettor, bet).await;
+    assert_eq!(custom_error(result), error_code(CustomError::ProgramNotPaused));
+
+    // Only the admin can pause
+    let result = set_paused(&mut context, &bettor, true).await;
+    assert_eq!(custom_error(result), error_code(CustomError::Unauthorized));
+
+    set_paused(&mut context, &admin, true).await.unwrap();
+
+    let result = place_bet(&mut context, game, &bettor, 4, LAMPORTS_PER_SOL).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::ProgramPaused));
+
+    let bettor_balance_before = balance(&mut context, bettor.pubkey()).await;
+    let bet_rent = balance(&mut context, bet).await;
+    refund_bet(&mut context, game, &bettor, bet).await.unwrap();
+
+    assert_eq!(
+        balance(&mut context, bettor.pubkey()).await,
+        bettor_balance_before + LAMPORTS_PER_SOL + bet_rent
+    );
+    let game_account = fetch_game(&mut context, game).await;
+    assert_eq!(game_account.total_value, 0);
+    assert_eq!(game_account.bets_per_number[2], 0);
+    assert_eq!(game_account.bettors_per_number[2], 0);
+
+    // Bets are accepted again after unpausing
+    set_paused(&mut context, &admin, false).await.unwrap();
+    place_bet(&mut context, game, &bettor, 4, LAMPORTS_PER_SOL).await.unwrap();
+}
+
+#[tokio::test]
+async fn does_not_refund_finished_games() {
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+    let bets = fill_game(&mut context, game, &bettor).await;
+    end_betting_period(&mut context, game, &admin).await;
+
+    set_paused(&mut context, &admin, true).await.unwrap();
+
+    let result = refund_bet(&mut context, game, &bettor, bets[0]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::BettingPeriodHasEnded));
+
+    // Winners can still claim while paused
+    let drawn_number = fetch_game(&mut context, game).await.confirmed_drawn_number().unwrap();
+    claim_prize(&mut context, game, &bettor, bets[(drawn_number - 1) as usize]).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdraws_a_bet_with_a_penalty_kept_in_the_pot() {
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+    let bet = place_bet(&mut context, game, &bettor, 7, LAMPORTS_PER_SOL).await.unwrap();
+    place_bet(&mut context, game, &bettor, 8, LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(fetch_config(&mut context).await.withdrawal_penalty_bps, 500);
+
+    // Only the bettor can withdraw
+    let stranger = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let result = withdraw_bet(&mut context, game, &stranger, bet).await;
+    assert_eq!(custom_error(result), error_code(CustomError::BetDoesNotBelongToBettor));
+
+    // The bettor gets the value minus 5% and the rent of the bet, the penalty stays in the pot
+    let bet_rent = balance(&mut context, bet).await;
+    let bettor_balance = balance(&mut context, bettor.pubkey()).await;
+    withdraw_bet(&mut context, game, &bettor, bet).await.unwrap();
+    assert_eq!(balance(&mut context, bettor.pubkey()).await, bettor_balance + LAMPORTS_PER_SOL * 95 / 100 + bet_rent);
+    assert!(!account_exists(&mut context, bet).await);
+
+    let game_account = fetch_game(&mut context, game).await;
+    assert_eq!(game_account.bets_per_number[6], 0);
+    assert_eq!(game_account.bettors_per_number[6], 0);
+    assert_eq!(game_account.total_value, LAMPORTS_PER_SOL + LAMPORTS_PER_SOL * 5 / 100);
+
+    // The admin can change the penalty, up to 50%
+    let result = set_withdrawal_penalty(&mut context, &admin, 5_001).await;
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidWithdrawalPenalty));
+    let result = set_withdrawal_penalty(&mut context, &stranger, 0).await;
+    assert_eq!(custom_error(result), error_code(CustomError::Unauthorized));
+    set_withdrawal_penalty(&mut context, &admin, 0).await.unwrap();
+    let bet = place_bet(&mut context, game, &bettor, 9, LAMPORTS_PER_SOL).await.unwrap();
+    let bettor_balance = balance(&mut context, bettor.pubkey()).await;
+    withdraw_bet(&mut context, game, &bettor, bet).await.unwrap();
+    assert_eq!(balance(&mut context, bettor.pubkey()).await, bettor_balance + LAMPORTS_PER_SOL + bet_rent);
+
+    // While paused, bets are refunded without penalty instead
+    let bet = place_bet(&mut context, game, &bettor, 9, LAMPORTS_PER_SOL).await.unwrap();
+    set_paused(&mut context, &admin, true).await.unwrap();
+    let result = withdraw_bet(&mut context, game, &bettor, bet).await;
+    assert_eq!(custom_error(result), error_code(CustomError::ProgramPaused));
+}
+
+#[tokio::test]
+async fn does_not_withdraw_after_the_min_ending_slot() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let bets = fill_game(&mut context, game, &bettor).await;
+
+    // Withdrawing the only bet on a number empties it, the game can't end until it is bet on again
+    withdraw_bet(&mut context, game, &bettor, bets[0]).await.unwrap();
+    let game_account = fetch_game(&mut context, game).await;
+    assert_eq!(game_account.bets_per_number[0], 0);
+    context.warp_to_slot(game_account.min_ending_slot).unwrap();
+    set_recent_blockhash(&mut context, special_blockhash(1));
+    place_bet(&mut context, game, &bettor, 2, MIN_BET_VALUE).await.unwrap();
+    assert!(!fetch_game(&mut context, game).await.is_betting_period_ended());
+
+    // After the minimum ending slot the bets are locked until the draw
+    let result = withdraw_bet(&mut context, game, &bettor, bets[1]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::WithdrawalPeriodHasEnded));
+}
+
+#[tokio::test]
+async fn credits_the_referrer() {
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let referrer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let referral = referral_address(&referrer.pubkey());
+
+    register_referrer(&mut context, &referrer).await.unwrap();
+    set_referral_fee(&mut context, &admin, 500).await.unwrap(); // 5%
+
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+
+    // The bettor can't refer themselves
+    let result = place_bet_with_referral(&mut context, game, &referrer, 1, LAMPORTS_PER_SOL, Some(referral))
+        .await
+        .map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::SelfReferral));
+
+    let bet = place_bet_with_referral(&mut context, game, &bettor, 1, LAMPORTS_PER_SOL, Some(referral))
+        .await
+        .unwrap();
+
+    let fee = LAMPORTS_PER_SOL / 20;
+    assert_eq!(fetch_bet(&mut context, bet).await.value, LAMPORTS_PER_SOL - fee);
+    assert_eq!(fetch_game(&mut context, game).await.total_value, LAMPORTS_PER_SOL - fee);
+    assert_eq!(fetch_referral(&mut context, referral).await.pending_rewards, fee);
+
+    let referrer_balance_before = balance(&mut context, referrer.pubkey()).await;
+    withdraw_referral_rewards(&mut context, &referrer).await.unwrap();
+    assert_eq!(balance(&mut context, referrer.pubkey()).await, referrer_balance_before + fee);
+
+    let referral_account = fetch_referral(&mut context, referral).await;
+    assert_eq!(referral_account.pending_rewards, 0);
+    assert_eq!(referral_account.total_rewards, fee);
+
+    let result = withdraw_referral_rewards(&mut context, &referrer).await;
+    assert_eq!(custom_error(result), error_code(CustomError::NoReferralRewards));
+}
+
+#[tokio::test]
+async fn limits_the_referral_fee() {
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let stranger = Keypair::new();
+
+    let result = set_referral_fee(&mut context, &admin, 1_001).await;
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidReferralFee));
+
+    let result = set_referral_fee(&mut context, &stranger, 100).await;
+    assert_eq!(custom_error(result), error_code(CustomError::Unauthorized));
+}
+
+#[tokio::test]
+async fn accepts_up_to_64_participants() {
+    let mut context = start().await;
+    let creator = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let stranger = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let last_participant = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    // The participants don't fit in one transaction, the rest is added after the creation
+    let mut participants: Vec<_> = (0..63).map(|_| Keypair::new().pubkey()).collect();
+    participants.push(last_participant.pubkey());
+    let game = create_game(&mut context, &creator, 100, Some(participants[..20].to_vec())).await.unwrap();
+    add_participants(&mut context, game, &creator, participants[20..45].to_vec()).await.unwrap();
+
+    // Only the creator can add participants
+    let result = add_participants(&mut context, game, &stranger, vec![stranger.pubkey()]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidCreator));
+
+    add_participants(&mut context, game, &creator, participants[45..].to_vec()).await.unwrap();
+    assert_eq!(fetch_game(&mut context, game).await.participants(), participants.as_slice());
+
+    let result = add_participants(&mut context, game, &creator, vec![stranger.pubkey()]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::TooManyParticipants));
+
+    place_bet(&mut context, game, &last_participant, 1, MIN_BET_VALUE).await.unwrap();
+    let result = place_bet(&mut context, game, &stranger, 1, MIN_BET_VALUE).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::ParticipantNotAllowed));
+
+    // A public game stays public
+    let public_game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let result = add_participants(&mut context, public_game, &creator, vec![stranger.pubkey()]).await;
+    assert_eq!(custom_error(result), error_code(CustomError::PublicGame));
+}
+
+#[tokio::test]
+async fn migrates_games_from_the_borsh_layout() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let payer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    // 10 participants was the maximum of the Borsh layout
+    let mut participants: Vec<_> = (0..9).map(|_| Keypair::new().pubkey()).collect();
+    participants.push(bettor.pubkey());
+    let game = create_game(&mut context, &creator, 100, Some(participants)).await.unwrap();
+    let bets = fill_game(&mut context, game, &bettor).await;
+    end_betting_period(&mut context, game, &bettor).await;
+
+    let game_account = fetch_game(&mut context, game).await;
+    let drawn_number = game_account.confirmed_drawn_number().unwrap();
+    let winning_bet = bets[(drawn_number - 1) as usize];
+    rewrite_with_older_layout(&mut context, game, legacy_game_data(&game_account)).await;
+
+    // The current program can't read the older layout
+    let result = claim_prize(&mut context, game, &bettor, winning_bet).await;
+    assert_eq!(custom_error(result), error_code(CustomError::GameNeedsMigration));
+
+    // Only the rent of the extra space is paid, by whoever migrates
+    let game_balance = balance(&mut context, game).await;
+    let payer_balance = balance(&mut context, payer.pubkey()).await;
+    migrate_game(&mut context, game, &payer).await.unwrap();
+    let extra_rent = rent.minimum_balance(GAME_SPACE) - rent.minimum_balance(LEGACY_GAME_SPACE);
+    assert_eq!(balance(&mut context, game).await, game_balance + extra_rent);
+    assert_eq!(balance(&mut context, payer.pubkey()).await, payer_balance - extra_rent);
+
+    // Everything is kept, but the count of bets per number that the Borsh layout didn't have
+    let migrated = fetch_game(&mut context, game).await;
+    assert_eq!(migrated.version, 1);
+    assert_eq!(migrated.participants(), game_account.participants());
+    assert_eq!(migrated.total_value, game_account.total_value);
+    assert_eq!(migrated.bets_per_number, game_account.bets_per_number);
+    assert_eq!(migrated.bettors_per_number, [0; 25]);
+    assert_eq!(migrated.combined_hash, game_account.combined_hash);
+    assert_eq!(migrated.number_of_bets, game_account.number_of_bets);
+    assert_eq!(migrated.confirmed_drawn_number(), Some(drawn_number));
+
+    let result = migrate_game(&mut context, game, &payer).await;
+    assert_eq!(custom_error(result), error_code(CustomError::AlreadyMigrated));
+
+    claim_prize(&mut context, game, &bettor, winning_bet).await.unwrap();
+}
+
+#[tokio::test]
+async fn migrates_only_the_exact_borsh_layout() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let payer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+
+    // A game account of any other size isn't a game of the first deployment, its bytes would be read as fields
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let mut data = legacy_game_data(&fetch_game(&mut context, game).await);
+    data.truncate(LEGACY_GAME_SPACE - 1);
+    rewrite_with_older_layout(&mut context, game, data).await;
+    let result = migrate_game(&mut context, game, &payer).await;
+    assert_eq!(custom_error(result), u32::from(anchor_lang::error::ErrorCode::AccountDidNotDeserialize));
+}
+
+#[tokio::test]
+async fn withdraws_and_refunds_bets_of_a_migrated_game() {
+    let mut context = start().await;
+    let admin = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let payer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &admin, 100, None).await.unwrap();
+    let withdrawn_bet = place_bet(&mut context, game, &bettor, 3, LAMPORTS_PER_SOL).await.unwrap();
+    let refunded_bet = place_bet(&mut context, game, &bettor, 7, LAMPORTS_PER_SOL).await.unwrap();
+    let game_account = fetch_game(&mut context, game).await;
+    rewrite_with_older_layout(&mut context, game, legacy_game_data(&game_account)).await;
+    migrate_game(&mut context, game, &payer).await.unwrap();
+
+    // The migrated game doesn't know how many bettors each number has, the count stays at zero
+    withdraw_bet(&mut context, game, &bettor, withdrawn_bet).await.unwrap();
+    let migrated = fetch_game(&mut context, game).await;
+    assert_eq!(migrated.bets_per_number[2], 0);
+    assert_eq!(migrated.bettors_per_number[2], 0);
+
+    set_paused(&mut context, &admin, true).await.unwrap();
+    let bettor_balance = balance(&mut context, bettor.pubkey()).await;
+    let bet_rent = balance(&mut context, refunded_bet).await;
+    refund_bet(&mut context, game, &bettor, refunded_bet).await.unwrap();
+    assert_eq!(balance(&mut context, bettor.pubkey()).await, bettor_balance + LAMPORTS_PER_SOL + bet_rent);
+
+    let migrated = fetch_game(&mut context, game).await;
+    assert_eq!(migrated.bets_per_number[6], 0);
+    assert_eq!(migrated.bettors_per_number[6], 0);
+}
+
+#[tokio::test]
+async fn migrates_bets_from_the_first_layout() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let payer = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let bets = fill_game(&mut context, game, &bettor).await;
+    end_betting_period(&mut context, game, &bettor).await;
+
+    let drawn_number = fetch_game(&mut context, game).await.confirmed_drawn_number().unwrap();
+    let winning_bet = bets[(drawn_number - 1) as usize];
+    let bet_account = fetch_bet(&mut context, winning_bet).await;
+    rewrite_with_older_layout(&mut context, winning_bet, bet_data_v0(&bet_account)).await;
+
+    // The current program can't read the older layout
+    let result = claim_prize(&mut context, game, &bettor, winning_bet).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::AccountDidNotDeserialize)
+    );
+
+    migrate_bet(&mut context, winning_bet, &payer).await.unwrap();
+    let migrated = fetch_bet(&mut context, winning_bet).await;
+    assert_eq!(migrated.version, 1);
+    assert_eq!(migrated.number, drawn_number);
+    assert_eq!(migrated.value, bet_account.value);
+
+    let result = migrate_bet(&mut context, winning_bet, &payer).await;
+    assert_eq!(custom_error(result), error_code(CustomError::AlreadyMigrated));
+
+    claim_prize(&mut context, game, &bettor, winning_bet).await.unwrap();
+}
+
+#[tokio::test]
+async fn only_migrates_program_accounts() {
+    let mut context = start().await;
+    let payer = context.payer.insecure_clone();
+    let creator = context.payer.insecure_clone();
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+
+    // A game can't be migrated as a bet
+    let result = migrate_bet(&mut context, game, &payer).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch)
+    );
+
+    // Nor an account of another program
+    let result = migrate_game(&mut context, payer.pubkey(), &payer).await;
+    assert_eq!(
+        custom_error(result),
+        u32::from(anchor_lang::error::ErrorCode::ConstraintOwner)
+    );
+}
+
+#[tokio::test]
+async fn splits_the_syndicate_prize_among_its_members() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let manager = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let big_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let small_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let other_bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let syndicate = create_syndicate(&mut context, game, &manager, 7).await.unwrap();
+    let syndicate_rent = balance(&mut context, syndicate).await;
+
+    // Contributions can be added until the bet
+    join_syndicate(&mut context, syndicate, &big_member, 3 * LAMPORTS_PER_SOL).await.unwrap();
+    join_syndicate(&mut context, syndicate, &small_member, LAMPORTS_PER_SOL / 2).await.unwrap();
+    deposit_to_syndicate(&mut context, syndicate, &small_member, LAMPORTS_PER_SOL / 2).await.unwrap();
+    let syndicate_account = fetch_syndicate(&mut context, syndicate).await;
+    assert_eq!(syndicate_account.total_contributed, 4 * LAMPORTS_PER_SOL);
+    assert_eq!(syndicate_account.members, 2);
+    assert_eq!(balance(&mut context, syndicate).await, syndicate_rent + 4 * LAMPORTS_PER_SOL);
+
+    // Only the manager places the bet, with everything contributed
+    let result = place_syndicate_bet(&mut context, syndicate, &big_member).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::NotSyndicateManager));
+    let bet = place_syndicate_bet(&mut context, syndicate, &manager).await.unwrap();
+    let bet_account = fetch_bet(&mut context, bet).await;
+    assert_eq!(bet_account.bettor, syndicate);
+    assert_eq!(bet_account.number, 7);
+    assert_eq!(bet_account.value, 4 * LAMPORTS_PER_SOL);
+    assert_eq!(balance(&mut context, syndicate).await, syndicate_rent);
+
+    let result = place_syndicate_bet(&mut context, syndicate, &manager).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::SyndicateBetAlreadyPlaced));
+    let result = deposit_to_syndicate(&mut context, syndicate, &small_member, LAMPORTS_PER_SOL).await;
+    assert_eq!(custom_error(result), error_code(CustomError::SyndicateBetAlreadyPlaced));
+    let result = withdraw_from_syndicate(&mut context, syndicate, &small_member).await;
+    assert_eq!(custom_error(result), error_code(CustomError::GameNotFinished));
+
+    fill_game(&mut context, game, &other_bettor).await;
+    finish_game(&mut context, game, 7).await;
+    let result = withdraw_from_syndicate(&mut context, syndicate, &small_member).await;
+    assert_eq!(custom_error(result), error_code(CustomError::SyndicatePrizeNotClaimed));
+
+    // Anyone can claim, the prize goes to the syndicate and the rent of the bet to the manager
+    let prize = fetch_game(&mut context, game).await.calculate_prize(&bet_account, 7).unwrap();
+    let bet_rent = balance(&mut context, bet).await;
+    let manager_balance = balance(&mut context, manager.pubkey()).await;
+    claim_syndicate_prize(&mut context, syndicate).await.unwrap();
+    let syndicate_account = fetch_syndicate(&mut context, syndicate).await;
+    assert!(syndicate_account.prize_claimed);
+    assert_eq!(syndicate_account.prize, prize);
+    assert_eq!(balance(&mut context, manager.pubkey()).await, manager_balance + bet_rent);
+
+    // Each member withdraws the share of their contribution and the rent of their member account
+    let member_rent = balance(&mut context, syndicate_member_address(&syndicate, &big_member.pubkey())).await;
+    let big_balance = balance(&mut context, big_member.pubkey()).await;
+    withdraw_from_syndicate(&mut context, syndicate, &big_member).await.unwrap();
+    assert_eq!(balance(&mut context, big_member.pubkey()).await, big_balance + prize * 3 / 4 + member_rent);
+
+    let small_balance = balance(&mut context, small_member.pubkey()).await;
+    withdraw_from_syndicate(&mut context, syndicate, &small_member).await.unwrap();
+    assert_eq!(balance(&mut context, small_member.pubkey()).await, small_balance + prize / 4 + member_rent);
+    assert!(!account_exists(&mut context, syndicate_member_address(&syndicate, &small_member.pubkey())).await);
+    assert!(balance(&mut context, syndicate).await >= syndicate_rent);
+}
+
+#[tokio::test]
+async fn returns_syndicate_contributions_until_the_bet_is_lost() {
+    let mut context = start().await;
+    let creator = context.payer.insecure_clone();
+    let manager = funded_keypair(&mut context, LAMPORTS_PER_SOL).await;
+    let leaving_member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let member = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let other_bettor = funded_keypair(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+    // On a private game the syndicate must be one of the participants
+    let private_game = create_game(&mut context, &creator, 100, Some(vec![member.pubkey()])).await.unwrap();
+    let result = create_syndicate(&mut context, private_game, &manager, 7).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::ParticipantNotAllowed));
+
+    let game = create_game(&mut context, &creator, 100, None).await.unwrap();
+    let result = create_syndicate(&mut context, game, &manager, 26).await.map(|_| ());
+    assert_eq!(custom_error(result), error_code(CustomError::InvalidNumber));
+    let syndicate = create_syndicate(&mut context, game, &manager, 7).await.unwrap();
+
+    // Before the bet the contribution is returned
+    join_syndicate(&mut context, syndicate, &leaving_member, 2 * LAMPORTS_PER_SOL).await.unwrap();
+    let balance_before = balance(&mut context, leaving_member.pubkey()).await;
+    let member_rent = balance(&mut context, syndicate_member_address(&syndicate, &leaving_member.pubkey())).await;
+    withdraw_from_syndicate(&mut context, syndicate, &leaving_member).await.unwrap();
+    assert_eq!(
+        balance(&mut context, leaving_member.pubkey()).await,
+        balance_before + 2 * LAMPORTS_PER_SOL + member_rent
+    );
+    assert_eq!(fetch_syndicate(&mut context, syndicate).await.total_contributed, 0);
+
+    // A refunded bet goes back to the syndicate, which can bet again
+    join_syndicate(&mut context, syndicate, &member, LAMPORTS_PER_SOL).await.unwrap();
+    let bet = place_syndicate_bet(&mut context, syndicate, &manager).await.unwrap();
+    set_paused(&mut context, &creator, true).await.unwrap();
+    refund_syndicate_bet(&mut context, syndicate).await.unwrap();
+    assert!(!account_exists(&mut context, bet).await);
+    assert_eq!(fetch_syndicate(&mut context, syndicate).await.bet, Pubkey::default());
+    assert_eq!(fetch_game(&mut context, game).await.total_value, 0);
+    set_paused(&mut context, &creator, false).await.unwrap();
+    let bet = place_syndicate_bet(&mut context, syndicate, &manager).await.unwrap();
+
+    // The bet lost, there is nothing to claim and the member only gets the rent of the member account back
+    fill_game(&mut context, game, &other_bettor).await;
+    finish_game(&mut context, game, 8).await;
+    let result = claim_syndicate_prize(&mut context, syndicate).await;
+    assert_eq!(custom_error(result), error_code(CustomError::NoPrize));
+
+    let balance_before = balance(&mut context, member.pubkey()).await;
+    let member_rent = balance(&mut context, syndicate_member_address(&syndicate, &member.pubkey())).await;
+    withdraw_from_syndicate(&mut context, syndicate, &member).await.unwrap();
+    assert_eq!(balance(&mut context, member.pubkey()).await, balance_before + member_rent);
+    assert!(account_exists(&mut context, bet).await);
+}
@@ -0,0 +1,146 @@
+// Property tests for the pure math of the game, they don't need a bank
+use bytemuck::Zeroable;
+use gotcritter::{Bet, Game, PrizeTier};
+use proptest::prelude::*;
+
+const ENDING_BET_PERIOD_REWARD: u64 = 10_000_000; // same as the program
+const MIN_BET_VALUE: u64 = 10_000_000; // same as the program
+const MAX_BET_VALUE: u64 = u64::MAX / 512; // keeps the sum of all bets inside a u64, like on-chain
+
+// Builds a finished game with the given bets, the same way place_bet accumulates them
+fn game_with_bets(bets: &[Bet], drawn_number: Option<u8>, combined_hash: [u8; 32]) -> Game {
+    tiered_game_with_bets(bets, drawn_number, combined_hash, (0, 0))
+}
+
+// Same as game_with_bets, with the basis points of the neighbour and group tiers
+fn tiered_game_with_bets(bets: &[Bet], drawn_number: Option<u8>, combined_hash: [u8; 32], tiers: (u16, u16)) -> Game {
+    let mut game = Game::zeroed();
+    (game.neighbour_prize_bps, game.group_prize_bps) = tiers;
+    game.combined_hash = combined_hash;
+    game.betting_period_ended = 1;
+    game.drawn_number_confirmed = drawn_number.unwrap_or(0);
+    game.version = 1;
+    for bet in bets {
+        game.bets_per_number[(bet.number - 1) as usize] += bet.value;
+        game.bettors_per_number[(bet.number - 1) as usize] += 1;
+        game.total_value += bet.value;
+        game.number_of_bets += 1;
+    }
+    game
+}
+
+// Bets are generated as (number, value), Bet doesn't implement Debug
+fn bet_strategy() -> impl Strategy<Value = (u8, u64)> {
+    (1u8..=25, MIN_BET_VALUE..=MAX_BET_VALUE)
+}
+
+// Bets concentrated on a few numbers, so there are often several winners
+fn crowded_bet_strategy() -> impl Strategy<Value = (u8, u64)> {
+    (1u8..=3, MIN_BET_VALUE..=MIN_BET_VALUE * 1_000)
+}
+
+proptest! {
+    #[test]
+    fn drawn_number_is_always_between_1_and_25(combined_hash in any::<[u8; 32]>()) {
+        let game = game_with_bets(&[], None, combined_hash);
+        let drawn_number = game.calculate_drawn_number().unwrap();
+        prop_assert!((1..=25).contains(&drawn_number));
+    }
+
+    #[test]
+    fn confirmed_drawn_number_is_kept(combined_hash in any::<[u8; 32]>(), confirmed in 1u8..=25) {
+        let game = game_with_bets(&[], Some(confirmed), combined_hash);
+        prop_assert_eq!(game.calculate_drawn_number().unwrap(), confirmed);
+    }
+
+    #[test]
+    fn prizes_never_exceed_the_pool(
+        bets in prop::collection::vec(bet_strategy(), 1..256),
+        drawn_number in 1u8..=25,
+    ) {
+        check_prizes(&bets, drawn_number, (0, 0))?;
+    }
+
+    #[test]
+    fn prizes_never_exceed_the_pool_with_many_winners(
+        bets in prop::collection::vec(crowded_bet_strategy(), 1..256),
+        drawn_number in 1u8..=3,
+    ) {
+        check_prizes(&bets, drawn_number, (0, 0))?;
+    }
+
+    #[test]
+    fn prizes_never_exceed_the_pool_with_prize_tiers(
+        bets in prop::collection::vec(bet_strategy(), 1..256),
+        drawn_number in 1u8..=25,
+        neighbour_bps in 0u16..=2_500,
+        group_bps in 0u16..=2_500,
+    ) {
+        check_prizes(&bets, drawn_number, (neighbour_bps, group_bps))?;
+    }
+
+    #[test]
+    fn group_tier_includes_the_neighbours_without_a_neighbour_tier(
+        drawn_number in 1u8..=25,
+        group_bps in 1u16..=5_000,
+        value in MIN_BET_VALUE..=MIN_BET_VALUE * 1_000,
+    ) {
+        // The same bet on every number, so every number of the group must get the same prize
+        let bets: Vec<Bet> = (1..=25u8).map(|number| Bet { number, value, ..Default::default() }).collect();
+        let game = tiered_game_with_bets(&bets, Some(drawn_number), [0; 32], (0, group_bps));
+
+        let group = (drawn_number - 1) / 5;
+        let group_prize = bets
+            .iter()
+            .find(|bet| bet.number != drawn_number && (bet.number - 1) / 5 == group)
+            .map(|bet| game.calculate_prize(bet, drawn_number).unwrap())
+            .unwrap();
+        prop_assert!(group_prize > 0);
+
+        for bet in &bets {
+            let tier = game.prize_tier(bet.number, drawn_number);
+            if bet.number == drawn_number {
+                prop_assert_eq!(tier, PrizeTier::Exact);
+            } else if (bet.number - 1) / 5 == group {
+                prop_assert_eq!(tier, PrizeTier::Group);
+                prop_assert_eq!(game.calculate_prize(bet, drawn_number).unwrap(), group_prize);
+            } else {
+                prop_assert_eq!(tier, PrizeTier::None);
+            }
+        }
+    }
+}
+
+fn check_prizes(bets: &[(u8, u64)], drawn_number: u8, tiers: (u16, u16)) -> Result<(), TestCaseError> {
+    let bets: Vec<Bet> = bets
+        .iter()
+        .map(|&(number, value)| Bet { number, value, ..Default::default() })
+        .collect();
+    let game = tiered_game_with_bets(&bets, Some(drawn_number), [0; 32], tiers);
+    let pool = game.total_value.saturating_sub(ENDING_BET_PERIOD_REWARD);
+
+    let mut total_prizes: u128 = 0;
+    for bet in &bets {
+        // calculate_prize must not fail nor overflow for any valid game
+        let prize = game.calculate_prize(bet, drawn_number).unwrap();
+
+        if game.prize_tier(bet.number, drawn_number) != PrizeTier::None {
+            prop_assert!(prize <= pool);
+        } else {
+            prop_assert_eq!(prize, 0);
+        }
+        total_prizes += prize as u128;
+    }
+
+    // The rounding only favours the game, so the winners never take more than the pool
+    prop_assert!(total_prizes <= pool as u128);
+
+    // And at most one lamport per winner is left behind, when someone bet on the drawn number to take the slices
+    // of the tiers without bets
+    let winners = bets.iter().filter(|bet| game.prize_tier(bet.number, drawn_number) != PrizeTier::None).count() as u128;
+    if bets.iter().any(|bet| bet.number == drawn_number) {
+        prop_assert!(pool as u128 - total_prizes <= winners);
+    }
+
+    Ok(())
+}
@@ -25,17 +25,56 @@ export { Gotcritter, GotcritterIDL };
 // The programId is imported from the program IDL.
 export const GOTCRITTER_PROGRAM_ID = new PublicKey(GotcritterIDL.address);
 
+// Game is a zero-copy account, flags are numbers and participants has a fixed size, see gameParticipants
 export type Game = {
   creator: PublicKey;
-  participants: PublicKey[];
+  combinedHash: number[];
   totalValue: BN;
   minEndingSlot: BN;
-  combinedHash: number[];
-  betsPerNumber: BN[];
-  bettingPeriodEnded: boolean;
-  drawnNumberConfirmed: number | null;
   numberOfBets: BN;
   valueProvidedToWinners: BN;
+  betsPerNumber: BN[];
+  bettorsPerNumber: number[];
+  participantsCount: number;
+  bettingPeriodEnded: number; // 0 or 1
+  drawnNumberConfirmed: number; // 0 until the betting period ends
+  version: number;
+  padding: number[];
+  startsAt: BN; // unix timestamp before which bets are rejected, 0 when the game starts right away
+  minEndingTimestamp: BN; // 0 when the betting window is in slots
+  neighbourPrizeBps: number; // the slice of the pool for the bets next to the drawn number
+  groupPrizeBps: number; // the slice of the pool for the other bets on the group of the drawn number
+  reserved: number[];
+  participants: PublicKey[];
+};
+
+// The betting window of createGame, in slots or until a unix timestamp
+export type BettingWindow =
+  | { slots: { count: BN } }
+  | { until: { timestamp: BN } };
+
+// Same as Game::is_min_ending_reached, from then on a Special Block ends the betting period
+export function isMinEndingReached(
+  game: Game,
+  slot: number,
+  timestamp: number
+): boolean {
+  if (!game.minEndingTimestamp.isZero()) {
+    return game.minEndingTimestamp.lte(new BN(timestamp));
+  }
+  return game.minEndingSlot.lte(new BN(slot));
+}
+
+// The prize tiers of createGame, in basis points. The drawn number gets the rest of the pool
+export type PrizeTiers = {
+  neighbourBps: number;
+  groupBps: number;
+};
+
+// The result of the prize view, tier is one of { none: {} }, { exact: {} }, { neighbour: {} } or { group: {} }
+export type Prize = {
+  tier: { none: {} } | { exact: {} } | { neighbour: {} } | { group: {} };
+  value: BN;
 };
 
 export type Bet = {
@@ -46,18 +85,54 @@ export type Bet = {
   prizeClaimed: boolean;
 };
 
+export type Config = {
+  admin: PublicKey;
+  paused: boolean;
+  referralFeeBps: number;
+  withdrawalPenaltyBps: number;
+};
+
+export type ReferralAccount = {
+  referrer: PublicKey;
+  pendingRewards: BN;
+  totalRewards: BN;
+};
+
+// The participants of a private game, empty on public games
+export function gameParticipants(game: Game): PublicKey[] {
+  return game.participants.slice(0, game.participantsCount);
+}
+
 // This is a helper function to get the Gotcritter Anchor program.
 export function getGotcritterProgram(provider: AnchorProvider) {
   return new Program(GotcritterIDL as Gotcritter, provider);
 }
 
+// The config is a singleton PDA, it holds the admin and the pause state
+export function getConfigAddress(
+  programId: PublicKey = GOTCRITTER_PROGRAM_ID
+): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from("config")], programId)[0];
+}
+
+// Each referrer has a single referral account, used to receive the referral rewards of the bets
+export function getReferralAddress(
+  referrer: PublicKey,
+  programId: PublicKey = GOTCRITTER_PROGRAM_ID
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("referral"), referrer.toBuffer()],
+    programId
+  )[0];
+}
+
 export async function findGames(
   connection: Connection,
   program: Program<Gotcritter>,
   options?: {
     onlyPublic?: boolean;
     bettingPeriodEnded?: boolean;
-    minEndingSlotPast?: boolean;
+    minEndingPast?: boolean;
     withBetOnAllNumbers?: boolean;
   }
 ): Promise<ProgramAccount<Game>[]> {
@@ -66,8 +141,8 @@ export async function findGames(
   if (options?.onlyPublic === true) {
     filters.push({
       memcmp: {
-        offset: 8 + 32, // 8 bytes for discriminator + 32 bytes for creator
-        bytes: utils.bytes.bs58.encode(Buffer.from([0, 0, 0, 0])), // 4 bytes representing an empty vector (participants)
+        offset: 8 + 396, // offset for participants_count
+        bytes: utils.bytes.bs58.encode(Buffer.from([0, 0])), // 2 bytes representing no participants
       },
     });
   }
@@ -75,14 +150,14 @@ export async function findGames(
   if (options?.bettingPeriodEnded === false) {
     filters.push({
       memcmp: {
-        offset: 8 + 32 + 4 + 8 + 8 + 32 + 200, // offset for betting_period_ended
+        offset: 8 + 398, // offset for betting_period_ended
         bytes: utils.bytes.bs58.encode(Buffer.from([0])), // false for betting_period_ended
       },
     });
   } else if (options?.bettingPeriodEnded === true) {
     filters.push({
       memcmp: {
-        offset: 8 + 32 + 4 + 8 + 8 + 32 + 200, // offset for betting_period_ended
+        offset: 8 + 398, // offset for betting_period_ended
         bytes: utils.bytes.bs58.encode(Buffer.from([1])), // true for betting_period_ended
       },
     });
@@ -90,15 +165,21 @@ export async function findGames(
 
   const games = await program.account.game.all(filters);
 
+  // The cluster time, the same the program compares the betting window with
+  const slot = await connection.getSlot();
+  const timestamp =
+    (await connection.getBlockTime(slot)) ?? Math.floor(Date.now() / 1000);
+
   const filteredGames: ProgramAccount<Game>[] = [];
 
   for (const game of games) {
     if (
-      (options?.onlyPublic !== true || !game.account.participants.length) && // double check
+      (options?.onlyPublic !== true || !game.account.participantsCount) && // double check
       (options?.bettingPeriodEnded === undefined ||
-        options?.bettingPeriodEnded === game.account.bettingPeriodEnded) && // double check
-      (options?.minEndingSlotPast === undefined ||
-        game.account.minEndingSlot.lt(new BN(await connection.getSlot()))) && // filter by minEndingSlotPast
+        options?.bettingPeriodEnded === !!game.account.bettingPeriodEnded) && // double check
+      (options?.minEndingPast === undefined ||
+        options.minEndingPast ===
+          isMinEndingReached(game.account, slot, timestamp)) && // filter by minEndingPast
       (options?.withBetOnAllNumbers === undefined ||
         game.account.betsPerNumber.every((bet) => bet.gt(new BN(0)))) // filter by checking if all betsPerNumber are greater than 0
     ) {
@@ -10,9 +10,13 @@ import {
   workspace,
 } from "@coral-xyz/anchor";
 import { Gotcritter } from "../target/types/gotcritter";
-import { jest, expect, describe, it } from "@jest/globals";
+import { jest, expect, describe, it, beforeAll } from "@jest/globals";
 import { LAMPORTS_PER_SOL } from "@solana/web3.js";
-import { closeGame } from "@project/anchor";
+import {
+  closeGame,
+  getConfigAddress,
+  getReferralAddress,
+} from "@project/anchor";
 
 jest.setTimeout(70 * 1000);
 
@@ -24,6 +28,19 @@ describe("gotcritter", () => {
   const program = workspace.Gotcritter as Program<Gotcritter>;
   const provider = getProvider();
 
+  beforeAll(async () => {
+    // The config is created only once per program, the provider becomes the admin
+    const config = await provider.connection.getAccountInfo(
+      getConfigAddress(program.programId)
+    );
+    if (!config) {
+      await program.methods
+        .initializeConfig()
+        .accounts({ admin: provider.publicKey })
+        .rpc();
+    }
+  });
+
   it(
     "Deve permitir fazer uma aposta",
     async () => {
@@ -31,7 +48,7 @@ describe("gotcritter", () => {
 
       // Create the game
       await program.methods
-        .createGame(new BN(1), null)
+        .createGame({ slots: { count: new BN(1) } }, null, null, null)
         .accounts({
           game: gameKeypair.publicKey, // we dont have a constraint over the seed, so we need to create and reference a keypair for each game, which is not a problem
           creator: provider.publicKey,
@@ -132,7 +149,7 @@ describe("gotcritter", () => {
           bet: winningBet,
         })
         .view();
-      expect(Number(prize)).toBe((25 - 0.01) * LAMPORTS_PER_SOL); // 0.01 SOL is the reward for the closer
+      expect(Number(prize.value)).toBe((25 - 0.01) * LAMPORTS_PER_SOL); // 0.01 SOL is the reward for the closer
 
       const bettorBalanceBeforeClaim = (
         await provider.connection.getAccountInfo(bettor.publicKey!)
@@ -164,4 +181,216 @@ describe("gotcritter", () => {
     },
     70 * 1000
   );
+
+  it("Deve permitir cancelar um jogo sem apostas", async () => {
+    const gameKeypair = web3.Keypair.generate();
+
+    await program.methods
+      .createGame({ slots: { count: new BN(1) } }, null, null, null)
+      .accounts({
+        game: gameKeypair.publicKey,
+        creator: provider.publicKey,
+      })
+      .signers([gameKeypair])
+      .rpc();
+
+    await program.methods
+      .cancelGame()
+      .accounts({
+        game: gameKeypair.publicKey,
+        creator: provider.publicKey,
+      })
+      .rpc();
+
+    const gameAccount = await provider.connection.getAccountInfo(
+      gameKeypair.publicKey
+    );
+    expect(gameAccount).toBeNull();
+  });
+
+  it("Não deve permitir cancelar um jogo com apostas", async () => {
+    const gameKeypair = web3.Keypair.generate();
+
+    await program.methods
+      .createGame({ slots: { count: new BN(1) } }, null, null, null)
+      .accounts({
+        game: gameKeypair.publicKey,
+        creator: provider.publicKey,
+      })
+      .signers([gameKeypair])
+      .rpc();
+
+    await program.methods
+      .placeBet(1, new BN(LAMPORTS_PER_SOL))
+      .accounts({
+        game: gameKeypair.publicKey,
+        bettor: provider.publicKey,
+      })
+      .rpc();
+
+    await expect(
+      program.methods
+        .cancelGame()
+        .accounts({
+          game: gameKeypair.publicKey,
+          creator: provider.publicKey,
+        })
+        .rpc()
+    ).rejects.toThrow(/GameHasBets/);
+  });
+
+  it("Deve bloquear apostas e permitir reembolsos quando pausado", async () => {
+    const gameKeypair = web3.Keypair.generate();
+
+    await program.methods
+      .createGame({ slots: { count: new BN(1) } }, null, null, null)
+      .accounts({
+        game: gameKeypair.publicKey,
+        creator: provider.publicKey,
+      })
+      .signers([gameKeypair])
+      .rpc();
+
+    const betSignature = await program.methods
+      .placeBet(7, new BN(LAMPORTS_PER_SOL))
+      .accounts({
+        game: gameKeypair.publicKey,
+        bettor: provider.publicKey,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const betTransaction = await provider.connection.getTransaction(
+      betSignature,
+      {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      }
+    );
+    const eventParser = new EventParser(
+      program.programId,
+      new BorshCoder(program.idl)
+    );
+    const betPDA = [
+      ...eventParser.parseLogs(betTransaction?.meta?.logMessages ?? []),
+    ].find((event) => event.name === "betPlaced")?.data.bet;
+
+    await program.methods.pause().accounts({ admin: provider.publicKey }).rpc();
+
+    try {
+      await expect(
+        program.methods
+          .placeBet(8, new BN(LAMPORTS_PER_SOL))
+          .accounts({
+            game: gameKeypair.publicKey,
+            bettor: provider.publicKey,
+          })
+          .rpc()
+      ).rejects.toThrow(/ProgramPaused/);
+
+      await program.methods
+        .refundBet()
+        .accounts({
+          game: gameKeypair.publicKey,
+          bettor: provider.publicKey,
+          bet: betPDA,
+        })
+        .rpc();
+
+      const gameAccount = await program.account.game.fetch(
+        gameKeypair.publicKey
+      );
+      expect(gameAccount.totalValue.toNumber()).toBe(0);
+      expect(gameAccount.betsPerNumber[6].toNumber()).toBe(0);
+      expect(await provider.connection.getAccountInfo(betPDA)).toBeNull();
+    } finally {
+      await program.methods
+        .unpause()
+        .accounts({ admin: provider.publicKey })
+        .rpc();
+    }
+  });
+
+  it("Deve creditar a taxa de indicação ao indicador", async () => {
+    const referrer = web3.Keypair.generate();
+    const referralAddress = getReferralAddress(
+      referrer.publicKey,
+      program.programId
+    );
+
+    // Fund the referrer account so it can pay the rent of the referral account
+    const airDropSignature = await provider.connection.requestAirdrop(
+      referrer.publicKey,
+      web3.LAMPORTS_PER_SOL
+    );
+    const latestBlockhash = await provider.connection.getLatestBlockhash();
+    await provider.connection.confirmTransaction(
+      { signature: airDropSignature, ...latestBlockhash },
+      "confirmed"
+    );
+
+    await program.methods
+      .registerReferrer()
+      .accounts({ referrer: referrer.publicKey })
+      .signers([referrer])
+      .rpc();
+
+    await program.methods
+      .setReferralFee(500) // 5%
+      .accounts({ admin: provider.publicKey })
+      .rpc();
+
+    try {
+      const gameKeypair = web3.Keypair.generate();
+
+      await program.methods
+        .createGame({ slots: { count: new BN(1) } }, null, null, null)
+        .accounts({
+          game: gameKeypair.publicKey,
+          creator: provider.publicKey,
+        })
+        .signers([gameKeypair])
+        .rpc();
+
+      await program.methods
+        .placeBet(3, new BN(LAMPORTS_PER_SOL))
+        .accounts({
+          game: gameKeypair.publicKey,
+          bettor: provider.publicKey,
+          referral: referralAddress,
+        })
+        .rpc();
+
+      const gameAccount = await program.account.game.fetch(
+        gameKeypair.publicKey
+      );
+      expect(gameAccount.totalValue.toNumber()).toBe(0.95 * LAMPORTS_PER_SOL);
+
+      const referralAccount = await program.account.referralAccount.fetch(
+        referralAddress
+      );
+      expect(referralAccount.pendingRewards.toNumber()).toBe(
+        0.05 * LAMPORTS_PER_SOL
+      );
+
+      const referrerBalanceBeforeWithdraw =
+        await provider.connection.getBalance(referrer.publicKey);
+
+      await program.methods
+        .withdrawReferralRewards()
+        .accounts({ referrer: referrer.publicKey })
+        .signers([referrer])
+        .rpc();
+
+      const referrerBalanceAfterWithdraw =
+        await provider.connection.getBalance(referrer.publicKey);
+      expect(referrerBalanceAfterWithdraw).toBeGreaterThan(
+        referrerBalanceBeforeWithdraw + 0.04 * LAMPORTS_PER_SOL
+      );
+    } finally {
+      await program.methods
+        .setReferralFee(0)
+        .accounts({ admin: provider.publicKey })
+        .rpc();
+    }
+  });
 });
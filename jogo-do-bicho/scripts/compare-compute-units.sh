@@ -0,0 +1,57 @@
+#!/usr/bin/env sh
+#
+# This script will print the compute units of the main instructions with the Borsh Game (the commit before the
+# zero-copy one) and with the zero-copy Game, as two markdown tables for the Compute Units section of the README.
+#
+# Each commit is checked out in a temporary worktree and measured with `cargo test-sbf`, so it needs the SBF
+# toolchain (`cargo-build-sbf`). The commit before the zero-copy one has no compute units test, so it gets
+# compute-units-borsh.patch, the same test without the participants that didn't exist yet (up to 10). The zero-copy
+# commit gets the test of this tree, which prints the markdown table.
+#
+# to run:
+# sh scripts/compare-compute-units.sh [<zero-copy commit>]
+
+set -eu
+
+scripts=$(cd "$(dirname "$0")" && pwd)
+root=$(git -C "$scripts" rev-parse --show-toplevel)
+prefix=$(git -C "$scripts/.." rev-parse --show-prefix) # the path of the project in the repository
+zero_copy=${1:-$(git -C "$root" log --format=%H -1 --grep="Switch Game to a zero-copy account")}
+
+if [ -z "$zero_copy" ]; then
+  echo "The zero-copy commit wasn't found, pass it as the first argument" >&2
+  exit 1
+fi
+
+work=$(mktemp -d)
+cleanup() {
+  git -C "$root" worktree remove --force "$work/borsh" 2>/dev/null || true
+  git -C "$root" worktree remove --force "$work/zero-copy" 2>/dev/null || true
+  rm -rf "$work"
+}
+trap cleanup EXIT
+
+measure() {
+  name=$1
+  commit=$2
+  git -C "$root" worktree add --quiet --detach "$work/$name" "$commit"
+
+  # The lock file isn't versioned, both commits are measured with the dependencies of this one
+  if [ -f "$root/${prefix}anchor/Cargo.lock" ]; then
+    cp "$root/${prefix}anchor/Cargo.lock" "$work/$name/${prefix}anchor/"
+  fi
+  if [ "$name" = borsh ]; then
+    git -C "$work/$name" apply "$scripts/compute-units-borsh.patch"
+  else
+    cp "$root/${prefix}anchor/programs/gotcritter/tests/compute_units.rs" "$work/$name/${prefix}anchor/programs/gotcritter/tests/"
+  fi
+
+  echo "### $name ($(git -C "$root" rev-parse --short "$commit"), $(solana --version 2>/dev/null || echo "unknown Solana version"))"
+  echo
+  (cd "$work/$name/${prefix}anchor/programs/gotcritter" &&
+    cargo test-sbf --test compute_units -- --ignored --nocapture | grep '^|')
+  echo
+}
+
+measure borsh "$zero_copy^"
+measure zero-copy "$zero_copy"
@@ -99,7 +99,7 @@ async function main() {
       const betPlacedEvent = events.find((event) => event.name === "betPlaced");
       console.log(
         "Bet placed time:",
-        betPlacedEvent?.data.timestamp.toNumber()
+        betPlacedEvent?.data.header.timestamp.toNumber()
       );
     }
   }
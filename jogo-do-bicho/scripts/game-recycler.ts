@@ -19,12 +19,12 @@ import BN from "bn.js";
  * npx esrun scripts/game-recycler.ts
  */
 // FOR PRODUCTION
-// // around 12 HOURS in milliseconds / 400ms (400ms is the amount of time it takes to process a block)
-// const DEFAULT_GAME_DURATION = Math.floor((12 * 60 * 60 * 1000) / 400);
+// // 12 HOURS in seconds
+// const DEFAULT_GAME_DURATION = 12 * 60 * 60;
 
 // OR FOR TESTING
-// around 5 minutes in milliseconds / 400ms (400ms is the amount of time it takes to process a block)
-const DEFAULT_GAME_DURATION = Math.floor((5 * 60 * 1000) / 400);
+// 5 minutes in seconds
+const DEFAULT_GAME_DURATION = 5 * 60;
 
 async function main() {
   // Connect to the local network
@@ -55,7 +55,7 @@ async function main() {
 
     const closeableGames = await findGames(connection, program, {
       bettingPeriodEnded: false,
-      minEndingSlotPast: true,
+      minEndingPast: true,
       onlyPublic: true,
       withBetOnAllNumbers: true,
     });
@@ -87,8 +87,9 @@ async function main() {
 
     if (anyPublicGame.length === 0) {
       const gameKeypair = web3.Keypair.generate();
+      const deadline = Math.floor(Date.now() / 1000) + DEFAULT_GAME_DURATION;
       await program.methods
-        .createGame(new BN(DEFAULT_GAME_DURATION), null)
+        .createGame({ until: { timestamp: new BN(deadline) } }, null, null, null)
         .accounts({
           game: gameKeypair.publicKey,
           creator: provider.publicKey,
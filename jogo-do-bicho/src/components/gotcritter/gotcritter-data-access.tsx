@@ -48,10 +48,16 @@ export function useGotCritterProgram() {
 
   const createGame = useMutation({
     mutationKey: ["gotcritter", "greet", { cluster }],
-    mutationFn: (duration: anchor.BN) => {
+    // endsAt and startsAt are unix timestamps, the game takes bets from startsAt (right away when null) until endsAt
+    mutationFn: ({ endsAt, startsAt }: { endsAt: number; startsAt: number | null }) => {
       const gameKeypair = anchor.web3.Keypair.generate();
       return program.methods
-        .createGame(duration, null)
+        .createGame(
+          { until: { timestamp: new anchor.BN(endsAt) } },
+          null,
+          startsAt === null ? null : new anchor.BN(startsAt),
+          null
+        )
         .accounts({
           game: gameKeypair.publicKey,
           creator: provider.publicKey,
@@ -13,19 +13,27 @@ import { useMemo, useState } from "react";
 import bs58 from "bs58";
 import { WalletButton } from "../solana/solana-provider";
 import { useWallet } from "@solana/wallet-adapter-react";
-import { Bet, Game } from "@project/anchor";
+import { Bet, Game, gameParticipants } from "@project/anchor";
 
 export function GotCritterCreate() {
   const { createGame } = useGotCritterProgram();
   const { publicKey } = useWallet();
 
   const handleCreateGame = async () => {
-    const [duration] = await quickDialogForm({
+    const [duration, startsIn] = await quickDialogForm({
       title: "Create Game",
-      inputs: [{ label: "Duration", type: "number" }],
+      inputs: [
+        { label: "Duration (minutes)", type: "number" },
+        { label: "Starts in (minutes, optional)", type: "number" },
+      ],
     });
 
-    await createGame.mutateAsync(new BN(duration));
+    const now = Math.floor(Date.now() / 1000);
+    const startsAt = startsIn ? now + Number(startsIn) * 60 : null;
+    await createGame.mutateAsync({
+      endsAt: (startsAt ?? now) + Number(duration) * 60,
+      startsAt,
+    });
   };
 
   return (
@@ -79,6 +87,11 @@ export function GotCritterProgram() {
   );
 }
 
+// A unix timestamp of the game on the local time of the browser
+function formatTimestamp(timestamp: BN) {
+  return new Date(timestamp.toNumber() * 1000).toLocaleString();
+}
+
 function GameCard({ game }: { game: ProgramAccount<Game> }) {
   const { drawnNumber, placeBet, userBets } = useGameProgramAccount({ game });
   const { currentSlot, currentBlockhash } = useGotCritterProgram();
@@ -152,12 +165,14 @@ function GameCard({ game }: { game: ProgramAccount<Game> }) {
             </div>
             <div>
               <span className="font-bold">Private:</span>{" "}
-              {game.account.participants.length > 0 ? "Yes" : "No"}
+              {game.account.participantsCount > 0 ? "Yes" : "No"}
             </div>
-            {game.account.participants.length > 0 && (
+            {game.account.participantsCount > 0 && (
               <div>
                 <span className="font-bold">Participants:</span>{" "}
-                {game.account.participants.map((p) => ellipsify(p.toString()))}
+                {gameParticipants(game.account).map((p) =>
+                  ellipsify(p.toString())
+                )}
               </div>
             )}
             <div>
@@ -180,11 +195,24 @@ function GameCard({ game }: { game: ProgramAccount<Game> }) {
             </div>
             {!game.account.bettingPeriodEnded && (
               <>
-                <div>
-                  <span className="font-bold">Progress:</span>{" "}
-                  {currentSlot.data?.toString()} /{" "}
-                  {game.account.minEndingSlot.toString()}
-                </div>
+                {!game.account.startsAt.isZero() && (
+                  <div>
+                    <span className="font-bold">Starts At:</span>{" "}
+                    {formatTimestamp(game.account.startsAt)}
+                  </div>
+                )}
+                {game.account.minEndingTimestamp.isZero() ? (
+                  <div>
+                    <span className="font-bold">Progress:</span>{" "}
+                    {currentSlot.data?.toString()} /{" "}
+                    {game.account.minEndingSlot.toString()}
+                  </div>
+                ) : (
+                  <div>
+                    <span className="font-bold">Ends At:</span>{" "}
+                    {formatTimestamp(game.account.minEndingTimestamp)}
+                  </div>
+                )}
                 <div>
                   <span className="font-bold">Current Blockhash (last 2):</span>{" "}
                   {getLastTwoDigits(currentBlockhashHex)}
@@ -257,9 +285,9 @@ function BetCard({
       </div>
       <div>
         <span className="font-bold">Estimated Prize:</span>{" "}
-        {prize.data?.toNumber() / LAMPORTS_PER_SOL} SOL
+        {prize.data?.value.toNumber() / LAMPORTS_PER_SOL} SOL
       </div>
-      {prize.data && prize.data > 0 && game.account.bettingPeriodEnded && (
+      {prize.data && prize.data.value.gtn(0) && !!game.account.bettingPeriodEnded && (
         <>
           {!bet.account.prizeClaimed ? (
             <button
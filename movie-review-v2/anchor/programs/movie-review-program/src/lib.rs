@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{mint_to, MintTo, Mint, TokenAccount, Token};
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{burn, mint_to, set_authority, Burn, MintTo, Mint, SetAuthority, TokenAccount, Token};
 use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("2rDaKa9MrQcCEMQRTV64sMxUdfSVYGo6sLw4gwfvS9Qg");
@@ -8,45 +11,182 @@ declare_id!("2rDaKa9MrQcCEMQRTV64sMxUdfSVYGo6sLw4gwfvS9Qg");
 pub mod movie_review_program {
     use super::*;
 
-    pub fn initialize_token_mint(_ctx: Context<InitializeMint>) -> Result<()> {
-        msg!("Token mint initialized");
+    pub fn initialize_token_mint(ctx: Context<InitializeMint>, reward_amount: u64) -> Result<()> {
+        initialize_rewards(&mut ctx.accounts.reward_config, ctx.accounts.user.key(), reward_amount)
+    }
+
+    // The mint created by the first deployment has its creator as the mint authority, so the program can't mint
+    // the rewards. Its authority hands the mint over to the mint PDA itself, before initialize_reward_config
+    pub fn migrate_mint_authority(ctx: Context<MigrateMintAuthority>) -> Result<()> {
+        set_authority(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: ctx.accounts.authority.to_account_info(),
+                    account_or_mint: ctx.accounts.mint.to_account_info(),
+                },
+            ),
+            AuthorityType::MintTokens,
+            Some(ctx.accounts.mint.key()),
+        )
+    }
+
+    // Same as initialize_token_mint for a mint that already exists, once the program is its authority
+    pub fn initialize_reward_config(ctx: Context<InitializeRewardConfig>, reward_amount: u64) -> Result<()> {
+        initialize_rewards(&mut ctx.accounts.reward_config, ctx.accounts.user.key(), reward_amount)
+    }
+
+    pub fn update_reward_amount(ctx: Context<UpdateRewardConfig>, reward_amount: u64) -> Result<()> {
+        require!(reward_amount > 0, MovieReviewError::InvalidRewardAmount);
+
+        ctx.accounts.reward_config.reward_amount = reward_amount;
+
+        emit!(RewardConfigUpdated::new(&ctx.accounts.reward_config));
+        Ok(())
+    }
+
+    // All zero by default, so reviews don't require a deposit until the admin sets these
+    pub fn update_review_rules(
+        ctx: Context<UpdateRewardConfig>,
+        review_deposit: u64,
+        review_cooldown: i64,
+        lock_period: i64,
+    ) -> Result<()> {
+        // The vault holds the sum of the deposits, so each one keeps it above the rent exempt minimum
+        require!(
+            review_deposit == 0 || review_deposit >= Rent::get()?.minimum_balance(0),
+            MovieReviewError::InvalidReviewRules
+        );
+        require!(review_cooldown >= 0 && lock_period >= 0, MovieReviewError::InvalidReviewRules);
+
+        let reward_config = &mut ctx.accounts.reward_config;
+        reward_config.review_deposit = review_deposit;
+        reward_config.review_cooldown = review_cooldown;
+        reward_config.lock_period = lock_period;
+
+        emit!(RewardConfigUpdated::new(reward_config));
         Ok(())
     }
 
     pub fn add_movie_review(ctx: Context<AddMovieReview>, title: String, description: String, rating: u8) -> Result<()> {
-        require!(rating >= MIN_RATING && rating <= MAX_RATING, MovieReviewError::InvalidRating);
-        require!(title.len() <= MAX_TITLE_LENGTH, MovieReviewError::TitleTooLong);
-        require!(description.len() <= MAX_DESCRIPTION_LENGTH, MovieReviewError::DescriptionTooLong);
+        validate_review(&title, &description, rating)?;
+        validate_title_seed(&title)?;
 
-        msg!("Movie Review account created");
-        msg!("Title: {}", title);
-        msg!("Description: {}", description);
-        msg!("Rating: {}", rating);
+        let movie_review = &mut ctx.accounts.movie_review;
+        movie_review.reviewer = ctx.accounts.initializer.key();
+        movie_review.updated_slot = Clock::get()?.slot;
+        movie_review.title = title.clone();
+        movie_review.description = description;
+        movie_review.rating = rating;
+
+        let movie = &mut ctx.accounts.movie;
+        movie.title = title;
+        movie.add_rating(rating);
+
+        let rules = &ctx.accounts.reward_config;
+        let now = Clock::get()?.unix_timestamp;
+        let reviewer_profile = &mut ctx.accounts.reviewer_profile;
+        reviewer_profile.reviewer = ctx.accounts.initializer.key();
+        reviewer_profile.start_review(now, rules.review_cooldown)?;
+        let reward = reviewer_profile.take_reward(rules.reward_amount)?;
+        let origin = reviewer_profile.next_origin();
+
+        // The deposit is refunded when the review is deleted, and the reward is burned if it's before the lock period
+        let movie_review = &mut ctx.accounts.movie_review;
+        movie_review.origin = origin;
+        movie_review.deposit = rules.review_deposit;
+        movie_review.reward = reward;
+        movie_review.unlocks_at = now.saturating_add(rules.lock_period);
+
+        collect_deposit(
+            &ctx.accounts.system_program,
+            &ctx.accounts.initializer,
+            &ctx.accounts.vault,
+            rules.review_deposit,
+        )?;
+
+        mint_reward(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_account,
+            ctx.bumps.mint,
+            reward,
+        )?;
+
+        emit!(ReviewAdded::new(&ctx.accounts.movie_review));
+        emit!(RewardMinted {
+            review: ctx.accounts.movie_review.key(),
+            reviewer: ctx.accounts.initializer.key(),
+            token_account: ctx.accounts.token_account.key(),
+            amount: reward,
+        });
+        Ok(())
+    }
+
+    // Same as add_movie_review, but the review and the movie are keyed by a movie id (see the README)
+    // and the title is only display data, so it can be longer and have any UTF-8 character
+    pub fn add_movie_review_v2(
+        ctx: Context<AddMovieReviewV2>,
+        movie_id: [u8; 32],
+        title: String,
+        description: String,
+        rating: u8,
+    ) -> Result<()> {
+        validate_review_v2(&movie_id, &title, &description, rating)?;
 
         let movie_review = &mut ctx.accounts.movie_review;
         movie_review.reviewer = ctx.accounts.initializer.key();
-        movie_review.title = title;
+        movie_review.updated_slot = Clock::get()?.slot;
+        movie_review.movie_id = movie_id;
+        movie_review.title = title.clone();
         movie_review.description = description;
         movie_review.rating = rating;
 
-        mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    authority: ctx.accounts.initializer.to_account_info(),
-                    to: ctx.accounts.token_account.to_account_info(),
-                    mint: ctx.accounts.mint.to_account_info(),
-                },
-                &[&[
-                    "mint".as_bytes(),
-                    &[ctx.bumps.mint]
-                ]]
-            ),
-            10*10^6
+        // The first review of the movie names it
+        let movie = &mut ctx.accounts.movie;
+        if movie.movie_id != movie_id {
+            movie.movie_id = movie_id;
+            movie.title = title;
+        }
+        movie.add_rating(rating);
+
+        let rules = &ctx.accounts.reward_config;
+        let now = Clock::get()?.unix_timestamp;
+        let reviewer_profile = &mut ctx.accounts.reviewer_profile;
+        reviewer_profile.reviewer = ctx.accounts.initializer.key();
+        reviewer_profile.start_review(now, rules.review_cooldown)?;
+        let reward = reviewer_profile.take_reward(rules.reward_amount)?;
+        let origin = reviewer_profile.next_origin();
+
+        // The deposit is refunded when the review is deleted, and the reward is burned if it's before the lock period
+        let movie_review = &mut ctx.accounts.movie_review;
+        movie_review.origin = origin;
+        movie_review.deposit = rules.review_deposit;
+        movie_review.reward = reward;
+        movie_review.unlocks_at = now.saturating_add(rules.lock_period);
+
+        collect_deposit(
+            &ctx.accounts.system_program,
+            &ctx.accounts.initializer,
+            &ctx.accounts.vault,
+            rules.review_deposit,
         )?;
 
-        msg!("Tokens minted");
+        mint_reward(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_account,
+            ctx.bumps.mint,
+            reward,
+        )?;
 
+        emit!(ReviewAdded::new(&ctx.accounts.movie_review));
+        emit!(RewardMinted {
+            review: ctx.accounts.movie_review.key(),
+            reviewer: ctx.accounts.initializer.key(),
+            token_account: ctx.accounts.token_account.key(),
+            amount: reward,
+        });
         Ok(())
     }
 
@@ -56,79 +196,1104 @@ pub mod movie_review_program {
         description: String,
         rating: u8,
     ) -> Result<()> {
-        // We require that the rating is between 1 and 5
-        require!(
-            rating >= MIN_RATING && rating <= MAX_RATING,
-            MovieReviewError::InvalidRating
-        );
-    
-        // We require that the description is not longer than 50 characters
-        require!(
-            description.len() <= MAX_DESCRIPTION_LENGTH,
-            MovieReviewError::DescriptionTooLong
-        );
-
-        msg!("Movie review account space reallocated");
-        msg!("Title: {}", title);
-        msg!("Description: {}", description);
-        msg!("Rating: {}", rating);
+        // The same validation as add_movie_review
+        validate_review(&title, &description, rating)?;
  
         let movie_review = &mut ctx.accounts.movie_review;
+        require!(movie_review.status != ReviewStatus::Removed, MovieReviewError::ReviewRemoved);
+
+        let movie = &mut ctx.accounts.movie;
+        movie.title = title;
+        if movie_review.status == ReviewStatus::Active {
+            movie.remove_rating(movie_review.rating);
+            movie.add_rating(rating);
+        }
+
+        emit!(ReviewUpdated::new(movie_review, &description, rating));
+        movie_review.record_revision(&mut ctx.accounts.revision, Clock::get()?.slot);
         movie_review.rating = rating;
         movie_review.description = description;
  
         Ok(())
     }
+
+    // The title of the reviews keyed by title is a seed, so renaming moves the review to the address of the new
+    // title. Comments, votes, flags and revisions stay keyed by the origin of the review, so it keeps its history
+    pub fn move_review(ctx: Context<MoveReview>, old_title: String, new_title: String) -> Result<()> {
+        let old_review = &ctx.accounts.old_review;
+        validate_review(&new_title, &old_review.description, old_review.rating)?;
+        validate_title_seed(&new_title)?;
+        require!(old_review.status != ReviewStatus::Removed, MovieReviewError::ReviewRemoved);
+
+        // Like on delete_movie_review, either Movie account can be created here, so both get their title even when
+        // the hidden reviews don't change their counts
+        let (old_movie, new_movie) = (&mut ctx.accounts.old_movie, &mut ctx.accounts.new_movie);
+        old_movie.title = old_title;
+        new_movie.title = new_title.clone();
+        if old_review.status == ReviewStatus::Active {
+            old_movie.remove_rating(old_review.rating);
+            new_movie.add_rating(old_review.rating);
+        }
+
+        let mut review = (**old_review).clone();
+        review.title = new_title;
+        ctx.accounts.new_review.set_inner(review);
+
+        emit!(ReviewMoved {
+            review: ctx.accounts.new_review.key(),
+            from: ctx.accounts.old_review.key(),
+            origin: ctx.accounts.new_review.origin,
+            reviewer: ctx.accounts.new_review.reviewer,
+            old_title: ctx.accounts.old_review.title.clone(),
+            title: ctx.accounts.new_review.title.clone(),
+        });
+        Ok(())
+    }
     
-    pub fn delete_movie_review(_ctx: Context<DeleteMovieReview>, title: String) -> Result<()> {
-        msg!("Movie review for {} deleted", title);
+    pub fn delete_movie_review(ctx: Context<DeleteMovieReview>, title: String) -> Result<()> {
+        // Otherwise the reviewer could take the deposit back before a moderator slashes it
+        require!(ctx.accounts.movie_review.pending_flags == 0, MovieReviewError::ReviewPendingModeration);
+
+        let movie = &mut ctx.accounts.movie;
+        movie.title = title;
+        if ctx.accounts.movie_review.status == ReviewStatus::Active {
+            movie.remove_rating(ctx.accounts.movie_review.rating);
+        }
+
+        let refunded_deposit = release_review(
+            &ctx.accounts.movie_review,
+            &ctx.accounts.initializer,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.system_program,
+        )?;
+        let (burned_reward, owed_reward) = claw_back_reward(
+            &ctx.accounts.movie_review,
+            &ctx.accounts.initializer,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_account,
+            &mut ctx.accounts.reviewer_profile,
+        )?;
+
+        emit!(ReviewDeleted::new(&ctx.accounts.movie_review, refunded_deposit, burned_reward, owed_reward));
+        Ok(())
+    }
+
+    pub fn update_movie_review_v2(
+        ctx: Context<UpdateMovieReviewV2>,
+        _movie_id: [u8; 32],
+        description: String,
+        rating: u8,
+    ) -> Result<()> {
+        let review = &ctx.accounts.movie_review;
+        validate_review_v2(&review.movie_id, &review.title, &description, rating)?;
+
+        let movie_review = &mut ctx.accounts.movie_review;
+        require!(movie_review.status != ReviewStatus::Removed, MovieReviewError::ReviewRemoved);
+
+        // The hidden and removed reviews aren't counted on the movie
+        let movie = &mut ctx.accounts.movie;
+        if movie_review.status == ReviewStatus::Active {
+            movie.remove_rating(movie_review.rating);
+            movie.add_rating(rating);
+        }
+
+        emit!(ReviewUpdated::new(movie_review, &description, rating));
+        movie_review.record_revision(&mut ctx.accounts.revision, Clock::get()?.slot);
+        movie_review.rating = rating;
+        movie_review.description = description;
+
+        Ok(())
+    }
+
+    pub fn delete_movie_review_v2(ctx: Context<DeleteMovieReviewV2>, _movie_id: [u8; 32]) -> Result<()> {
+        require!(ctx.accounts.movie_review.pending_flags == 0, MovieReviewError::ReviewPendingModeration);
+
+        if ctx.accounts.movie_review.status == ReviewStatus::Active {
+            ctx.accounts.movie.remove_rating(ctx.accounts.movie_review.rating);
+        }
+
+        let refunded_deposit = release_review(
+            &ctx.accounts.movie_review,
+            &ctx.accounts.initializer,
+            &ctx.accounts.vault,
+            ctx.bumps.vault,
+            &ctx.accounts.system_program,
+        )?;
+        let (burned_reward, owed_reward) = claw_back_reward(
+            &ctx.accounts.movie_review,
+            &ctx.accounts.initializer,
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_account,
+            &mut ctx.accounts.reviewer_profile,
+        )?;
+
+        emit!(ReviewDeleted::new(&ctx.accounts.movie_review, refunded_deposit, burned_reward, owed_reward));
+        Ok(())
+    }
+
+    // The signer becomes the admin of the moderator set
+    pub fn initialize_moderation(ctx: Context<InitializeModeration>) -> Result<()> {
+        ctx.accounts.moderation_config.admin = ctx.accounts.admin.key();
+
+        emit!(ModerationConfigUpdated::new(&ctx.accounts.moderation_config));
+        Ok(())
+    }
+
+    pub fn add_moderator(ctx: Context<UpdateModerationConfig>, moderator: Pubkey) -> Result<()> {
+        let moderators = &mut ctx.accounts.moderation_config.moderators;
+        require!(!moderators.contains(&moderator), MovieReviewError::ModeratorAlreadyAdded);
+        require!(moderators.len() < MAX_MODERATORS, MovieReviewError::TooManyModerators);
+        moderators.push(moderator);
+
+        emit!(ModerationConfigUpdated::new(&ctx.accounts.moderation_config));
+        Ok(())
+    }
+
+    pub fn remove_moderator(ctx: Context<UpdateModerationConfig>, moderator: Pubkey) -> Result<()> {
+        let moderators = &mut ctx.accounts.moderation_config.moderators;
+        let position = moderators
+            .iter()
+            .position(|key| *key == moderator)
+            .ok_or(MovieReviewError::ModeratorNotFound)?;
+        moderators.remove(position);
+
+        emit!(ModerationConfigUpdated::new(&ctx.accounts.moderation_config));
+        Ok(())
+    }
+
+    // One flag per wallet and review, the moderators look at the reviews with the most flags
+    pub fn flag_review(ctx: Context<FlagReview>) -> Result<()> {
+        let movie_review = &mut ctx.accounts.movie_review;
+        // A removed review can't be moderated again, so its flags would block its deletion forever
+        require!(movie_review.status != ReviewStatus::Removed, MovieReviewError::ReviewRemoved);
+        movie_review.flag_count += 1;
+        movie_review.pending_flags += 1;
+
+        let flag = &mut ctx.accounts.flag;
+        flag.review = movie_review.origin;
+        flag.flagger = ctx.accounts.flagger.key();
+
+        emit!(ReviewFlagged {
+            review: flag.review,
+            flagger: flag.flagger,
+            flag_count: movie_review.flag_count,
+        });
+        Ok(())
+    }
+
+    // Hidden reviews can be restored, removed ones can't. Slashing sends the deposit to the moderation admin
+    // and adds the reward to the debt of the reviewer, paid from their next rewards, so a reviewer who stops
+    // reviewing keeps the tokens
+    pub fn moderate_review(ctx: Context<ModerateReview>, status: ReviewStatus, slash: bool) -> Result<()> {
+        let movie_review = &mut ctx.accounts.movie_review;
+        require!(movie_review.status != ReviewStatus::Removed, MovieReviewError::ReviewRemoved);
+
+        // Only the active reviews are counted on the movie
+        let movie = &mut ctx.accounts.movie;
+        match (movie_review.status == ReviewStatus::Active, status == ReviewStatus::Active) {
+            (true, false) => movie.remove_rating(movie_review.rating),
+            (false, true) => movie.add_rating(movie_review.rating),
+            _ => {}
+        }
+        movie_review.status = status;
+        movie_review.pending_flags = 0;
+
+        let (deposit, reward) = (movie_review.deposit, movie_review.reward);
+        if slash {
+            movie_review.deposit = 0;
+            movie_review.reward = 0;
+
+            let reviewer_profile = &mut ctx.accounts.reviewer_profile;
+            reviewer_profile.reviewer = movie_review.reviewer;
+            reviewer_profile.reward_debt = reviewer_profile.reward_debt.saturating_add(reward);
+
+            if deposit > 0 {
+                transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.vault.to_account_info(),
+                            to: ctx.accounts.admin.to_account_info(),
+                        },
+                        &[&["vault".as_bytes(), &[ctx.bumps.vault]]],
+                    ),
+                    deposit,
+                )?;
+            }
+        }
+
+        emit!(ReviewModerated {
+            review: ctx.accounts.movie_review.key(),
+            moderator: ctx.accounts.moderator.key(),
+            status,
+            slashed_deposit: if slash { deposit } else { 0 },
+            slashed_reward: if slash { reward } else { 0 },
+        });
+        Ok(())
+    }
+
+    // One vote per wallet and review, the reviewer can't vote on their own review
+    pub fn vote_review(ctx: Context<VoteReview>, helpful: bool) -> Result<()> {
+        let movie_review = &mut ctx.accounts.movie_review;
+        require!(movie_review.status == ReviewStatus::Active, MovieReviewError::ReviewNotActive);
+        require_keys_neq!(movie_review.reviewer, ctx.accounts.voter.key(), MovieReviewError::CannotVoteOwnReview);
+
+        let vote = &mut ctx.accounts.vote;
+        vote.review = movie_review.origin;
+        vote.voter = ctx.accounts.voter.key();
+        vote.helpful = helpful;
+
+        let reviewer_profile = &mut ctx.accounts.reviewer_profile;
+        reviewer_profile.reviewer = movie_review.reviewer;
+        if helpful {
+            movie_review.helpful_votes += 1;
+            reviewer_profile.helpful_votes += 1;
+        } else {
+            movie_review.unhelpful_votes += 1;
+            reviewer_profile.unhelpful_votes += 1;
+        }
+
+        emit!(ReviewVoted {
+            review: vote.review,
+            voter: vote.voter,
+            reviewer: reviewer_profile.reviewer,
+            helpful,
+            reputation: reviewer_profile.reputation(),
+        });
+        Ok(())
+    }
+
+    // Any wallet comments on a review, or replies to one of its comments with the index of the parent
+    pub fn add_comment(ctx: Context<AddComment>, content: String, parent: Option<u64>) -> Result<()> {
+        require!(
+            !content.is_empty() && content.len() <= MAX_COMMENT_LENGTH,
+            MovieReviewError::InvalidCommentLength
+        );
+
+        let movie_review = &mut ctx.accounts.movie_review;
+        require!(movie_review.status == ReviewStatus::Active, MovieReviewError::ReviewNotActive);
+        if let Some(parent) = parent {
+            require!(parent < movie_review.comment_count, MovieReviewError::InvalidParentComment);
+        }
+
+        let comment = &mut ctx.accounts.comment;
+        comment.review = movie_review.origin;
+        comment.commenter = ctx.accounts.commenter.key();
+        comment.index = movie_review.comment_count;
+        comment.parent = parent;
+        comment.content = content;
+
+        // The counter is the index of the next comment, so it never goes down and the addresses are never reused
+        movie_review.comment_count += 1;
+
+        emit!(CommentAdded {
+            review: comment.review,
+            comment: comment.key(),
+            commenter: comment.commenter,
+            index: comment.index,
+            parent,
+        });
+        Ok(())
+    }
+
+    pub fn edit_comment(ctx: Context<EditComment>, content: String) -> Result<()> {
+        require!(
+            !content.is_empty() && content.len() <= MAX_COMMENT_LENGTH,
+            MovieReviewError::InvalidCommentLength
+        );
+
+        let comment = &mut ctx.accounts.comment;
+        comment.content = content;
+
+        emit!(CommentEdited {
+            review: comment.review,
+            comment: comment.key(),
+            commenter: comment.commenter,
+            index: comment.index,
+        });
+        Ok(())
+    }
+
+    // The replies of a deleted comment are kept, their parent index just points to a closed account
+    pub fn delete_comment(ctx: Context<DeleteComment>) -> Result<()> {
+        let comment = &ctx.accounts.comment;
+
+        emit!(CommentDeleted {
+            review: comment.review,
+            comment: comment.key(),
+            commenter: comment.commenter,
+            index: comment.index,
+        });
+        Ok(())
+    }
+
+    // Reviews added by the first deployment of the program have the layout before the movie ids, which the other
+    // instructions can't read. Rewrites one in the current layout and counts it on its movie, the reviewer pays the
+    // rent of the extra space. It keeps no deposit and no reward, like a review added with the rules at zero
+    pub fn migrate_review(ctx: Context<MigrateReview>, _title: String) -> Result<()> {
+        let review_info = ctx.accounts.movie_review.to_account_info();
+        // Only the exact layout of the first deployment, the bytes after its fields would be read as new fields
+        let legacy = {
+            let data = review_info.try_borrow_data()?;
+            require!(
+                data.len() == LEGACY_REVIEW_SPACE && data[..DISCRIMINATOR] == MovieAccountState::DISCRIMINATOR,
+                MovieReviewError::NotLegacyReview
+            );
+            LegacyMovieAccountState::deserialize(&mut &data[DISCRIMINATOR..])?
+        };
+
+        let space = DISCRIMINATOR + MovieAccountState::INIT_SPACE;
+        let extra_rent = Rent::get()?.minimum_balance(space).saturating_sub(review_info.lamports());
+        if extra_rent > 0 {
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.initializer.to_account_info(),
+                        to: review_info.clone(),
+                    },
+                ),
+                extra_rent,
+            )?;
+        }
+        review_info.realloc(space, true)?;
+
+        let reviewer_profile = &mut ctx.accounts.reviewer_profile;
+        reviewer_profile.reviewer = ctx.accounts.initializer.key();
+        let review = MovieAccountState {
+            reviewer: legacy.reviewer,
+            rating: legacy.rating,
+            title: legacy.title,
+            description: legacy.description,
+            origin: reviewer_profile.next_origin(),
+            updated_slot: Clock::get()?.slot,
+            ..Default::default()
+        };
+        review.try_serialize(&mut &mut review_info.try_borrow_mut_data()?[..])?;
+
+        let movie = &mut ctx.accounts.movie;
+        movie.title = review.title.clone();
+        movie.add_rating(review.rating);
+
+        emit!(ReviewMigrated {
+            review: review_info.key(),
+            origin: review.origin,
+            reviewer: review.reviewer,
+            title: review.title,
+        });
+        Ok(())
+    }
+}
+
+// The seeds of the reviews keyed by title are the title and the reviewer, so a title starting with the first seed
+// of another PDA could put the review at the address of that account. Only checked when a review takes a new title,
+// so the reviews added before the check can still be updated
+fn validate_title_seed(title: &str) -> Result<()> {
+    require!(
+        !RESERVED_TITLE_PREFIXES.iter().any(|prefix| title.starts_with(prefix)),
+        MovieReviewError::ReservedTitle
+    );
+    Ok(())
+}
+
+fn validate_review(title: &str, description: &str, rating: u8) -> Result<()> {
+    require!((MIN_RATING..=MAX_RATING).contains(&rating), MovieReviewError::InvalidRating);
+    require!(title.len() <= MAX_TITLE_LENGTH, MovieReviewError::TitleTooLong);
+    require!(description.len() <= MAX_DESCRIPTION_LENGTH, MovieReviewError::DescriptionTooLong);
+    Ok(())
+}
+
+// The signer of the initialization is the only one allowed to change the reward later
+fn initialize_rewards(reward_config: &mut RewardConfig, admin: Pubkey, reward_amount: u64) -> Result<()> {
+    require!(reward_amount > 0, MovieReviewError::InvalidRewardAmount);
+
+    reward_config.admin = admin;
+    reward_config.reward_amount = reward_amount;
+
+    emit!(RewardConfigUpdated::new(reward_config));
+    Ok(())
+}
+
+// The title of the reviews keyed by movie id is only display data, limited in characters instead of bytes. The zero
+// movie id marks the reviews keyed by title, see MovieAccountState::movie_address
+fn validate_review_v2(movie_id: &[u8; 32], title: &str, description: &str, rating: u8) -> Result<()> {
+    require!(*movie_id != [0; 32], MovieReviewError::InvalidMovieId);
+    require!((MIN_RATING..=MAX_RATING).contains(&rating), MovieReviewError::InvalidRating);
+    require!(title.chars().count() <= MAX_DISPLAY_TITLE_CHARACTERS, MovieReviewError::DisplayTitleTooLong);
+    require!(description.len() <= MAX_DESCRIPTION_LENGTH, MovieReviewError::DescriptionTooLong);
+    Ok(())
+}
+
+// The mint is its own authority, so the program signs for it with the mint seeds
+fn mint_reward<'info>(
+    token_program: &Program<'info, Token>,
+    mint: &Account<'info, Mint>,
+    token_account: &Account<'info, TokenAccount>,
+    mint_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                authority: mint.to_account_info(),
+                to: token_account.to_account_info(),
+                mint: mint.to_account_info(),
+            },
+            &[&[
+                "mint".as_bytes(),
+                &[mint_bump]
+            ]]
+        ),
+        amount
+    )
+}
+
+// The deposit goes to the vault, a system account of the program, so it's refunded to the reviewer on delete
+fn collect_deposit<'info>(
+    system_program: &Program<'info, System>,
+    initializer: &Signer<'info>,
+    vault: &SystemAccount<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: initializer.to_account_info(),
+                to: vault.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+// Returns the refunded lamports
+fn release_review<'info>(
+    review: &MovieAccountState,
+    initializer: &Signer<'info>,
+    vault: &SystemAccount<'info>,
+    vault_bump: u8,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    if review.deposit == 0 {
+        return Ok(0);
+    }
+    transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: initializer.to_account_info(),
+            },
+            &[&["vault".as_bytes(), &[vault_bump]]],
+        ),
+        review.deposit,
+    )?;
+
+    Ok(review.deposit)
+}
+
+// Deleting a review before the lock period burns its reward, so creating and deleting reviews doesn't farm tokens.
+// When the tokens were moved or the token account closed, the reward becomes a debt like a slashed one instead of
+// blocking the refund of the deposit. Returns the burned and the owed tokens
+fn claw_back_reward<'info>(
+    review: &MovieAccountState,
+    initializer: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+    mint: &Account<'info, Mint>,
+    token_account: &Option<Account<'info, TokenAccount>>,
+    reviewer_profile: &mut Reviewer,
+) -> Result<(u64, u64)> {
+    if review.reward == 0 || Clock::get()?.unix_timestamp >= review.unlocks_at {
+        return Ok((0, 0));
+    }
+    let Some(token_account) = token_account.as_ref().filter(|account| account.amount >= review.reward) else {
+        reviewer_profile.reward_debt = reviewer_profile.reward_debt.saturating_add(review.reward);
+        return Ok((0, review.reward));
+    };
+    burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: mint.to_account_info(),
+                from: token_account.to_account_info(),
+                authority: initializer.to_account_info(),
+            },
+        ),
+        review.reward,
+    )?;
+
+    Ok((review.reward, 0))
+}
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct MovieAccountState {
+    pub reviewer: Pubkey, // 32
+    pub rating: u8, // 1
+    #[max_len(256)]
+    pub title: String, // 4 + len(), up to 64 characters of 4 bytes on the reviews keyed by movie id
+    #[max_len(50)]
+    pub description: String, // 4 + len()
+    pub movie_id: [u8; 32], // 32, zero on the reviews keyed by title
+    pub comment_count: u64, // 8, the index of the next comment
+    pub helpful_votes: u64, // 8
+    pub unhelpful_votes: u64, // 8
+    pub deposit: u64, // 8, the lamports held in the vault until the review is deleted
+    pub reward: u64, // 8, the tokens minted for the review
+    pub unlocks_at: i64, // 8, the reward is burned if the review is deleted before this timestamp
+    pub flag_count: u64, // 8
+    pub pending_flags: u64, // 8, the flags since the last moderation, the review can't be deleted until they're moderated
+    pub status: ReviewStatus, // 1
+    pub origin: Pubkey, // 32, unique to the review and kept when it's moved, keys its comments, votes, flags and revisions
+    pub edit_count: u64, // 8, the number of revisions
+    pub updated_slot: u64, // 8, the slot of the current rating and description
+}
+
+// The layout of MovieAccountState on the first deployment, it has the same discriminator
+#[derive(AnchorDeserialize)]
+pub struct LegacyMovieAccountState {
+    pub reviewer: Pubkey,
+    pub rating: u8,
+    pub title: String,
+    pub description: String,
+}
+
+impl MovieAccountState {
+    // Appends the current rating and description to the history before an update replaces them
+    pub fn record_revision(&mut self, revision: &mut ReviewRevision, slot: u64) {
+        revision.review = self.origin;
+        revision.index = self.edit_count;
+        revision.rating = self.rating;
+        revision.description = self.description.clone();
+        revision.slot = self.updated_slot;
+
+        self.edit_count += 1;
+        self.updated_slot = slot;
+    }
+
+    // The Movie account of the review depends on whether it's keyed by title or by movie id
+    pub fn movie_address(&self) -> Pubkey {
+        let key: &[u8] = if self.movie_id == [0; 32] { self.title.as_bytes() } else { &self.movie_id };
+        Pubkey::find_program_address(&["movie".as_bytes(), key], &crate::ID).0
+    }
+}
+
+// Clients only list the active reviews, and only those are counted on the movie
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace)]
+pub enum ReviewStatus {
+    #[default]
+    Active,
+    Hidden,
+    Removed,
+}
+
+// The aggregate of the reviews of a movie, so clients read the average rating without fetching every review
+#[account]
+#[derive(InitSpace)]
+pub struct Movie {
+    #[max_len(256)]
+    pub title: String, // 4 + len()
+    pub review_count: u64, // 8
+    pub rating_sum: u64, // 8
+    pub rating_histogram: [u64; 5], // 8 * 5, the number of reviews with each rating, from 1 to 5
+    pub movie_id: [u8; 32], // 32, zero on the movies keyed by title
+}
+
+impl Movie {
+    pub fn add_rating(&mut self, rating: u8) {
+        self.review_count += 1;
+        self.rating_sum += rating as u64;
+        self.rating_histogram[(rating - MIN_RATING) as usize] += 1;
+    }
+
+    // Saturates, because reviews created before the Movie accounts existed were never added to them
+    pub fn remove_rating(&mut self, rating: u8) {
+        self.review_count = self.review_count.saturating_sub(1);
+        self.rating_sum = self.rating_sum.saturating_sub(rating as u64);
+        let count = &mut self.rating_histogram[(rating - MIN_RATING) as usize];
+        *count = count.saturating_sub(1);
+    }
+}
+
+// A previous version of a review, the history is append-only since only the updates create revisions
+#[account]
+#[derive(InitSpace)]
+pub struct ReviewRevision {
+    pub review: Pubkey, // 32, the origin of the review
+    pub index: u64, // 8
+    pub rating: u8, // 1
+    #[max_len(50)]
+    pub description: String, // 4 + len()
+    pub slot: u64, // 8, the slot the rating and description were written at
+}
+
+// The profile of a reviewer, the reputation is the balance of the votes received on all their reviews
+#[account]
+#[derive(InitSpace)]
+pub struct Reviewer {
+    pub reviewer: Pubkey, // 32
+    pub helpful_votes: u64, // 8
+    pub unhelpful_votes: u64, // 8
+    pub last_review_at: i64, // 8, zero before the first review
+    pub reward_debt: u64, // 8, the slashed rewards, deducted from the next ones
+    pub review_count: u64, // 8, the reviews created, never decremented
+}
+
+impl Reviewer {
+    pub fn start_review(&mut self, now: i64, cooldown: i64) -> Result<()> {
+        require!(
+            self.last_review_at == 0 || now >= self.last_review_at.saturating_add(cooldown),
+            MovieReviewError::ReviewCooldown
+        );
+        self.last_review_at = now;
         Ok(())
     }
+
+    // The origin of a new review. The address of a review is reused when it's deleted and created again, or when
+    // another review is moved away from it, so the origin is derived from the count of reviews of the reviewer instead
+    pub fn next_origin(&mut self) -> Pubkey {
+        let (origin, _) = Pubkey::find_program_address(
+            &["origin".as_bytes(), self.reviewer.as_ref(), &self.review_count.to_le_bytes()],
+            &crate::ID,
+        );
+        self.review_count += 1;
+        origin
+    }
+
+    pub fn reputation(&self) -> i64 {
+        (self.helpful_votes as i64).saturating_sub(self.unhelpful_votes as i64)
+    }
+
+    // Every point of reputation adds 10% to the reward, up to MAX_REPUTATION_BONUS points,
+    // a negative reputation doesn't reduce it
+    pub fn reward(&self, reward_amount: u64) -> Result<u64> {
+        let bonus = self.reputation().clamp(0, MAX_REPUTATION_BONUS) as u128;
+        let reward = reward_amount as u128 * (REPUTATION_BONUS_DENOMINATOR + bonus) / REPUTATION_BONUS_DENOMINATOR;
+        u64::try_from(reward).map_err(|_| error!(MovieReviewError::RewardOverflow))
+    }
+
+    // The reward of a new review, after paying the debt of the slashed ones
+    pub fn take_reward(&mut self, reward_amount: u64) -> Result<u64> {
+        let reward = self.reward(reward_amount)?;
+        let paid = reward.min(self.reward_debt);
+        self.reward_debt -= paid;
+        Ok(reward - paid)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ModerationConfig {
+    pub admin: Pubkey, // 32
+    #[max_len(10)]
+    pub moderators: Vec<Pubkey>, // 4 + 32 * len()
+}
+
+// A flag on a review, its address makes it one per wallet and review
+#[account]
+#[derive(InitSpace)]
+pub struct Flag {
+    pub review: Pubkey, // 32
+    pub flagger: Pubkey, // 32
+}
+
+// A vote on a review, its address makes it one per wallet and review
+#[account]
+#[derive(InitSpace)]
+pub struct Vote {
+    pub review: Pubkey, // 32
+    pub voter: Pubkey, // 32
+    pub helpful: bool, // 1
+}
+
+// A comment on a review, or a reply to another comment of the same review
+#[account]
+#[derive(InitSpace)]
+pub struct Comment {
+    pub review: Pubkey, // 32
+    pub commenter: Pubkey, // 32
+    pub index: u64, // 8
+    pub parent: Option<u64>, // 1 + 8, the index of the comment it replies to
+    #[max_len(200)]
+    pub content: String, // 4 + len()
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RewardConfig {
+    pub admin: Pubkey, // 32
+    pub reward_amount: u64, // 8, in base units of the mint
+    pub review_deposit: u64, // 8, in lamports
+    pub review_cooldown: i64, // 8, the seconds between two reviews of the same reviewer
+    pub lock_period: i64, // 8, the seconds after which deleting a review keeps its reward
+}
+
+#[derive(Accounts)]
+pub struct InitializeMint<'info> {
+    #[account(
+        init,
+        seeds = ["mint".as_bytes()],
+        bump,
+        payer = user,
+        mint::decimals = MINT_DECIMALS,
+        mint::authority = mint,
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        seeds = ["reward_config".as_bytes()],
+        bump,
+        space = DISCRIMINATOR + RewardConfig::INIT_SPACE,
+        payer = user,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMintAuthority<'info> {
+    #[account(
+        mut,
+        seeds = ["mint".as_bytes()],
+        bump,
+        mint::authority = authority,
+    )]
+    pub mint: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardConfig<'info> {
+    #[account(
+        seeds = ["mint".as_bytes()],
+        bump,
+        mint::authority = mint,
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        seeds = ["reward_config".as_bytes()],
+        bump,
+        space = DISCRIMINATOR + RewardConfig::INIT_SPACE,
+        payer = user,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(title: String, description: String)]
+pub struct AddMovieReview<'info> {
+    #[account(
+        init, 
+        seeds = [title.as_bytes(), initializer.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + MovieAccountState::INIT_SPACE,
+        payer = initializer, 
+    )]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), title.as_bytes()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub movie: Account<'info, Movie>,
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = ["mint".as_bytes()],
+        bump,
+        mut
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        seeds = ["reward_config".as_bytes()],
+        bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    #[account(
+        init_if_needed,
+        seeds = ["reviewer".as_bytes(), initializer.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + Reviewer::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
+    #[account(
+        mut,
+        seeds = ["vault".as_bytes()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = mint,
+        associated_token::authority = initializer
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+#[instruction(movie_id: [u8; 32])]
+pub struct AddMovieReviewV2<'info> {
+    #[account(
+        init,
+        seeds = ["review".as_bytes(), movie_id.as_ref(), initializer.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + MovieAccountState::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), movie_id.as_ref()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub movie: Account<'info, Movie>,
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = ["mint".as_bytes()],
+        bump,
+        mut
+    )]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        seeds = ["reward_config".as_bytes()],
+        bump,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    #[account(
+        init_if_needed,
+        seeds = ["reviewer".as_bytes(), initializer.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + Reviewer::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
+    #[account(
+        mut,
+        seeds = ["vault".as_bytes()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = initializer,
+        associated_token::mint = mint,
+        associated_token::authority = initializer
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRewardConfig<'info> {
+    #[account(
+        mut,
+        seeds = ["reward_config".as_bytes()],
+        bump,
+        has_one = admin @ MovieReviewError::Unauthorized,
+    )]
+    pub reward_config: Account<'info, RewardConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(title:String, description:String)]
+pub struct UpdateMovieReview<'info> {
+    #[account(
+        mut,
+        seeds = [title.as_bytes(), initializer.key().as_ref()],
+        bump,
+    )]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init,
+        seeds = ["revision".as_bytes(), movie_review.origin.as_ref(), &movie_review.edit_count.to_le_bytes()],
+        bump,
+        space = DISCRIMINATOR + ReviewRevision::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub revision: Account<'info, ReviewRevision>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), title.as_bytes()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub movie: Account<'info, Movie>,
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct MovieAccountState {
-    pub reviewer: Pubkey, // 32
-    pub rating: u8, // 1
-    #[max_len(20)]
-    pub title: String, // 4 + len()
-    #[max_len(50)]
-    pub description: String, // 4 + len()
+#[derive(Accounts)]
+#[instruction(old_title: String, new_title: String)]
+pub struct MoveReview<'info> {
+    #[account(
+        mut,
+        seeds = [old_title.as_bytes(), initializer.key().as_ref()],
+        bump,
+        close = initializer
+    )]
+    pub old_review: Account<'info, MovieAccountState>,
+    #[account(
+        init,
+        seeds = [new_title.as_bytes(), initializer.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + MovieAccountState::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub new_review: Account<'info, MovieAccountState>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), old_title.as_bytes()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub old_movie: Account<'info, Movie>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), new_title.as_bytes()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub new_movie: Account<'info, Movie>,
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeMint<'info> {
+#[instruction(title: String)]
+pub struct DeleteMovieReview<'info> {
+    #[account(
+        mut,
+        seeds=[title.as_bytes(), initializer.key().as_ref()],
+        bump,
+        close=initializer
+    )]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), title.as_bytes()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub movie: Account<'info, Movie>,
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = ["vault".as_bytes()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    pub token_program: Program<'info, Token>,
     #[account(
-        init,
         seeds = ["mint".as_bytes()],
         bump,
-        payer = user,
-        mint::decimals = 6,
-        mint::authority = user,
+        mut
     )]
     pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = initializer
+    )]
+    pub token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = ["reviewer".as_bytes(), initializer.key().as_ref()],
+        bump,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
+}
+
+#[derive(Accounts)]
+#[instruction(movie_id: [u8; 32])]
+pub struct UpdateMovieReviewV2<'info> {
+    #[account(
+        mut,
+        seeds = ["review".as_bytes(), movie_id.as_ref(), initializer.key().as_ref()],
+        bump,
+    )]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init,
+        seeds = ["revision".as_bytes(), movie_review.origin.as_ref(), &movie_review.edit_count.to_le_bytes()],
+        bump,
+        space = DISCRIMINATOR + ReviewRevision::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub revision: Account<'info, ReviewRevision>,
+    #[account(
+        mut,
+        seeds = ["movie".as_bytes(), movie_id.as_ref()],
+        bump,
+    )]
+    pub movie: Account<'info, Movie>,
     #[account(mut)]
-    pub user: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+    pub initializer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(title: String, description: String)]
-pub struct AddMovieReview<'info> {
+#[instruction(movie_id: [u8; 32])]
+pub struct DeleteMovieReviewV2<'info> {
     #[account(
-        init, 
-        seeds = [title.as_bytes(), initializer.key().as_ref()],
+        mut,
+        seeds = ["review".as_bytes(), movie_id.as_ref(), initializer.key().as_ref()],
         bump,
-        space = DISCRIMINATOR + MovieAccountState::INIT_SPACE,
-        payer = initializer, 
+        close = initializer
     )]
     pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        mut,
+        seeds = ["movie".as_bytes(), movie_id.as_ref()],
+        bump,
+    )]
+    pub movie: Account<'info, Movie>,
     #[account(mut)]
     pub initializer: Signer<'info>,
     pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = ["vault".as_bytes()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
     pub token_program: Program<'info, Token>,
     #[account(
         seeds = ["mint".as_bytes()],
@@ -137,53 +1302,409 @@ pub struct AddMovieReview<'info> {
     )]
     pub mint: Account<'info, Mint>,
     #[account(
-        init_if_needed,
-        payer = initializer,
+        mut,
         associated_token::mint = mint,
         associated_token::authority = initializer
     )]
-    pub token_account: Account<'info, TokenAccount>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = ["reviewer".as_bytes(), initializer.key().as_ref()],
+        bump,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
 }
 
 #[derive(Accounts)]
-#[instruction(title:String, description:String)]
-pub struct UpdateMovieReview<'info> {
+#[instruction(title: String)]
+pub struct MigrateReview<'info> {
+    /// CHECK: a review with the legacy layout, checked and rewritten by the instruction
     #[account(
         mut,
         seeds = [title.as_bytes(), initializer.key().as_ref()],
         bump,
-        realloc = DISCRIMINATOR + MovieAccountState::INIT_SPACE,
-        realloc::payer = initializer,
-        realloc::zero = true,
+        owner = crate::ID,
     )]
-    pub movie_review: Account<'info, MovieAccountState>,
+    pub movie_review: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        seeds = ["movie".as_bytes(), title.as_bytes()],
+        bump,
+        space = DISCRIMINATOR + Movie::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub movie: Account<'info, Movie>,
+    #[account(
+        init_if_needed,
+        seeds = ["reviewer".as_bytes(), initializer.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + Reviewer::INIT_SPACE,
+        payer = initializer,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
     #[account(mut)]
     pub initializer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(title: String)]
-pub struct DeleteMovieReview<'info> {
+pub struct InitializeModeration<'info> {
+    #[account(
+        init,
+        seeds = ["moderation".as_bytes()],
+        bump,
+        space = DISCRIMINATOR + ModerationConfig::INIT_SPACE,
+        payer = admin,
+    )]
+    pub moderation_config: Account<'info, ModerationConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateModerationConfig<'info> {
     #[account(
         mut,
-        seeds=[title.as_bytes(), initializer.key().as_ref()],
+        seeds = ["moderation".as_bytes()],
         bump,
-        close=initializer
+        has_one = admin @ MovieReviewError::Unauthorized,
     )]
+    pub moderation_config: Account<'info, ModerationConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FlagReview<'info> {
+    #[account(mut)]
     pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init,
+        seeds = ["flag".as_bytes(), movie_review.origin.as_ref(), flagger.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + Flag::INIT_SPACE,
+        payer = flagger,
+    )]
+    pub flag: Account<'info, Flag>,
     #[account(mut)]
-    pub initializer: Signer<'info>,
-    pub system_program: Program<'info, System>
+    pub flagger: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateReview<'info> {
+    #[account(mut)]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        mut,
+        address = movie_review.movie_address() @ MovieReviewError::MovieDoesNotMatchReview,
+    )]
+    pub movie: Account<'info, Movie>,
+    #[account(
+        init_if_needed,
+        seeds = ["reviewer".as_bytes(), movie_review.reviewer.as_ref()],
+        bump,
+        space = DISCRIMINATOR + Reviewer::INIT_SPACE,
+        payer = moderator,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
+    #[account(
+        seeds = ["moderation".as_bytes()],
+        bump,
+        has_one = admin,
+        constraint = moderation_config.moderators.contains(&moderator.key()) @ MovieReviewError::NotModerator,
+    )]
+    pub moderation_config: Account<'info, ModerationConfig>,
+    /// CHECK: the admin of the moderation config, receives the slashed deposits
+    #[account(mut)]
+    pub admin: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = ["vault".as_bytes()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub moderator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VoteReview<'info> {
+    #[account(mut)]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init,
+        seeds = ["vote".as_bytes(), movie_review.origin.as_ref(), voter.key().as_ref()],
+        bump,
+        space = DISCRIMINATOR + Vote::INIT_SPACE,
+        payer = voter,
+    )]
+    pub vote: Account<'info, Vote>,
+    #[account(
+        init_if_needed,
+        seeds = ["reviewer".as_bytes(), movie_review.reviewer.as_ref()],
+        bump,
+        space = DISCRIMINATOR + Reviewer::INIT_SPACE,
+        payer = voter,
+    )]
+    pub reviewer_profile: Account<'info, Reviewer>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddComment<'info> {
+    #[account(mut)]
+    pub movie_review: Account<'info, MovieAccountState>,
+    #[account(
+        init,
+        seeds = ["comment".as_bytes(), movie_review.origin.as_ref(), &movie_review.comment_count.to_le_bytes()],
+        bump,
+        space = DISCRIMINATOR + Comment::INIT_SPACE,
+        payer = commenter,
+    )]
+    pub comment: Account<'info, Comment>,
+    #[account(mut)]
+    pub commenter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EditComment<'info> {
+    #[account(
+        mut,
+        seeds = ["comment".as_bytes(), comment.review.as_ref(), &comment.index.to_le_bytes()],
+        bump,
+        has_one = commenter @ MovieReviewError::NotCommentAuthor,
+    )]
+    pub comment: Account<'info, Comment>,
+    pub commenter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteComment<'info> {
+    #[account(
+        mut,
+        seeds = ["comment".as_bytes(), comment.review.as_ref(), &comment.index.to_le_bytes()],
+        bump,
+        has_one = commenter @ MovieReviewError::NotCommentAuthor,
+        close = commenter
+    )]
+    pub comment: Account<'info, Comment>,
+    #[account(mut)]
+    pub commenter: Signer<'info>,
+}
+
+// The movie id is zero on the reviews keyed by title
+#[event]
+pub struct ReviewAdded {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub title: String,
+    pub movie_id: [u8; 32],
+    pub rating: u8,
+    pub description: String,
+    pub deposit: u64,
+}
+
+impl ReviewAdded {
+    fn new(review: &Account<MovieAccountState>) -> Self {
+        Self {
+            review: review.key(),
+            reviewer: review.reviewer,
+            title: review.title.clone(),
+            movie_id: review.movie_id,
+            rating: review.rating,
+            description: review.description.clone(),
+            deposit: review.deposit,
+        }
+    }
+}
+
+// The old values are the ones before the update
+#[event]
+pub struct ReviewUpdated {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub title: String,
+    pub movie_id: [u8; 32],
+    pub old_rating: u8,
+    pub rating: u8,
+    pub old_description: String,
+    pub description: String,
+}
+
+impl ReviewUpdated {
+    fn new(review: &Account<MovieAccountState>, description: &str, rating: u8) -> Self {
+        Self {
+            review: review.key(),
+            reviewer: review.reviewer,
+            title: review.title.clone(),
+            movie_id: review.movie_id,
+            old_rating: review.rating,
+            rating,
+            old_description: review.description.clone(),
+            description: description.to_string(),
+        }
+    }
+}
+
+#[event]
+pub struct ReviewDeleted {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub title: String,
+    pub movie_id: [u8; 32],
+    pub rating: u8,
+    pub refunded_deposit: u64,
+    pub burned_reward: u64,
+    pub owed_reward: u64, // added to the reward debt when the reward couldn't be burned
+}
+
+impl ReviewDeleted {
+    fn new(review: &Account<MovieAccountState>, refunded_deposit: u64, burned_reward: u64, owed_reward: u64) -> Self {
+        Self {
+            review: review.key(),
+            reviewer: review.reviewer,
+            title: review.title.clone(),
+            movie_id: review.movie_id,
+            rating: review.rating,
+            refunded_deposit,
+            burned_reward,
+            owed_reward,
+        }
+    }
+}
+
+// The amount is in base units of the mint, after the reputation bonus and the debt of slashed rewards
+#[event]
+pub struct RewardMinted {
+    pub review: Pubkey,
+    pub reviewer: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardConfigUpdated {
+    pub admin: Pubkey,
+    pub reward_amount: u64,
+    pub review_deposit: u64,
+    pub review_cooldown: i64,
+    pub lock_period: i64,
+}
+
+impl RewardConfigUpdated {
+    fn new(reward_config: &RewardConfig) -> Self {
+        Self {
+            admin: reward_config.admin,
+            reward_amount: reward_config.reward_amount,
+            review_deposit: reward_config.review_deposit,
+            review_cooldown: reward_config.review_cooldown,
+            lock_period: reward_config.lock_period,
+        }
+    }
+}
+
+#[event]
+pub struct ModerationConfigUpdated {
+    pub admin: Pubkey,
+    pub moderators: Vec<Pubkey>,
+}
+
+impl ModerationConfigUpdated {
+    fn new(moderation_config: &ModerationConfig) -> Self {
+        Self {
+            admin: moderation_config.admin,
+            moderators: moderation_config.moderators.clone(),
+        }
+    }
+}
+
+#[event]
+pub struct ReviewMigrated {
+    pub review: Pubkey,
+    pub origin: Pubkey,
+    pub reviewer: Pubkey,
+    pub title: String,
+}
+
+#[event]
+pub struct ReviewMoved {
+    pub review: Pubkey,
+    pub from: Pubkey,
+    pub origin: Pubkey,
+    pub reviewer: Pubkey,
+    pub old_title: String,
+    pub title: String,
+}
+
+#[event]
+pub struct ReviewFlagged {
+    pub review: Pubkey,
+    pub flagger: Pubkey,
+    pub flag_count: u64,
+}
+
+#[event]
+pub struct ReviewModerated {
+    pub review: Pubkey,
+    pub moderator: Pubkey,
+    pub status: ReviewStatus,
+    pub slashed_deposit: u64,
+    pub slashed_reward: u64,
+}
+
+#[event]
+pub struct ReviewVoted {
+    pub review: Pubkey,
+    pub voter: Pubkey,
+    pub reviewer: Pubkey,
+    pub helpful: bool,
+    pub reputation: i64,
+}
+
+#[event]
+pub struct CommentAdded {
+    pub review: Pubkey,
+    pub comment: Pubkey,
+    pub commenter: Pubkey,
+    pub index: u64,
+    pub parent: Option<u64>,
+}
+
+#[event]
+pub struct CommentEdited {
+    pub review: Pubkey,
+    pub comment: Pubkey,
+    pub commenter: Pubkey,
+    pub index: u64,
+}
+
+#[event]
+pub struct CommentDeleted {
+    pub review: Pubkey,
+    pub comment: Pubkey,
+    pub commenter: Pubkey,
+    pub index: u64,
 }
 
 const DISCRIMINATOR: usize = 8;
 
 const MIN_RATING: u8 = 1;
 const MAX_RATING: u8 = 5;
-const MAX_TITLE_LENGTH: usize = 20;
+const MAX_TITLE_LENGTH: usize = 20; // in bytes, the title is a seed of the reviews keyed by title
+const RESERVED_TITLE_PREFIXES: [&str; 7] = ["comment", "flag", "movie", "origin", "review", "revision", "vote"];
+const MAX_DISPLAY_TITLE_CHARACTERS: usize = 64; // in characters, on the reviews keyed by movie id
 const MAX_DESCRIPTION_LENGTH: usize = 50;
+const LEGACY_REVIEW_SPACE: usize = DISCRIMINATOR + 32 + 1 + 4 + 20 + 4 + 50; // the reviews of the first deployment
+const MAX_COMMENT_LENGTH: usize = 200;
+const MINT_DECIMALS: u8 = 6;
+const MAX_MODERATORS: usize = 10;
+const MAX_REPUTATION_BONUS: i64 = 40; // up to 5 times the reward
+const REPUTATION_BONUS_DENOMINATOR: u128 = 10;
 
 #[error_code]
 pub enum MovieReviewError {
@@ -193,4 +1714,47 @@ pub enum MovieReviewError {
     TitleTooLong,
     #[msg("Description must be less than 50 characters")]
     DescriptionTooLong,
+    #[msg("Reward amount must be greater than 0")]
+    InvalidRewardAmount,
+    #[msg("Only the admin of the config can change it")]
+    Unauthorized,
+    #[msg("The title starts with a seed of another account")]
+    ReservedTitle,
+    #[msg("Title must have at most 64 characters")]
+    DisplayTitleTooLong,
+    #[msg("The account isn't a review with the layout of the first deployment")]
+    NotLegacyReview,
+    #[msg("Comment must have between 1 and 200 characters")]
+    InvalidCommentLength,
+    #[msg("Replies must point to an earlier comment of the same review")]
+    InvalidParentComment,
+    #[msg("Only the author of the comment can change it")]
+    NotCommentAuthor,
+    #[msg("Reviewers can't vote on their own reviews")]
+    CannotVoteOwnReview,
+    #[msg("The reward doesn't fit in a token amount")]
+    RewardOverflow,
+    #[msg("The deposit must cover the rent of the vault, and the periods can't be negative")]
+    InvalidReviewRules,
+    #[msg("Wait for the cooldown before adding another review")]
+    ReviewCooldown,
+    #[msg("The moderator set is full")]
+    TooManyModerators,
+    #[msg("The moderator was already added")]
+    ModeratorAlreadyAdded,
+    #[msg("The moderator isn't in the moderator set")]
+    ModeratorNotFound,
+    #[msg("Only the moderators can moderate reviews")]
+    NotModerator,
+    #[msg("The review was removed by a moderator")]
+    ReviewRemoved,
+    #[msg("The review is hidden or removed")]
+    ReviewNotActive,
+    #[msg("The movie account isn't the movie of the review")]
+    MovieDoesNotMatchReview,
+    #[msg("The movie id can't be zero")]
+    InvalidMovieId,
+    #[msg("The review has flags waiting for a moderator")]
+    ReviewPendingModeration,
 }
+
@@ -1,5 +1,12 @@
 // Here we export some useful types and functions for interacting with the Anchor program.
-import { AnchorProvider, Program } from "@coral-xyz/anchor";
+import {
+  AnchorProvider,
+  BN,
+  BorshCoder,
+  EventParser,
+  Program,
+  utils,
+} from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
 import MovieReviewProgramIDL from "../target/idl/movie_review_program.json";
 import type { MovieReviewProgram } from "../target/types/movie_review_program";
@@ -17,6 +24,213 @@ export function getMovieReviewProgram(provider: AnchorProvider) {
   return new Program(MovieReviewProgramIDL as MovieReviewProgram, provider);
 }
 
+// The aggregate of the reviews of a movie, see the Movie account
+export type Movie = {
+  title: string;
+  reviewCount: BN;
+  ratingSum: BN;
+  ratingHistogram: BN[]; // the number of reviews with each rating, from 1 to 5
+  movieId: number[]; // zero on the movies keyed by title
+};
+
+// A previous version of a review, see the ReviewRevision account
+export type ReviewRevision = {
+  review: PublicKey; // the origin of the review
+  index: BN;
+  rating: number;
+  description: string;
+  slot: BN;
+};
+
+// Clients only list the active reviews, and only those are counted on the movie
+export type ReviewStatus = { active: {} } | { hidden: {} } | { removed: {} };
+
+export function isActive(review: { status: ReviewStatus }): boolean {
+  return "active" in review.status;
+}
+
+// The votes received by a reviewer on all their reviews
+export type Reviewer = {
+  reviewer: PublicKey;
+  helpfulVotes: BN;
+  unhelpfulVotes: BN;
+  reviewCount: BN; // the reviews created, the index of the origin of the next one
+};
+
+// A comment on a review, or a reply to another comment of the same review
+export type Comment = {
+  review: PublicKey;
+  commenter: PublicKey;
+  index: BN;
+  parent: BN | null; // the index of the comment it replies to
+  content: string;
+};
+
+// Titles that only differ by case, spacing or unicode composition are the same movie
+export function normalizeTitle(title: string): string {
+  return title.normalize("NFC").trim().replace(/\s+/g, " ").toLowerCase();
+}
+
+// The movie id of add_movie_review_v2 for a title, the sha256 of the normalized title
+export function movieIdFromTitle(title: string): number[] {
+  return [
+    ...Buffer.from(utils.sha256.hash(`title:${normalizeTitle(title)}`), "hex"),
+  ];
+}
+
+// The movie id of add_movie_review_v2 for an external id, eg: movieIdFromExternalId("imdb", "tt0137523")
+export function movieIdFromExternalId(
+  source: "imdb" | "tmdb",
+  id: string
+): number[] {
+  return [...Buffer.from(utils.sha256.hash(`${source}:${id.trim()}`), "hex")];
+}
+
+// Whether a review or a movie is keyed by movie id instead of title
+export function hasMovieId(account: { movieId: number[] }): boolean {
+  return account.movieId.some((byte) => byte !== 0);
+}
+
+// The address of the Movie account of a title
+export function getMoviePda(title: string, programId = MOVIE_REVIEW_PROGRAM_ID) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("movie"), Buffer.from(title)],
+    programId
+  )[0];
+}
+
+// The address of the Movie account of a movie id
+export function getMoviePdaById(
+  movieId: number[],
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("movie"), Buffer.from(movieId)],
+    programId
+  )[0];
+}
+
+// The address of the review of a movie id by a reviewer
+export function getMovieReviewPdaById(
+  movieId: number[],
+  reviewer: PublicKey,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("review"), Buffer.from(movieId), reviewer.toBuffer()],
+    programId
+  )[0];
+}
+
+// The origin of a review, the index is the review count of the reviewer when it was added
+export function getOriginPda(
+  reviewer: PublicKey,
+  index: BN,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("origin"), reviewer.toBuffer(), index.toArrayLike(Buffer, "le", 8)],
+    programId
+  )[0];
+}
+
+// The address of a comment, the index is the comment count of the review when it was added
+export function getCommentPda(
+  review: PublicKey,
+  index: BN,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("comment"), review.toBuffer(), index.toArrayLike(Buffer, "le", 8)],
+    programId
+  )[0];
+}
+
+// The address of the system account holding the deposits of the reviews
+export function getVaultPda(programId = MOVIE_REVIEW_PROGRAM_ID) {
+  return PublicKey.findProgramAddressSync([Buffer.from("vault")], programId)[0];
+}
+
+// The address of a previous version of a review, the index goes from 0 to the edit count
+export function getRevisionPda(
+  origin: PublicKey,
+  index: BN,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("revision"), origin.toBuffer(), index.toArrayLike(Buffer, "le", 8)],
+    programId
+  )[0];
+}
+
+// The address of the admin and the moderator set
+export function getModerationConfigPda(programId = MOVIE_REVIEW_PROGRAM_ID) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("moderation")],
+    programId
+  )[0];
+}
+
+// The address of the flag of a wallet on a review
+export function getFlagPda(
+  review: PublicKey,
+  flagger: PublicKey,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("flag"), review.toBuffer(), flagger.toBuffer()],
+    programId
+  )[0];
+}
+
+// The address of the profile of a reviewer
+export function getReviewerPda(
+  reviewer: PublicKey,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("reviewer"), reviewer.toBuffer()],
+    programId
+  )[0];
+}
+
+// The address of the vote of a wallet on a review
+export function getVotePda(
+  review: PublicKey,
+  voter: PublicKey,
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("vote"), review.toBuffer(), voter.toBuffer()],
+    programId
+  )[0];
+}
+
+// The helpful votes minus the unhelpful ones, every point adds 10% to the reward, up to 5 times
+export function reputation(reviewer: Reviewer): number {
+  return reviewer.helpfulVotes.toNumber() - reviewer.unhelpfulVotes.toNumber();
+}
+
+// The events emitted by the program in the logs of a transaction, eg: reviewAdded, rewardMinted
+export function parseMovieReviewEvents(
+  logs: string[],
+  programId = MOVIE_REVIEW_PROGRAM_ID
+) {
+  const parser = new EventParser(
+    programId,
+    new BorshCoder(MovieReviewProgramIDL as MovieReviewProgram)
+  );
+  return [...parser.parseLogs(logs)];
+}
+
+// The average rating of a movie, null when it has no reviews
+export function averageRating(movie: Movie): number | null {
+  if (movie.reviewCount.isZero()) {
+    return null;
+  }
+  return movie.ratingSum.toNumber() / movie.reviewCount.toNumber();
+}
+
 // This is a helper function to get the program ID for the AnchorCounter2 program depending on the cluster.
 export function getMovieReviewProgramId(cluster: Cluster) {
   switch (cluster) {
@@ -2,7 +2,24 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { MovieReviewProgram } from "../target/types/movie_review_program";
 import { expect } from "chai";
-import { getAssociatedTokenAddress, getAccount } from "@solana/spl-token";
+import { readFileSync } from "fs";
+import {
+  getAssociatedTokenAddress,
+  getAccount,
+  getMint,
+} from "@solana/spl-token";
+import {
+  getCommentPda,
+  getMoviePda,
+  getMoviePdaById,
+  getMovieReviewPdaById,
+  getOriginPda,
+  getReviewerPda,
+  getRevisionPda,
+  getVotePda,
+  movieIdFromTitle,
+  parseMovieReviewEvents,
+} from "../src/movie-review-program-exports";
 
 describe("movie-review-program", () => {
   // Configure the client to use the local cluster.
@@ -23,13 +40,75 @@ describe("movie-review-program", () => {
     program.programId
   );
 
+  // The first review of the wallet, comments and revisions are keyed by it
+  const movieReviewOrigin = getOriginPda(
+    provider.wallet.publicKey,
+    new anchor.BN(0),
+    program.programId
+  );
+
+  const [moviePda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("movie"), Buffer.from(movieReview.title)],
+    program.programId
+  );
+
   const [tokenMintPda] = anchor.web3.PublicKey.findProgramAddressSync(
     [Buffer.from("mint")],
     program.programId
   );
 
+  const [rewardConfigPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_config")],
+    program.programId
+  );
+
+  // 10 tokens, the mint has 6 decimals
+  const rewardAmount = new anchor.BN(10 * 10 ** 6);
+
+  // The validator starts with the mint of the first deployment (see Anchor.toml), its creator is the mint authority
+  const legacyMintAuthority = anchor.web3.Keypair.fromSecretKey(
+    Uint8Array.from(
+      JSON.parse(
+        readFileSync(`${__dirname}/fixtures/legacy-mint-authority.json`, "utf8")
+      )
+    )
+  );
+
   it("Initializes the reward token", async () => {
-    const tx = await program.methods.initializeTokenMint().rpc();
+    try {
+      await program.methods.initializeTokenMint(rewardAmount).rpc();
+      expect.fail("The mint was created again");
+    } catch (error) {
+      expect(error.message).to.include("already in use");
+    }
+
+    // The program can't mint the rewards until the authority hands the mint over
+    try {
+      await program.methods.initializeRewardConfig(rewardAmount).rpc();
+      expect.fail("The rewards were set up on a mint of another authority");
+    } catch (error) {
+      expect(error.message).to.include("ConstraintMintMintAuthority");
+    }
+
+    await program.methods
+      .migrateMintAuthority()
+      .accounts({ authority: legacyMintAuthority.publicKey })
+      .signers([legacyMintAuthority])
+      .rpc();
+    const mint = await getMint(provider.connection, tokenMintPda);
+    expect(mint.mintAuthority.toBase58()).to.equal(tokenMintPda.toBase58());
+
+    await program.methods.initializeRewardConfig(rewardAmount).rpc();
+
+    const rewardConfig = await program.account.rewardConfig.fetch(
+      rewardConfigPda
+    );
+    expect(rewardConfig.admin.toBase58()).to.equal(
+      provider.wallet.publicKey.toBase58()
+    );
+    expect(rewardConfig.rewardAmount.toString()).to.equal(
+      rewardAmount.toString()
+    );
   });
 
   it("Movie review is added", async () => {
@@ -49,6 +128,666 @@ describe("movie-review-program", () => {
     expect(movieReviewAccount.title === movieReview.title);
     expect(movieReviewAccount.description === movieReview.description);
     expect(movieReviewAccount.rating === movieReview.rating);
+
+    const tokenAccount = await getAccount(
+      provider.connection,
+      await getAssociatedTokenAddress(tokenMintPda, provider.wallet.publicKey)
+    );
+    expect(tokenAccount.amount.toString()).to.equal(rewardAmount.toString());
+
+    const movie = await program.account.movie.fetch(moviePda);
+    expect(movie.reviewCount.toNumber()).to.equal(1);
+    expect(movie.ratingSum.toNumber()).to.equal(movieReview.rating);
+    expect(movie.ratingHistogram.map((count) => count.toNumber())).to.eql([
+      0, 0, 0, 1, 0,
+    ]);
+  });
+
+  it("Rejects titles that start with the seed of another account", async () => {
+    // ["reviewer", wallet] is the address of the Reviewer profile of the wallet
+    try {
+      await program.methods.addMovieReview("reviewer", "Squatted", 1).rpc();
+      expect.fail("The review was added at the address of the profile");
+    } catch (error) {
+      expect(error.message).to.include("ReservedTitle");
+    }
+    try {
+      await program.methods.moveReview(movieReview.title, "movie").rpc();
+      expect.fail("The review was moved to the address of a movie");
+    } catch (error) {
+      expect(error.message).to.include("ReservedTitle");
+    }
+  });
+
+  it("Rewards a reviewer that didn't create the mint", async () => {
+    const reviewer = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        reviewer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+
+    await program.methods
+      .addMovieReview("The Matrix", "Red pill", 5)
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+
+    const tokenAccount = await getAccount(
+      provider.connection,
+      await getAssociatedTokenAddress(tokenMintPda, reviewer.publicKey)
+    );
+    expect(tokenAccount.amount.toString()).to.equal(rewardAmount.toString());
+  });
+
+  it("Votes on reviews build the reputation of the reviewer", async () => {
+    const reviewer = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        reviewer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+
+    await program.methods
+      .addMovieReview("Heat", "Diner scene", 5)
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+    const [reviewPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("Heat"), reviewer.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .voteReview(true)
+      .accounts({ movieReview: reviewPda })
+      .rpc();
+
+    const review = await program.account.movieAccountState.fetch(reviewPda);
+    expect(review.helpfulVotes.toNumber()).to.equal(1);
+    expect(review.unhelpfulVotes.toNumber()).to.equal(0);
+    const vote = await program.account.vote.fetch(
+      getVotePda(review.origin, provider.wallet.publicKey, program.programId)
+    );
+    expect(vote.helpful).to.equal(true);
+    const profile = await program.account.reviewer.fetch(
+      getReviewerPda(reviewer.publicKey, program.programId)
+    );
+    expect(profile.helpfulVotes.toNumber()).to.equal(1);
+
+    // One vote per wallet, and not on their own reviews
+    try {
+      await program.methods
+        .voteReview(false)
+        .accounts({ movieReview: reviewPda })
+        .rpc();
+      expect.fail("The wallet voted twice on the same review");
+    } catch (error) {
+      expect(error.message).to.include("already in use");
+    }
+    try {
+      await program.methods
+        .voteReview(true)
+        .accounts({ movieReview: reviewPda, voter: reviewer.publicKey })
+        .signers([reviewer])
+        .rpc();
+      expect.fail("The reviewer voted on their own review");
+    } catch (error) {
+      expect(error.message).to.include("CannotVoteOwnReview");
+    }
+
+    // A point of reputation adds 10% to the next reward
+    await program.methods
+      .addMovieReview("Collateral", "Cab ride", 4)
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+    const tokenAccount = await getAccount(
+      provider.connection,
+      await getAssociatedTokenAddress(tokenMintPda, reviewer.publicKey)
+    );
+    expect(tokenAccount.amount.toString()).to.equal(
+      rewardAmount.muln(21).divn(10).toString()
+    );
+  });
+
+  it("Comments and replies on a review", async () => {
+    const commenter = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        commenter.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+
+    await program.methods
+      .addComment("First rule?", null)
+      .accounts({ movieReview: movieReviewPda, commenter: commenter.publicKey })
+      .signers([commenter])
+      .rpc();
+    await program.methods
+      .addComment("You don't talk about it", new anchor.BN(0))
+      .accounts({ movieReview: movieReviewPda })
+      .rpc();
+
+    const review = await program.account.movieAccountState.fetch(
+      movieReviewPda
+    );
+    expect(review.commentCount.toNumber()).to.equal(2);
+
+    expect(review.origin.toBase58()).to.equal(movieReviewOrigin.toBase58());
+
+    const commentPda = getCommentPda(
+      movieReviewOrigin,
+      new anchor.BN(0),
+      program.programId
+    );
+    const replyPda = getCommentPda(
+      movieReviewOrigin,
+      new anchor.BN(1),
+      program.programId
+    );
+    const reply = await program.account.comment.fetch(replyPda);
+    expect(reply.parent.toNumber()).to.equal(0);
+    expect(reply.commenter.toBase58()).to.equal(
+      provider.wallet.publicKey.toBase58()
+    );
+
+    // Replies point to an earlier comment
+    try {
+      await program.methods
+        .addComment("Nobody", new anchor.BN(5))
+        .accounts({ movieReview: movieReviewPda })
+        .rpc();
+      expect.fail("The reply was added to a comment that doesn't exist");
+    } catch (error) {
+      expect(error.message).to.include("InvalidParentComment");
+    }
+
+    // Only the author edits or deletes a comment
+    try {
+      await program.methods
+        .editComment("Edited")
+        .accountsPartial({ comment: commentPda })
+        .rpc();
+      expect.fail("The comment was edited by someone else");
+    } catch (error) {
+      expect(error.message).to.include("NotCommentAuthor");
+    }
+
+    await program.methods
+      .editComment("What is the first rule?")
+      .accountsPartial({ comment: commentPda, commenter: commenter.publicKey })
+      .signers([commenter])
+      .rpc();
+    const comment = await program.account.comment.fetch(commentPda);
+    expect(comment.content).to.equal("What is the first rule?");
+
+    await program.methods
+      .deleteComment()
+      .accountsPartial({ comment: commentPda, commenter: commenter.publicKey })
+      .signers([commenter])
+      .rpc();
+    expect(await program.account.comment.fetchNullable(commentPda)).to.equal(
+      null
+    );
+
+    // The reply stays, and the next comment doesn't reuse the deleted address
+    expect(await program.account.comment.fetchNullable(replyPda)).to.not.equal(
+      null
+    );
+    await program.methods
+      .addComment("Second rule?", null)
+      .accounts({ movieReview: movieReviewPda })
+      .rpc();
+    const next = await program.account.comment.fetch(
+      getCommentPda(movieReviewOrigin, new anchor.BN(2), program.programId)
+    );
+    expect(next.index.toNumber()).to.equal(2);
+  });
+
+  it("Reviews by movie id with a long unicode title", async () => {
+    // Longer than a 32 bytes seed, and two spellings of the same movie
+    const title = "Amélie: O Fabuloso Destino de Amélie Poulain";
+    const movieId = movieIdFromTitle(title);
+    expect(movieIdFromTitle("  amélie:  o fabuloso destino de AMÉLIE poulain ")).to.eql(
+      movieId
+    );
+
+    await program.methods
+      .addMovieReviewV2(movieId, title, "Charming", 4)
+      .rpc();
+
+    const reviewPda = getMovieReviewPdaById(
+      movieId,
+      provider.wallet.publicKey,
+      program.programId
+    );
+    const review = await program.account.movieAccountState.fetch(reviewPda);
+    expect(review.title).to.equal(title);
+    expect(review.movieId).to.eql(movieId);
+
+    await program.methods.updateMovieReviewV2(movieId, "Lovely", 5).rpc();
+    const movie = await program.account.movie.fetch(
+      getMoviePdaById(movieId, program.programId)
+    );
+    expect(movie.title).to.equal(title);
+    expect(movie.reviewCount.toNumber()).to.equal(1);
+    expect(movie.ratingSum.toNumber()).to.equal(5);
+
+    // The title is display data, limited in characters instead of bytes
+    try {
+      await program.methods
+        .addMovieReviewV2(movieIdFromTitle("é".repeat(65)), "é".repeat(65), "", 3)
+        .rpc();
+      expect.fail("The review was added with a title of 65 characters");
+    } catch (error) {
+      expect(error.message).to.include("DisplayTitleTooLong");
+    }
+
+    // The zero movie id is the one of the reviews keyed by title
+    try {
+      await program.methods
+        .addMovieReviewV2(Array(32).fill(0), "Zero", "", 3)
+        .rpc();
+      expect.fail("The review was added with the zero movie id");
+    } catch (error) {
+      expect(error.message).to.include("InvalidMovieId");
+    }
+
+    await program.methods.deleteMovieReviewV2(movieId).rpc();
+    const deleted = await program.account.movieAccountState.fetchNullable(
+      reviewPda
+    );
+    expect(deleted).to.equal(null);
+  });
+
+  it("Holds a deposit and burns the reward of reviews deleted early", async () => {
+    const reviewer = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        reviewer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    const [vaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault")],
+      program.programId
+    );
+    const deposit = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100);
+    const hour = new anchor.BN(3600);
+
+    // The deposit must keep the vault rent exempt
+    try {
+      await program.methods
+        .updateReviewRules(new anchor.BN(1), hour, hour)
+        .rpc();
+      expect.fail("The deposit was lower than the rent of the vault");
+    } catch (error) {
+      expect(error.message).to.include("InvalidReviewRules");
+    }
+    await program.methods.updateReviewRules(deposit, hour, hour).rpc();
+
+    await program.methods
+      .addMovieReview("Alien", "In space", 5)
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+    expect(await provider.connection.getBalance(vaultPda)).to.equal(
+      deposit.toNumber()
+    );
+
+    // One review per hour
+    try {
+      await program.methods
+        .addMovieReview("Aliens", "Game over", 4)
+        .accounts({ initializer: reviewer.publicKey })
+        .signers([reviewer])
+        .rpc();
+      expect.fail("The review was added during the cooldown");
+    } catch (error) {
+      expect(error.message).to.include("ReviewCooldown");
+    }
+
+    // Deleted before the lock period, the deposit is refunded and the reward burned
+    await program.methods
+      .deleteMovieReview("Alien")
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+    expect(await provider.connection.getBalance(vaultPda)).to.equal(0);
+    const tokenAccount = await getAccount(
+      provider.connection,
+      await getAssociatedTokenAddress(tokenMintPda, reviewer.publicKey)
+    );
+    expect(tokenAccount.amount.toString()).to.equal("0");
+
+    // Without the token account, the reward becomes a debt and the deposit is still refunded
+    const closer = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        closer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    await program.methods
+      .addMovieReview("Alien 3", "Bald", 2)
+      .accounts({ initializer: closer.publicKey })
+      .signers([closer])
+      .rpc();
+    await program.methods
+      .deleteMovieReview("Alien 3")
+      .accountsPartial({ initializer: closer.publicKey, tokenAccount: null })
+      .signers([closer])
+      .rpc();
+    expect(await provider.connection.getBalance(vaultPda)).to.equal(0);
+    const profile = await program.account.reviewer.fetch(
+      getReviewerPda(closer.publicKey, program.programId)
+    );
+    expect(profile.rewardDebt.toString()).to.equal(rewardAmount.toString());
+
+    const zero = new anchor.BN(0);
+    await program.methods.updateReviewRules(zero, zero, zero).rpc();
+  });
+
+  it("Moderators hide, restore and remove reviews", async () => {
+    const reviewer = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        reviewer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    await program.methods
+      .addMovieReview("Ronin", "Car chase", 2)
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+    const [reviewPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("Ronin"), reviewer.publicKey.toBuffer()],
+      program.programId
+    );
+    const movie = getMoviePda("Ronin", program.programId);
+    const admin = provider.wallet.publicKey;
+
+    await program.methods.initializeModeration().rpc();
+
+    // Only the moderators moderate
+    try {
+      await program.methods
+        .moderateReview({ hidden: {} }, false)
+        .accountsPartial({ movieReview: reviewPda, movie, admin })
+        .rpc();
+      expect.fail("The review was moderated by someone else");
+    } catch (error) {
+      expect(error.message).to.include("NotModerator");
+    }
+    await program.methods.addModerator(admin).rpc();
+
+    // One flag per wallet
+    await program.methods.flagReview().accounts({ movieReview: reviewPda }).rpc();
+    try {
+      await program.methods
+        .flagReview()
+        .accounts({ movieReview: reviewPda })
+        .rpc();
+      expect.fail("The wallet flagged the review twice");
+    } catch (error) {
+      expect(error.message).to.include("already in use");
+    }
+
+    // The reviewer can't take the deposit back before a moderator looks at the flags
+    try {
+      await program.methods
+        .deleteMovieReview("Ronin")
+        .accounts({ initializer: reviewer.publicKey })
+        .signers([reviewer])
+        .rpc();
+      expect.fail("The flagged review was deleted");
+    } catch (error) {
+      expect(error.message).to.include("ReviewPendingModeration");
+    }
+
+    // A hidden review isn't counted on the movie and can't be voted on
+    await program.methods
+      .moderateReview({ hidden: {} }, false)
+      .accountsPartial({ movieReview: reviewPda, movie, admin })
+      .rpc();
+    let review = await program.account.movieAccountState.fetch(reviewPda);
+    expect(review.status).to.eql({ hidden: {} });
+    expect(review.flagCount.toNumber()).to.equal(1);
+    expect(review.pendingFlags.toNumber()).to.equal(0);
+    expect(
+      (await program.account.movie.fetch(movie)).reviewCount.toNumber()
+    ).to.equal(0);
+    try {
+      await program.methods
+        .voteReview(true)
+        .accounts({ movieReview: reviewPda })
+        .rpc();
+      expect.fail("The hidden review was voted on");
+    } catch (error) {
+      expect(error.message).to.include("ReviewNotActive");
+    }
+
+    await program.methods
+      .moderateReview({ active: {} }, false)
+      .accountsPartial({ movieReview: reviewPda, movie, admin })
+      .rpc();
+    expect(
+      (await program.account.movie.fetch(movie)).reviewCount.toNumber()
+    ).to.equal(1);
+
+    // Removing with a slash takes the reward back from the next review
+    await program.methods
+      .moderateReview({ removed: {} }, true)
+      .accountsPartial({ movieReview: reviewPda, movie, admin })
+      .rpc();
+    review = await program.account.movieAccountState.fetch(reviewPda);
+    expect(review.status).to.eql({ removed: {} });
+    expect(review.reward.toNumber()).to.equal(0);
+    try {
+      await program.methods
+        .updateMovieReview("Ronin", "Edited", 5)
+        .accounts({ initializer: reviewer.publicKey })
+        .signers([reviewer])
+        .rpc();
+      expect.fail("The removed review was updated");
+    } catch (error) {
+      expect(error.message).to.include("ReviewRemoved");
+    }
+    try {
+      await program.methods
+        .flagReview()
+        .accounts({ movieReview: reviewPda, flagger: reviewer.publicKey })
+        .signers([reviewer])
+        .rpc();
+      expect.fail("The removed review was flagged");
+    } catch (error) {
+      expect(error.message).to.include("ReviewRemoved");
+    }
+
+    await program.methods
+      .addMovieReview("Ronin II", "No", 1)
+      .accounts({ initializer: reviewer.publicKey })
+      .signers([reviewer])
+      .rpc();
+    const tokenAccount = await getAccount(
+      provider.connection,
+      await getAssociatedTokenAddress(tokenMintPda, reviewer.publicKey)
+    );
+    expect(tokenAccount.amount.toString()).to.equal(rewardAmount.toString());
+
+    await program.methods.removeModerator(admin).rpc();
+  });
+
+  it("Renames a review keeping its history", async () => {
+    await program.methods.addMovieReview("Se7en", "Whats in the box", 5).rpc();
+    const [oldPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("Se7en"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const [newPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("Seven"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .addComment("Spoiler!", null)
+      .accounts({ movieReview: oldPda })
+      .rpc();
+    const { origin } = await program.account.movieAccountState.fetch(oldPda);
+
+    // The new title is validated like on add_movie_review
+    try {
+      await program.methods
+        .moveReview("Se7en", "Seven, the one with the box")
+        .rpc();
+      expect.fail("The review was moved to a title of 27 bytes");
+    } catch (error) {
+      expect(error.message).to.include("TitleTooLong");
+    }
+
+    await program.methods.moveReview("Se7en", "Seven").rpc();
+    expect(
+      await program.account.movieAccountState.fetchNullable(oldPda)
+    ).to.equal(null);
+
+    const review = await program.account.movieAccountState.fetch(newPda);
+    expect(review.title).to.equal("Seven");
+    expect(review.origin.toBase58()).to.equal(origin.toBase58());
+    expect(review.commentCount.toNumber()).to.equal(1);
+
+    // The comments are still keyed by the origin of the review
+    await program.methods
+      .addComment("Still here", new anchor.BN(0))
+      .accounts({ movieReview: newPda })
+      .rpc();
+    const reply = await program.account.comment.fetch(
+      getCommentPda(origin, new anchor.BN(1), program.programId)
+    );
+    expect(reply.review.toBase58()).to.equal(origin.toBase58());
+
+    // The rating moves to the new movie
+    const oldMovie = await program.account.movie.fetch(
+      getMoviePda("Se7en", program.programId)
+    );
+    const newMovie = await program.account.movie.fetch(
+      getMoviePda("Seven", program.programId)
+    );
+    expect(oldMovie.reviewCount.toNumber()).to.equal(0);
+    expect(newMovie.reviewCount.toNumber()).to.equal(1);
+    expect(newMovie.ratingSum.toNumber()).to.equal(5);
+
+    // A new review at the old address has its own origin, it doesn't share the comments of the moved one
+    await program.methods.addMovieReview("Se7en", "Gluttony", 4).rpc();
+    const readded = await program.account.movieAccountState.fetch(oldPda);
+    expect(readded.origin.toBase58()).to.not.equal(origin.toBase58());
+    await program.methods
+      .addComment("Fresh start", null)
+      .accounts({ movieReview: oldPda })
+      .rpc();
+    const fresh = await program.account.comment.fetch(
+      getCommentPda(readded.origin, new anchor.BN(0), program.programId)
+    );
+    expect(fresh.content).to.equal("Fresh start");
+  });
+
+  it("Moves a hidden review to a movie without reviews", async () => {
+    await program.methods.addMovieReview("Heat", "Diner scene", 5).rpc();
+    const [reviewPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("Heat"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const admin = provider.wallet.publicKey;
+    await program.methods.addModerator(admin).rpc();
+    await program.methods
+      .moderateReview({ hidden: {} }, false)
+      .accountsPartial({
+        movieReview: reviewPda,
+        movie: getMoviePda("Heat", program.programId),
+        admin,
+      })
+      .rpc();
+
+    // The hidden review isn't counted on either movie, but the new one still gets its title
+    await program.methods.moveReview("Heat", "Heat 1995").rpc();
+    const newMovie = await program.account.movie.fetch(
+      getMoviePda("Heat 1995", program.programId)
+    );
+    expect(newMovie.title).to.equal("Heat 1995");
+    expect(newMovie.reviewCount.toNumber()).to.equal(0);
+    const oldMovie = await program.account.movie.fetch(
+      getMoviePda("Heat", program.programId)
+    );
+    expect(oldMovie.reviewCount.toNumber()).to.equal(0);
+
+    await program.methods.removeModerator(admin).rpc();
+  });
+
+  it("Emits events for the reviews and rewards", async () => {
+    const events = async (signature: string) => {
+      const transaction = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return parseMovieReviewEvents(
+        transaction.meta.logMessages,
+        program.programId
+      );
+    };
+    const movieId = movieIdFromTitle("Oldboy");
+    const reviewPda = getMovieReviewPdaById(
+      movieId,
+      provider.wallet.publicKey,
+      program.programId
+    );
+
+    let signature = await program.methods
+      .addMovieReviewV2(movieId, "Oldboy", "Hallway", 5)
+      .rpc({ commitment: "confirmed" });
+    let [added, minted] = await events(signature);
+    expect(added.name).to.equal("reviewAdded");
+    expect(added.data.review.toBase58()).to.equal(reviewPda.toBase58());
+    expect(added.data.movieId).to.eql(movieId);
+    expect(added.data.rating).to.equal(5);
+    expect(minted.name).to.equal("rewardMinted");
+    expect(minted.data.amount.toString()).to.equal(rewardAmount.toString());
+
+    signature = await program.methods
+      .updateMovieReviewV2(movieId, "Octopus", 4)
+      .rpc({ commitment: "confirmed" });
+    const [updated] = await events(signature);
+    expect(updated.name).to.equal("reviewUpdated");
+    expect(updated.data.oldRating).to.equal(5);
+    expect(updated.data.rating).to.equal(4);
+    expect(updated.data.oldDescription).to.equal("Hallway");
+
+    signature = await program.methods
+      .deleteMovieReviewV2(movieId)
+      .rpc({ commitment: "confirmed" });
+    const [deleted] = await events(signature);
+    expect(deleted.name).to.equal("reviewDeleted");
+    expect(deleted.data.rating).to.equal(4);
+    expect(deleted.data.burnedReward.toNumber()).to.equal(0);
+  });
+
+  it("Only the admin updates the reward amount", async () => {
+    const other = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .updateRewardAmount(new anchor.BN(1))
+        .accounts({ admin: other.publicKey })
+        .signers([other])
+        .rpc();
+      expect.fail("The reward amount was updated by someone else");
+    } catch (error) {
+      expect(error.message).to.include("Unauthorized");
+    }
+
+    await program.methods.updateRewardAmount(rewardAmount).rpc();
   });
 
   it("Movie review is updated", async () => {
@@ -66,6 +805,25 @@ describe("movie-review-program", () => {
     expect(movieReviewAccount.title === movieReview.title);
     expect(movieReviewAccount.description === newDescription);
     expect(movieReviewAccount.rating === newRating);
+
+    // The previous version is kept in the history
+    expect(movieReviewAccount.editCount.toNumber()).to.equal(1);
+    const revision = await program.account.reviewRevision.fetch(
+      getRevisionPda(movieReviewOrigin, new anchor.BN(0), program.programId)
+    );
+    expect(revision.rating).to.equal(movieReview.rating);
+    expect(revision.description).to.equal(movieReview.description);
+    expect(revision.slot.toNumber()).to.be.lessThan(
+      movieReviewAccount.updatedSlot.toNumber()
+    );
+
+    // The old rating is replaced on the aggregate
+    const movie = await program.account.movie.fetch(moviePda);
+    expect(movie.reviewCount.toNumber()).to.equal(1);
+    expect(movie.ratingSum.toNumber()).to.equal(newRating);
+    expect(movie.ratingHistogram.map((count) => count.toNumber())).to.eql([
+      0, 0, 0, 0, 1,
+    ]);
   });
 
   it("Movie review is deleted", async () => {
@@ -79,5 +837,55 @@ describe("movie-review-program", () => {
       // Verifique se o erro é o esperado
       expect(error.message).to.include("Account does not exist or has no data");
     }
+
+    const movie = await program.account.movie.fetch(moviePda);
+    expect(movie.reviewCount.toNumber()).to.equal(0);
+    expect(movie.ratingSum.toNumber()).to.equal(0);
+  });
+
+  it("A review added again doesn't take the comments of the deleted one", async () => {
+    await program.methods
+      .addMovieReview(movieReview.title, "Second viewing", 3)
+      .rpc();
+    const review = await program.account.movieAccountState.fetch(
+      movieReviewPda
+    );
+    expect(review.origin.toBase58()).to.not.equal(movieReviewOrigin.toBase58());
+    expect(review.commentCount.toNumber()).to.equal(0);
+
+    // The comments of the deleted review stay at its origin
+    await program.methods
+      .addComment("Welcome back", null)
+      .accounts({ movieReview: movieReviewPda })
+      .rpc();
+    const comment = await program.account.comment.fetch(
+      getCommentPda(review.origin, new anchor.BN(0), program.programId)
+    );
+    expect(comment.content).to.equal("Welcome back");
+    const old = await program.account.comment.fetch(
+      getCommentPda(movieReviewOrigin, new anchor.BN(1), program.programId)
+    );
+    expect(old.review.toBase58()).to.equal(movieReviewOrigin.toBase58());
+  });
+
+  it("A review added again starts a new edit history", async () => {
+    await program.methods
+      .updateMovieReview(movieReview.title, "Third viewing", 4)
+      .rpc();
+    const review = await program.account.movieAccountState.fetch(
+      movieReviewPda
+    );
+    expect(review.editCount.toNumber()).to.equal(1);
+    const revision = await program.account.reviewRevision.fetch(
+      getRevisionPda(review.origin, new anchor.BN(0), program.programId)
+    );
+    expect(revision.rating).to.equal(3);
+    expect(revision.description).to.equal("Second viewing");
+
+    // The history of the deleted review is kept at its origin
+    const old = await program.account.reviewRevision.fetch(
+      getRevisionPda(movieReviewOrigin, new anchor.BN(0), program.programId)
+    );
+    expect(old.description).to.equal(movieReview.description);
   });
 });
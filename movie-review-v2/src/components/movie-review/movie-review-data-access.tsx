@@ -2,9 +2,17 @@
 
 import {
   MOVIE_REVIEW_PROGRAM_ID as programId,
+  getCommentPda,
+  getMoviePda,
+  getMoviePdaById,
   getMovieReviewProgram,
+  hasMovieId,
+  isActive,
+  ReviewStatus,
+  movieIdFromTitle,
   getMovieReviewProgramId,
 } from "@project/anchor";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 import { useConnection } from "@solana/wallet-adapter-react";
 import {
   Cluster,
@@ -19,13 +27,29 @@ import { useCluster } from "../cluster/cluster-data-access";
 import { useAnchorProvider } from "../solana/solana-provider";
 import { useTransactionToast } from "../ui/ui-layout";
 import { useMemo } from "react";
-import { ProgramAccount } from "@coral-xyz/anchor";
+import { BN, ProgramAccount } from "@coral-xyz/anchor";
+
+// 10 tokens for each review, the mint has 6 decimals
+const REWARD_AMOUNT = new BN(10 * 10 ** 6);
 
 export type MovieReview = {
   reviewer: PublicKey;
   rating: number;
   title: string;
   description: string;
+  movieId: number[]; // zero on the reviews keyed by title
+  commentCount: BN;
+  helpfulVotes: BN;
+  unhelpfulVotes: BN;
+  deposit: BN;
+  reward: BN;
+  unlocksAt: BN;
+  flagCount: BN;
+  pendingFlags: BN; // the review can't be deleted until a moderator looks at them
+  status: ReviewStatus;
+  origin: PublicKey; // unique to the review and kept when it's moved, keys its comments and revisions
+  editCount: BN;
+  updatedSlot: BN;
 };
 
 export function useMovieReviewProgram() {
@@ -41,7 +65,14 @@ export function useMovieReviewProgram() {
 
   const accounts = useQuery({
     queryKey: ["movie-review", "all", { cluster }],
-    queryFn: () => program.account.movieAccountState.all(),
+    // The hidden and removed reviews are only visible on the explorer, and the reviews with the layout of the
+    // first deployment can't be decoded until they're migrated
+    queryFn: async () =>
+      (
+        await program.account.movieAccountState.all([
+          { dataSize: program.account.movieAccountState.size },
+        ])
+      ).filter(({ account }) => isActive(account)),
   });
 
   const programAccount = useQuery({
@@ -58,7 +89,7 @@ export function useMovieReviewProgram() {
   });
 
   const initializeTokenMint = useMutation({
-    mutationFn: () => program.methods.initializeTokenMint().rpc(),
+    mutationFn: () => program.methods.initializeTokenMint(REWARD_AMOUNT).rpc(),
 
     onSuccess: (signature) => {
       transactionToast(signature);
@@ -74,12 +105,19 @@ export function useMovieReviewProgram() {
       rating: number;
     }) =>
       program.methods
-        .addMovieReview(params.title, params.description, params.rating)
+        .addMovieReviewV2(
+          movieIdFromTitle(params.title),
+          params.title,
+          params.description,
+          params.rating
+        )
         .rpc(),
     onSuccess: (tx) => {
       transactionToast(tx);
       return accounts.refetch();
     },
+    onError: () =>
+      toast.error("Failed to add movie review, wait for the cooldown"),
   });
 
   return {
@@ -99,34 +137,194 @@ export function useMovieReviewProgramAccount({
   account: ProgramAccount<MovieReview>;
 }) {
   const transactionToast = useTransactionToast();
-  const { accounts, program } = useMovieReviewProgram();
+  const { accounts, program, programId } = useMovieReviewProgram();
 
-  const updateMovieReview = useMutation({
-    mutationFn: (params: { description: string; rating: number }) =>
+  // Reviews created before the movie ids are keyed by title
+  const keyedById = hasMovieId(account.account);
+
+  // The aggregate of all the reviews of the same movie
+  const movie = useQuery({
+    queryKey: ["movie", { review: account.publicKey.toString() }],
+    queryFn: () =>
+      program.account.movie.fetchNullable(
+        keyedById
+          ? getMoviePdaById(account.account.movieId, programId)
+          : getMoviePda(account.account.title, programId)
+      ),
+  });
+
+  // The comments of the review, the origin of the review is the first field after the discriminator
+  const comments = useQuery({
+    queryKey: ["comments", { review: account.account.origin.toString() }],
+    queryFn: async () =>
+      (
+        await program.account.comment.all([
+          { memcmp: { offset: 8, bytes: account.account.origin.toBase58() } },
+        ])
+      ).sort((a, b) => a.account.index.cmp(b.account.index)),
+  });
+
+  // The previous versions of the review, oldest first
+  const revisions = useQuery({
+    queryKey: ["revisions", { review: account.account.origin.toString() }],
+    queryFn: async () =>
+      (
+        await program.account.reviewRevision.all([
+          { memcmp: { offset: 8, bytes: account.account.origin.toBase58() } },
+        ])
+      ).sort((a, b) => a.account.index.cmp(b.account.index)),
+    enabled: !account.account.editCount.isZero(),
+  });
+
+  const addComment = useMutation({
+    mutationFn: (params: { content: string; parent: BN | null }) =>
       program.methods
-        .updateMovieReview(
-          account.account.title,
-          params.description,
-          params.rating
-        )
+        .addComment(params.content, params.parent)
+        .accounts({ movieReview: account.publicKey })
+        .rpc(),
+    onSuccess: (tx) => {
+      transactionToast(tx);
+      return Promise.all([accounts.refetch(), comments.refetch()]);
+    },
+  });
+
+  const editComment = useMutation({
+    mutationFn: (params: { index: BN; content: string }) =>
+      program.methods
+        .editComment(params.content)
+        .accountsPartial({
+          comment: getCommentPda(
+            account.account.origin,
+            params.index,
+            programId
+          ),
+        })
+        .rpc(),
+    onSuccess: (tx) => {
+      transactionToast(tx);
+      return comments.refetch();
+    },
+  });
+
+  const deleteComment = useMutation({
+    mutationFn: (index: BN) =>
+      program.methods
+        .deleteComment()
+        .accountsPartial({
+          comment: getCommentPda(account.account.origin, index, programId),
+        })
+        .rpc(),
+    onSuccess: (tx) => {
+      transactionToast(tx);
+      return comments.refetch();
+    },
+  });
+
+  const voteReview = useMutation({
+    mutationFn: (helpful: boolean) =>
+      program.methods
+        .voteReview(helpful)
+        .accounts({ movieReview: account.publicKey })
         .rpc(),
     onSuccess: (tx) => {
       transactionToast(tx);
       return accounts.refetch();
     },
+    onError: () => toast.error("You can vote once on the reviews of others"),
   });
 
-  const deleteMovieReview = useMutation({
+  const flagReview = useMutation({
     mutationFn: () =>
-      program.methods.deleteMovieReview(account.account.title).rpc(),
+      program.methods
+        .flagReview()
+        .accounts({ movieReview: account.publicKey })
+        .rpc(),
     onSuccess: (tx) => {
       transactionToast(tx);
       return accounts.refetch();
     },
+    onError: () => toast.error("You can flag a review once"),
+  });
+
+  const updateMovieReview = useMutation({
+    mutationFn: (params: { description: string; rating: number }) =>
+      keyedById
+        ? program.methods
+            .updateMovieReviewV2(
+              account.account.movieId,
+              params.description,
+              params.rating
+            )
+            .rpc()
+        : program.methods
+            .updateMovieReview(
+              account.account.title,
+              params.description,
+              params.rating
+            )
+            .rpc(),
+    onSuccess: (tx) => {
+      transactionToast(tx);
+      return Promise.all([
+        accounts.refetch(),
+        movie.refetch(),
+        revisions.refetch(),
+      ]);
+    },
+  });
+
+  // Only the reviews keyed by title have the title in their address
+  const moveReview = useMutation({
+    mutationFn: (newTitle: string) =>
+      program.methods.moveReview(account.account.title, newTitle).rpc(),
+    onSuccess: (tx) => {
+      transactionToast(tx);
+      return Promise.all([accounts.refetch(), movie.refetch()]);
+    },
+  });
+
+  // A closed token account is omitted, the program then owes the reward of a review deleted during its lock period
+  const deleteMovieReview = useMutation({
+    mutationFn: async () => {
+      const tokenAccount = getAssociatedTokenAddressSync(
+        PublicKey.findProgramAddressSync([Buffer.from("mint")], programId)[0],
+        account.account.reviewer
+      );
+      const deleteAccounts = {
+        tokenAccount: (await program.provider.connection.getAccountInfo(
+          tokenAccount
+        ))
+          ? tokenAccount
+          : null,
+      };
+      return keyedById
+        ? program.methods
+            .deleteMovieReviewV2(account.account.movieId)
+            .accountsPartial(deleteAccounts)
+            .rpc()
+        : program.methods
+            .deleteMovieReview(account.account.title)
+            .accountsPartial(deleteAccounts)
+            .rpc();
+    },
+    onSuccess: (tx) => {
+      transactionToast(tx);
+      return Promise.all([accounts.refetch(), movie.refetch()]);
+    },
   });
 
   return {
+    movie,
+    comments,
+    revisions,
+    voteReview,
+    flagReview,
+    addComment,
+    editComment,
+    deleteComment,
+    keyedById,
     updateMovieReview,
+    moveReview,
     deleteMovieReview,
   };
 }
@@ -1,6 +1,7 @@
 "use client";
 
 import { useMemo } from "react";
+import { averageRating } from "@project/anchor";
 import {
   MovieReview,
   useMovieReviewProgram,
@@ -8,7 +9,8 @@ import {
 } from "./movie-review-data-access";
 import { ExplorerLink } from "../cluster/cluster-ui";
 import { ellipsify } from "../ui/ui-layout";
-import { ProgramAccount } from "@coral-xyz/anchor";
+import { BN, ProgramAccount } from "@coral-xyz/anchor";
+import { useWallet } from "@solana/wallet-adapter-react";
 import { quickDialogForm } from "../ui/quickDialogForm";
 import Swal from "sweetalert2";
 
@@ -100,11 +102,87 @@ function MovieReviewCard({
 }: {
   account: ProgramAccount<MovieReview>;
 }) {
-  const { updateMovieReview, deleteMovieReview } = useMovieReviewProgramAccount(
-    {
-      account,
-    }
-  );
+  const { publicKey } = useWallet();
+  const {
+    movie,
+    comments,
+    revisions,
+    voteReview,
+    flagReview,
+    addComment,
+    editComment,
+    deleteComment,
+    keyedById,
+    updateMovieReview,
+    moveReview,
+    deleteMovieReview,
+  } = useMovieReviewProgramAccount({
+    account,
+  });
+
+  const handleComment = async (parent: BN | null) => {
+    const [content] = await quickDialogForm({
+      title: parent ? "Reply" : "Comment",
+      inputs: [{ label: "Comment", type: "textarea" }],
+    });
+
+    addComment.mutateAsync({ content, parent });
+  };
+
+  const handleEditComment = async (index: BN) => {
+    const [content] = await quickDialogForm({
+      title: "Edit Comment",
+      inputs: [{ label: "Comment", type: "textarea" }],
+    });
+
+    editComment.mutateAsync({ index, content });
+  };
+
+  // The replies are listed under their parent, the comments are sorted by index
+  const replies = (parent: BN | null) =>
+    (comments.data ?? []).filter(({ account: comment }) =>
+      parent ? comment.parent?.eq(parent) : comment.parent === null
+    );
+
+  const renderComments = (parent: BN | null) =>
+    replies(parent).map(({ publicKey: address, account: comment }) => (
+      <div key={address.toString()} className="text-left pl-4 border-l">
+        <p className="text-sm">
+          <span className="opacity-60">
+            {ellipsify(comment.commenter.toString())}:
+          </span>{" "}
+          {comment.content}
+        </p>
+        <div className="flex gap-2">
+          <button
+            className="btn btn-xs btn-ghost"
+            onClick={() => handleComment(comment.index)}
+            disabled={addComment.isPending}
+          >
+            Reply
+          </button>
+          {publicKey && comment.commenter.equals(publicKey) && (
+            <>
+              <button
+                className="btn btn-xs btn-ghost"
+                onClick={() => handleEditComment(comment.index)}
+                disabled={editComment.isPending}
+              >
+                Edit
+              </button>
+              <button
+                className="btn btn-xs btn-ghost"
+                onClick={() => deleteComment.mutateAsync(comment.index)}
+                disabled={deleteComment.isPending}
+              >
+                Delete
+              </button>
+            </>
+          )}
+        </div>
+        {renderComments(comment.index)}
+      </div>
+    ));
 
   const handleUpdate = async () => {
     const [description, rating] = await quickDialogForm({
@@ -121,6 +199,15 @@ function MovieReviewCard({
     });
   };
 
+  const handleRename = async () => {
+    const [title] = await quickDialogForm({
+      title: "Rename Movie Review",
+      inputs: [{ label: "Title", type: "text" }],
+    });
+
+    moveReview.mutateAsync(title);
+  };
+
   const handleDelete = async () => {
     await Swal.fire({
       title: "Are you sure you want to delete this movie review?",
@@ -154,6 +241,46 @@ function MovieReviewCard({
           </h2>
           <p>{account.account.description}</p>
           <p>{account.account.rating}</p>
+          {movie.data && averageRating(movie.data) !== null && (
+            <p className="text-sm">
+              Average: {averageRating(movie.data)?.toFixed(1)} (
+              {movie.data.reviewCount.toString()} reviews)
+            </p>
+          )}
+          {!!revisions.data?.length && (
+            <details className="text-sm text-left">
+              <summary>Edited {account.account.editCount.toString()} times</summary>
+              {revisions.data.map(({ publicKey: address, account: revision }) => (
+                <p key={address.toString()} className="opacity-60">
+                  {revision.rating}: {revision.description} (slot{" "}
+                  {revision.slot.toString()})
+                </p>
+              ))}
+            </details>
+          )}
+          <div className="flex gap-2 justify-center">
+            <button
+              className="btn btn-xs btn-ghost"
+              onClick={() => voteReview.mutateAsync(true)}
+              disabled={voteReview.isPending}
+            >
+              Helpful ({account.account.helpfulVotes.toString()})
+            </button>
+            <button
+              className="btn btn-xs btn-ghost"
+              onClick={() => voteReview.mutateAsync(false)}
+              disabled={voteReview.isPending}
+            >
+              Unhelpful ({account.account.unhelpfulVotes.toString()})
+            </button>
+            <button
+              className="btn btn-xs btn-ghost"
+              onClick={() => flagReview.mutateAsync()}
+              disabled={flagReview.isPending}
+            >
+              Flag
+            </button>
+          </div>
           <div className="card-actions justify-around">
             <button
               className="btn btn-xs lg:btn-md btn-outline"
@@ -162,6 +289,15 @@ function MovieReviewCard({
             >
               Update
             </button>
+            {!keyedById && (
+              <button
+                className="btn btn-xs lg:btn-md btn-outline"
+                onClick={handleRename}
+                disabled={moveReview.isPending}
+              >
+                Rename
+              </button>
+            )}
             <button
               className="btn btn-xs lg:btn-md btn-outline"
               onClick={handleDelete}
@@ -169,7 +305,17 @@ function MovieReviewCard({
             >
               Delete
             </button>
+            <button
+              className="btn btn-xs lg:btn-md btn-outline"
+              onClick={() => handleComment(null)}
+              disabled={addComment.isPending}
+            >
+              Comment
+            </button>
           </div>
+          {!!comments.data?.length && (
+            <div className="space-y-2">{renderComments(null)}</div>
+          )}
           <div className="text-center space-y-4">
             <p>
               <ExplorerLink